@@ -0,0 +1,140 @@
+//! Derive macros for rusty_gl, re-exported by the main crate.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Fields, LitInt, LitStr};
+
+/// Implements `rusty_gl::vertices::VertexLayout` for a struct with named fields.
+/// Every field becomes one attribute, with locations counting up from 0 in declaration order.
+/// Field types must implement `AttributeType`, i.e. have 1 to 4 scalar components, larger ones need to be split.
+///
+/// Fields can be configured with `#[vertex(...)]`:
+/// * `location = N` - Use location N, following fields continue from N + 1
+/// * `name = "vPos"` - Name of the shader attribute, defaults to the field name
+/// * `normalized` - Map integer values to 0..1 or -1..1 floats
+/// * `divisor = N` - Advance once per N instances instead of once per vertex
+/// * `skip` - Not an attribute, e.g. padding
+///
+/// # Examples
+/// ```
+/// #[derive(Vertex)]
+/// struct LitVertex {
+///     #[vertex(name = "vPos")]
+///     position: Vector3<f32>,
+///     normal: Vector3<f32>,
+///     #[vertex(normalized)]
+///     color: [u8; 4],
+///     material: u32,
+/// }
+/// ```
+#[proc_macro_derive(Vertex, attributes(vertex))]
+pub fn derive_vertex(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match vertex_layout(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+/// Settings of one field given by `#[vertex(...)]`
+#[derive(Default)]
+struct FieldOptions {
+    location: Option<u32>,
+    name: Option<String>,
+    normalized: bool,
+    divisor: u32,
+    skip: bool,
+}
+
+fn field_options(field: &syn::Field) -> syn::Result<FieldOptions> {
+    let mut options = FieldOptions::default();
+
+    for attribute in field.attrs.iter().filter(|a| a.path().is_ident("vertex")) {
+        attribute.parse_nested_meta(|meta| {
+            if meta.path.is_ident("location") {
+                let location: LitInt = meta.value()?.parse()?;
+                options.location = Some(location.base10_parse()?);
+            } else if meta.path.is_ident("name") {
+                let name: LitStr = meta.value()?.parse()?;
+                options.name = Some(name.value());
+            } else if meta.path.is_ident("normalized") {
+                options.normalized = true;
+            } else if meta.path.is_ident("divisor") {
+                let divisor: LitInt = meta.value()?.parse()?;
+                options.divisor = divisor.base10_parse()?;
+            } else if meta.path.is_ident("skip") {
+                options.skip = true;
+            } else {
+                return Err(
+                    meta.error("expected `location`, `name`, `normalized`, `divisor` or `skip`")
+                );
+            }
+            Ok(())
+        })?;
+    }
+
+    Ok(options)
+}
+
+fn vertex_layout(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    &input.ident,
+                    "Vertex can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "Vertex can only be derived for structs",
+            ))
+        }
+    };
+
+    let mut attributes = Vec::new();
+    let mut location = 0;
+
+    for field in fields {
+        let options = field_options(field)?;
+        if options.skip {
+            continue;
+        }
+
+        location = options.location.unwrap_or(location);
+        let ident = field.ident.as_ref().unwrap();
+        let ty = &field.ty;
+        let name = options.name.unwrap_or_else(|| ident.to_string());
+        let normalized = options.normalized;
+        let divisor = options.divisor;
+
+        attributes.push(quote! {
+            ::rusty_gl::vertices::Attribute::new(
+                #location,
+                <#ty as ::rusty_gl::vertices::AttributeType>::COUNT,
+                <#ty as ::rusty_gl::vertices::AttributeType>::GL_TYPE,
+                ::std::mem::offset_of!(Self, #ident),
+            )
+            .normalized(#normalized)
+            .divisor(#divisor)
+            .named(#name)
+        });
+
+        location += 1;
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::rusty_gl::vertices::VertexLayout for #ident #ty_generics #where_clause {
+            fn attributes() -> ::std::vec::Vec<::rusty_gl::vertices::Attribute> {
+                ::std::vec![#(#attributes),*]
+            }
+        }
+    })
+}
@@ -0,0 +1,320 @@
+//! This module implements a RGBA color type and a set of predefined colors.
+//! Colors are stored as sRGB encoded floats between 0 and 1, like they are written in hex codes,
+//! and can be converted from and to HSV, HSL, Oklab and linear RGB.
+
+use vector::{Vector3, Vector4};
+
+/// A color with red, green, blue and alpha channels between 0 and 1
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Color {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
+}
+
+pub const TRANSPARENT: Color = Color::new(0.0, 0.0, 0.0, 0.0);
+pub const WHITE: Color = Color::rgb(1.0, 1.0, 1.0);
+pub const BLACK: Color = Color::rgb(0.0, 0.0, 0.0);
+pub const RED: Color = Color::rgb(1.0, 0.0, 0.0);
+pub const GREEN: Color = Color::rgb(0.0, 1.0, 0.0);
+pub const BLUE: Color = Color::rgb(0.0, 0.0, 1.0);
+pub const YELLOW: Color = Color::rgb(1.0, 1.0, 0.0);
+pub const CYAN: Color = Color::rgb(0.0, 1.0, 1.0);
+pub const MAGENTA: Color = Color::rgb(1.0, 0.0, 1.0);
+pub const PURPLE: Color = Color::rgb(0.5, 0.0, 0.5);
+pub const ORANGE: Color = Color::rgb(1.0, 0.5, 0.0);
+pub const PINK: Color = Color::rgb(1.0, 0.0, 0.5);
+pub const LIME: Color = Color::rgb(0.0, 1.0, 0.5);
+pub const TEAL: Color = Color::rgb(0.5, 1.0, 0.5);
+
+impl Color {
+    pub const fn new(r: f32, g: f32, b: f32, a: f32) -> Color {
+        Color { r, g, b, a }
+    }
+
+    /// Creates an opaque color
+    pub const fn rgb(r: f32, g: f32, b: f32) -> Color {
+        Color::new(r, g, b, 1.0)
+    }
+
+    /// Creates a color from 8 bit channels
+    ///
+    /// # Examples
+    /// ```
+    /// let orange = Color::from_u8(255, 128, 0, 255);
+    /// ```
+    pub fn from_u8(r: u8, g: u8, b: u8, a: u8) -> Color {
+        Color::new(
+            r as f32 / 255.0,
+            g as f32 / 255.0,
+            b as f32 / 255.0,
+            a as f32 / 255.0,
+        )
+    }
+
+    /// The channels rounded to 8 bit values
+    pub fn to_u8(&self) -> [u8; 4] {
+        let convert = |c: f32| (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+        [
+            convert(self.r),
+            convert(self.g),
+            convert(self.b),
+            convert(self.a),
+        ]
+    }
+
+    /// Parses a hex color code. The leading '#' is optional.
+    ///
+    /// # Arguments
+    /// * `hex` - Code in the form RGB, RGBA, RRGGBB or RRGGBBAA
+    ///
+    /// # Examples
+    /// ```
+    /// let background = Color::from_hex("#1e1e2e")?;
+    /// let overlay = Color::from_hex("0008")?;
+    /// ```
+    pub fn from_hex(hex: &str) -> Result<Color, Box<dyn std::error::Error>> {
+        let digits = hex.strip_prefix('#').unwrap_or(hex);
+        if !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+            Err(format!("Invalid hex color \"{}\"", hex))?
+        }
+
+        let channel = |i: usize, width: usize| {
+            let value = u8::from_str_radix(&digits[i * width..(i + 1) * width], 16).unwrap();
+            // Short codes repeat every digit, so F becomes FF
+            if width == 1 {
+                value * 17
+            } else {
+                value
+            }
+        };
+
+        let [r, g, b, a] = match digits.len() {
+            3 => [channel(0, 1), channel(1, 1), channel(2, 1), 255],
+            4 => [channel(0, 1), channel(1, 1), channel(2, 1), channel(3, 1)],
+            6 => [channel(0, 2), channel(1, 2), channel(2, 2), 255],
+            8 => [channel(0, 2), channel(1, 2), channel(2, 2), channel(3, 2)],
+            _ => Err(format!(
+                "Invalid hex color \"{}\", expected 3, 4, 6 or 8 digits",
+                hex
+            ))?,
+        };
+
+        Ok(Color::from_u8(r, g, b, a))
+    }
+
+    /// The color as a hex code in the form #RRGGBBAA
+    pub fn to_hex(&self) -> String {
+        let [r, g, b, a] = self.to_u8();
+        format!("#{:02x}{:02x}{:02x}{:02x}", r, g, b, a)
+    }
+
+    /// Creates an opaque color from hue, saturation and value
+    ///
+    /// # Arguments
+    /// * `hue` - Angle in degrees, wraps around at 360
+    /// * `saturation` - Between 0 and 1
+    /// * `value` - Between 0 and 1
+    pub fn from_hsv(hue: f32, saturation: f32, value: f32) -> Color {
+        let chroma = value * saturation;
+        from_hue(hue, chroma, value - chroma)
+    }
+
+    /// Hue in degrees, saturation and value of the color
+    pub fn to_hsv(&self) -> [f32; 3] {
+        let (hue, max, min) = to_hue(self);
+        let saturation = if max > 0.0 { (max - min) / max } else { 0.0 };
+        [hue, saturation, max]
+    }
+
+    /// Creates an opaque color from hue, saturation and lightness
+    ///
+    /// # Arguments
+    /// * `hue` - Angle in degrees, wraps around at 360
+    /// * `saturation` - Between 0 and 1
+    /// * `lightness` - Between 0 and 1
+    pub fn from_hsl(hue: f32, saturation: f32, lightness: f32) -> Color {
+        let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+        from_hue(hue, chroma, lightness - chroma / 2.0)
+    }
+
+    /// Hue in degrees, saturation and lightness of the color
+    pub fn to_hsl(&self) -> [f32; 3] {
+        let (hue, max, min) = to_hue(self);
+        let lightness = (max + min) / 2.0;
+        let saturation = if max > min {
+            (max - min) / (1.0 - (2.0 * lightness - 1.0).abs())
+        } else {
+            0.0
+        };
+        [hue, saturation, lightness]
+    }
+
+    /// Creates an opaque color from the perceptual Oklab color space.
+    /// See https://bottosson.github.io/posts/oklab/
+    ///
+    /// # Arguments
+    /// * `lightness` - Perceived lightness between 0 and 1
+    /// * `a` - Green to red axis
+    /// * `b` - Blue to yellow axis
+    pub fn from_oklab(lightness: f32, a: f32, b: f32) -> Color {
+        let l = (lightness + 0.396_337_78 * a + 0.215_803_76 * b).powi(3);
+        let m = (lightness - 0.105_561_346 * a - 0.063_854_17 * b).powi(3);
+        let s = (lightness - 0.089_484_18 * a - 1.291_485_5 * b).powi(3);
+
+        Color::rgb(
+            4.076_741_7 * l - 3.307_711_6 * m + 0.230_969_94 * s,
+            -1.268_438 * l + 2.609_757_4 * m - 0.341_319_38 * s,
+            -0.004_196_086_3 * l - 0.703_418_6 * m + 1.707_614_7 * s,
+        )
+        .to_srgb()
+    }
+
+    /// Lightness, a and b of the color in the Oklab color space
+    pub fn to_oklab(&self) -> [f32; 3] {
+        let linear = self.to_linear();
+        let (r, g, b) = (linear.r, linear.g, linear.b);
+
+        let l = (0.412_221_46 * r + 0.536_332_55 * g + 0.051_445_995 * b).cbrt();
+        let m = (0.211_903_5 * r + 0.680_699_5 * g + 0.107_396_96 * b).cbrt();
+        let s = (0.088_302_46 * r + 0.281_718_85 * g + 0.629_978_7 * b).cbrt();
+
+        [
+            0.210_454_26 * l + 0.793_617_8 * m - 0.004_072_047 * s,
+            1.977_998_5 * l - 2.428_592_2 * m + 0.450_593_7 * s,
+            0.025_904_037 * l + 0.782_771_77 * m - 0.808_675_77 * s,
+        ]
+    }
+
+    /// Converts the sRGB encoded color into linear RGB, which is what lighting and blending math expects.
+    /// Alpha is left as is.
+    pub fn to_linear(&self) -> Color {
+        let convert = |c: f32| {
+            if c <= 0.04045 {
+                c / 12.92
+            } else {
+                ((c + 0.055) / 1.055).powf(2.4)
+            }
+        };
+        Color::new(convert(self.r), convert(self.g), convert(self.b), self.a)
+    }
+
+    /// Converts a linear RGB color back into sRGB encoding. Alpha is left as is.
+    pub fn to_srgb(&self) -> Color {
+        let convert = |c: f32| {
+            if c <= 0.003_130_8 {
+                c * 12.92
+            } else {
+                1.055 * c.powf(1.0 / 2.4) - 0.055
+            }
+        };
+        Color::new(convert(self.r), convert(self.g), convert(self.b), self.a)
+    }
+
+    /// Linearly interpolates every channel between two colors
+    ///
+    /// # Arguments
+    /// * `other` - The color returned for t = 1
+    /// * `t` - Interpolation factor between 0 and 1
+    pub fn lerp(&self, other: Color, t: f32) -> Color {
+        let lerp = |a: f32, b: f32| a + (b - a) * t;
+        Color::new(
+            lerp(self.r, other.r),
+            lerp(self.g, other.g),
+            lerp(self.b, other.b),
+            lerp(self.a, other.a),
+        )
+    }
+
+    /// Interpolates between two colors in the Oklab color space.
+    /// Unlike lerp, gradients keep an even brightness and don't turn gray in between.
+    ///
+    /// # Arguments
+    /// * `other` - The color returned for t = 1
+    /// * `t` - Interpolation factor between 0 and 1
+    ///
+    /// # Examples
+    /// ```
+    /// let gradient = (0..10).map(|i| color::BLUE.mix(color::YELLOW, i as f32 / 9.0));
+    /// ```
+    pub fn mix(&self, other: Color, t: f32) -> Color {
+        let [l1, a1, b1] = self.to_oklab();
+        let [l2, a2, b2] = other.to_oklab();
+        Color::from_oklab(l1 + (l2 - l1) * t, a1 + (a2 - a1) * t, b1 + (b2 - b1) * t)
+            .with_alpha(self.a + (other.a - self.a) * t)
+    }
+
+    /// The same color with a different alpha
+    pub fn with_alpha(self, a: f32) -> Color {
+        Color { a, ..self }
+    }
+}
+
+/// Builds a color from the hue and the chroma and offset of the HSV and HSL models
+fn from_hue(hue: f32, chroma: f32, offset: f32) -> Color {
+    let sector = hue.rem_euclid(360.0) / 60.0;
+    let x = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+
+    let (r, g, b) = match sector as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+
+    Color::rgb(r + offset, g + offset, b + offset)
+}
+
+/// Returns the hue in degrees and the largest and smallest channel
+fn to_hue(color: &Color) -> (f32, f32, f32) {
+    let (r, g, b) = (color.r, color.g, color.b);
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let delta = max - min;
+
+    let hue = if delta == 0.0 {
+        0.0
+    } else if max == r {
+        60.0 * ((g - b) / delta).rem_euclid(6.0)
+    } else if max == g {
+        60.0 * ((b - r) / delta + 2.0)
+    } else {
+        60.0 * ((r - g) / delta + 4.0)
+    };
+
+    (hue, max, min)
+}
+
+impl From<Vector3<f32>> for Color {
+    fn from(v: Vector3<f32>) -> Color {
+        Color::rgb(v.x, v.y, v.z)
+    }
+}
+
+impl From<Vector4<f32>> for Color {
+    fn from(v: Vector4<f32>) -> Color {
+        Color::new(v.x, v.y, v.z, v.w)
+    }
+}
+
+impl From<Color> for Vector4<f32> {
+    fn from(c: Color) -> Vector4<f32> {
+        Vector4::new(c.r, c.g, c.b, c.a)
+    }
+}
+
+impl From<[f32; 4]> for Color {
+    fn from(c: [f32; 4]) -> Color {
+        Color::new(c[0], c[1], c[2], c[3])
+    }
+}
+
+impl From<Color> for [f32; 4] {
+    fn from(c: Color) -> [f32; 4] {
+        [c.r, c.g, c.b, c.a]
+    }
+}
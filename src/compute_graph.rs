@@ -0,0 +1,577 @@
+//! This module implements a small task graph for compute shaders.
+//! Passes declare the buffers and images they access, the graph orders them
+//! and inserts only the memory barriers which are actually needed in between.
+//!
+//! Passes are declared in their logical order. Two passes depend on each other
+//! if they access the same resource and at least one of them writes it.
+//! Independent passes are grouped, so a single barrier covers all of them.
+//! Write after read hazards only restrict the order, since OpenGL doesn't need
+//! a memory barrier to make a write follow an earlier read.
+
+use std::fmt;
+
+use vector::Vector3;
+
+use crate::{textures::Texture, ComputeShader, GPU, SSBO};
+
+/// A gpu resource tracked by the graph
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resource {
+    Buffer(u32),
+    Image(u32),
+}
+
+impl Resource {
+    /// Barrier which makes shader writes to this resource visible to later compute passes
+    fn barrier(&self) -> gl::types::GLbitfield {
+        match self {
+            Resource::Buffer(_) => gl::SHADER_STORAGE_BARRIER_BIT,
+            Resource::Image(_) => gl::SHADER_IMAGE_ACCESS_BARRIER_BIT,
+        }
+    }
+}
+
+impl fmt::Display for Resource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Resource::Buffer(id) => write!(f, "buffer {}", id),
+            Resource::Image(id) => write!(f, "image {}", id),
+        }
+    }
+}
+
+/// How a pass accesses a resource
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    Read,
+    Write,
+    ReadWrite,
+}
+
+impl Access {
+    fn reads(&self) -> bool {
+        *self != Access::Write
+    }
+
+    fn writes(&self) -> bool {
+        *self != Access::Read
+    }
+}
+
+/// Should be implemented by types which can be accessed by a pass
+pub trait GraphResource {
+    /// The resource as tracked by the graph
+    fn resource(&self) -> Resource;
+
+    /// Binds the resource to the given binding index before the pass is dispatched
+    fn bind_for(&self, binding: u32, access: Access);
+}
+
+impl<T: GPU> GraphResource for SSBO<T> {
+    fn resource(&self) -> Resource {
+        Resource::Buffer(self.id())
+    }
+
+    fn bind_for(&self, binding: u32, _: Access) {
+        self.bind(binding);
+    }
+}
+
+impl<T: Texture> GraphResource for T {
+    fn resource(&self) -> Resource {
+        Resource::Image(self.id())
+    }
+
+    fn bind_for(&self, binding: u32, access: Access) {
+        let access = match access {
+            Access::Read => gl::READ_ONLY,
+            Access::Write => gl::WRITE_ONLY,
+            Access::ReadWrite => gl::READ_WRITE,
+        };
+
+        self.bind_image(binding, 0, access, self.internal_format());
+    }
+}
+
+/// Size of a pass dispatch
+#[derive(Debug, Clone, Copy)]
+pub enum Dispatch {
+    /// Number of work groups, as passed to ComputeShader::dispatch
+    Groups(u32, u32, u32),
+    /// Number of threads, rounded up to whole work groups as in ComputeShader::dispatch_threads
+    Threads(u32, u32, u32),
+}
+
+/// Kind of conflict between two passes accessing the same resource
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HazardKind {
+    ReadAfterWrite,
+    WriteAfterWrite,
+    WriteAfterRead,
+}
+
+/// A conflict between two passes, which forces their order
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hazard {
+    pub kind: HazardKind,
+    pub resource: Resource,
+    pub producer: String,
+    pub consumer: String,
+    /// Memory barrier inserted because of this hazard, 0 if ordering alone suffices
+    pub barrier: gl::types::GLbitfield,
+}
+
+impl fmt::Display for Hazard {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let kind = match self.kind {
+            HazardKind::ReadAfterWrite => "RAW",
+            HazardKind::WriteAfterWrite => "WAW",
+            HazardKind::WriteAfterRead => "WAR",
+        };
+
+        write!(
+            f,
+            "{} hazard on {} between '{}' and '{}'",
+            kind, self.resource, self.producer, self.consumer
+        )?;
+
+        if self.barrier != 0 {
+            write!(f, ", barrier {}", barrier_to_string(self.barrier))?;
+        }
+
+        Ok(())
+    }
+}
+
+struct Binding<'a> {
+    resource: &'a dyn GraphResource,
+    binding: u32,
+    access: Access,
+}
+
+/// A single compute shader dispatch together with the resources it accesses
+pub struct Pass<'a> {
+    name: String,
+    shader: &'a ComputeShader,
+    dispatch: Dispatch,
+    bindings: Vec<Binding<'a>>,
+}
+
+impl<'a> Pass<'a> {
+    /// Declares a resource which is only read by this pass
+    ///
+    /// # Arguments
+    /// * `resource` - The accessed resource
+    /// * `binding` - Binding index the shader expects the resource at
+    pub fn read<R: GraphResource>(&mut self, resource: &'a R, binding: u32) -> &mut Self {
+        self.access(resource, binding, Access::Read)
+    }
+
+    /// Declares a resource which is only written by this pass
+    ///
+    /// # Arguments
+    /// * `resource` - The accessed resource
+    /// * `binding` - Binding index the shader expects the resource at
+    pub fn write<R: GraphResource>(&mut self, resource: &'a R, binding: u32) -> &mut Self {
+        self.access(resource, binding, Access::Write)
+    }
+
+    /// Declares a resource which is read and written by this pass
+    ///
+    /// # Arguments
+    /// * `resource` - The accessed resource
+    /// * `binding` - Binding index the shader expects the resource at
+    pub fn read_write<R: GraphResource>(&mut self, resource: &'a R, binding: u32) -> &mut Self {
+        self.access(resource, binding, Access::ReadWrite)
+    }
+
+    fn access<R: GraphResource>(
+        &mut self,
+        resource: &'a R,
+        binding: u32,
+        access: Access,
+    ) -> &mut Self {
+        self.bindings.push(Binding {
+            resource,
+            binding,
+            access,
+        });
+        self
+    }
+
+    fn accesses(&self, resource: Resource) -> Option<Access> {
+        let mut reads = false;
+        let mut writes = false;
+
+        for binding in self.bindings.iter() {
+            if binding.resource.resource() == resource {
+                reads |= binding.access.reads();
+                writes |= binding.access.writes();
+            }
+        }
+
+        match (reads, writes) {
+            (true, true) => Some(Access::ReadWrite),
+            (true, false) => Some(Access::Read),
+            (false, true) => Some(Access::Write),
+            (false, false) => None,
+        }
+    }
+
+    fn resources(&self) -> Vec<Resource> {
+        let mut resources: Vec<Resource> = Vec::new();
+        for binding in self.bindings.iter() {
+            let resource = binding.resource.resource();
+            if !resources.contains(&resource) {
+                resources.push(resource);
+            }
+        }
+        resources
+    }
+}
+
+/// Passes which can run without barriers between them, preceded by a single barrier
+struct Level {
+    barrier: gl::types::GLbitfield,
+    passes: Vec<usize>,
+}
+
+pub struct ComputeGraph<'a> {
+    passes: Vec<Pass<'a>>,
+    outputs: Vec<(Resource, gl::types::GLbitfield)>,
+    debug: bool,
+}
+
+impl<'a> Default for ComputeGraph<'a> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> ComputeGraph<'a> {
+    pub fn new() -> ComputeGraph<'a> {
+        ComputeGraph {
+            passes: Vec::new(),
+            outputs: Vec::new(),
+            debug: false,
+        }
+    }
+
+    /// Prints every hazard and inserted barrier when the graph is run
+    pub fn set_debug(&mut self, debug: bool) -> &mut Self {
+        self.debug = debug;
+        self
+    }
+
+    /// Adds a new pass to the graph. Passes are declared in their logical order.
+    ///
+    /// # Arguments
+    /// * `name` - Name of the pass, used for hazard reports
+    /// * `shader` - The compute shader to dispatch
+    /// * `dispatch` - Number of work groups or threads to dispatch
+    ///
+    /// # Examples
+    /// ```
+    /// let mut graph = ComputeGraph::new();
+    ///
+    /// graph
+    ///     .add_pass("forces", &forces, Dispatch::Threads(1000, 1, 1))
+    ///     .read(&positions, 0)
+    ///     .write(&velocities, 1);
+    ///
+    /// graph
+    ///     .add_pass("integrate", &integrate, Dispatch::Threads(1000, 1, 1))
+    ///     .read(&velocities, 1)
+    ///     .read_write(&positions, 0);
+    ///
+    /// graph.output(&positions, gl::VERTEX_ATTRIB_ARRAY_BARRIER_BIT);
+    /// graph.run()?;
+    /// ```
+    pub fn add_pass(
+        &mut self,
+        name: &str,
+        shader: &'a ComputeShader,
+        dispatch: Dispatch,
+    ) -> &mut Pass<'a> {
+        self.passes.push(Pass {
+            name: name.to_string(),
+            shader,
+            dispatch,
+            bindings: Vec::new(),
+        });
+
+        self.passes.last_mut().unwrap()
+    }
+
+    /// Declares how a resource is used after the graph has run, e.g. as vertex data or by SSBO::load.
+    /// The matching barrier is issued after the last pass writing the resource.
+    ///
+    /// # Arguments
+    /// * `resource` - The resource used after the graph
+    /// * `barrier` - Barrier for the following usage. See https://www.khronos.org/registry/OpenGL-Refpages/gl4/html/glMemoryBarrier.xhtml
+    pub fn output<R: GraphResource>(
+        &mut self,
+        resource: &R,
+        barrier: gl::types::GLbitfield,
+    ) -> &mut Self {
+        self.outputs.push((resource.resource(), barrier));
+        self
+    }
+
+    /// All hazards between the passes of this graph
+    pub fn hazards(&self) -> Vec<Hazard> {
+        self.dependencies()
+            .into_iter()
+            .map(|(_, _, hazard)| hazard)
+            .collect()
+    }
+
+    /// Hazards together with the indices of the producing and consuming pass
+    fn dependencies(&self) -> Vec<(usize, usize, Hazard)> {
+        let mut dependencies = Vec::new();
+
+        for (consumer_index, consumer) in self.passes.iter().enumerate() {
+            for resource in consumer.resources() {
+                let access = consumer.accesses(resource).unwrap();
+
+                // Earlier passes touching the resource, closest first
+                let earlier = self.passes[..consumer_index]
+                    .iter()
+                    .enumerate()
+                    .rev()
+                    .filter_map(|(index, pass)| pass.accesses(resource).map(|a| (index, a)));
+
+                let mut hazard = |producer: usize, kind: HazardKind| {
+                    let barrier = match kind {
+                        HazardKind::WriteAfterRead => 0,
+                        _ => resource.barrier(),
+                    };
+
+                    dependencies.push((
+                        producer,
+                        consumer_index,
+                        Hazard {
+                            kind,
+                            resource,
+                            producer: self.passes[producer].name.clone(),
+                            consumer: consumer.name.clone(),
+                            barrier,
+                        },
+                    ));
+                };
+
+                let last_writer = earlier.clone().find(|(_, a)| a.writes());
+
+                if access.reads() {
+                    if let Some((producer, _)) = last_writer {
+                        hazard(producer, HazardKind::ReadAfterWrite);
+                    }
+                }
+
+                if access.writes() {
+                    // Readers since the last write are only ordered after that write,
+                    // not after each other, so the write has to wait for every one of them
+                    for (producer, _) in earlier.clone().take_while(|(_, a)| !a.writes()) {
+                        hazard(producer, HazardKind::WriteAfterRead);
+                    }
+
+                    if let Some((producer, _)) = last_writer {
+                        if !access.reads() {
+                            hazard(producer, HazardKind::WriteAfterWrite);
+                        }
+                    }
+                }
+            }
+        }
+
+        dependencies
+    }
+
+    /// Names of the passes in the order they are dispatched, grouped into levels.
+    /// The passes of a level are independent of each other, a barrier may be issued between levels.
+    pub fn levels(&self) -> Vec<Vec<&str>> {
+        self.schedule()
+            .into_iter()
+            .map(|level| {
+                level
+                    .passes
+                    .into_iter()
+                    .map(|index| self.passes[index].name.as_str())
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Groups the passes into levels of independent passes
+    fn schedule(&self) -> Vec<Level> {
+        // Level of every pass, one past the highest level of any pass it depends on
+        let mut levels = vec![0; self.passes.len()];
+        for (producer, consumer, _) in self.dependencies() {
+            levels[consumer] = levels[consumer].max(levels[producer] + 1);
+        }
+
+        let count = levels.iter().max().map_or(0, |max| max + 1);
+        let mut schedule: Vec<Level> = (0..count)
+            .map(|_| Level {
+                barrier: 0,
+                passes: Vec::new(),
+            })
+            .collect();
+
+        for (index, level) in levels.iter().enumerate() {
+            schedule[*level].passes.push(index);
+        }
+
+        // Writes which haven't been made visible by a barrier yet
+        let mut pending: Vec<Resource> = Vec::new();
+        for level in schedule.iter_mut() {
+            for index in level.passes.iter() {
+                for resource in self.passes[*index].resources() {
+                    if pending.contains(&resource) {
+                        level.barrier |= resource.barrier();
+                    }
+                }
+            }
+
+            if level.barrier != 0 {
+                pending.retain(|resource| level.barrier & resource.barrier() == 0);
+            }
+
+            for index in level.passes.iter() {
+                let pass = &self.passes[*index];
+                for resource in pass.resources() {
+                    if pass.accesses(resource).unwrap().writes() && !pending.contains(&resource) {
+                        pending.push(resource);
+                    }
+                }
+            }
+        }
+
+        schedule
+    }
+
+    /// Work group counts of every pass, checked against the limits of the gpu
+    fn work_groups(&self) -> Result<Vec<Vector3<u32>>, Box<dyn std::error::Error>> {
+        let max_count = ComputeShader::max_work_group_count();
+
+        self.passes
+            .iter()
+            .map(|pass| match pass.dispatch {
+                Dispatch::Groups(x, y, z) => {
+                    if x > max_count.x || y > max_count.y || z > max_count.z {
+                        Err(format!(
+                            "Pass '{}' dispatches ({}, {}, {}) work groups, \
+                            which exceeds GL_MAX_COMPUTE_WORK_GROUP_COUNT ({}, {}, {})",
+                            pass.name, x, y, z, max_count.x, max_count.y, max_count.z
+                        ))?
+                    }
+                    Ok(Vector3::new(x, y, z))
+                }
+                Dispatch::Threads(x, y, z) => {
+                    let groups = pass
+                        .shader
+                        .work_groups_for(x, y, z)
+                        .map_err(|e| format!("Pass '{}': {}", pass.name, e))?;
+                    Ok(groups)
+                }
+            })
+            .collect()
+    }
+
+    /// Dispatches all passes in dependency order with the minimal barriers in between.
+    /// The work group counts of all passes are checked first, so an error leaves nothing half run.
+    pub fn run(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let work_groups = self.work_groups()?;
+
+        if self.debug {
+            for hazard in self.hazards() {
+                println!("Compute graph: {}", hazard);
+            }
+        }
+
+        for level in self.schedule() {
+            if level.barrier != 0 {
+                if self.debug {
+                    println!(
+                        "Compute graph: barrier {} before {}",
+                        barrier_to_string(level.barrier),
+                        level
+                            .passes
+                            .iter()
+                            .map(|index| format!("'{}'", self.passes[*index].name))
+                            .collect::<Vec<String>>()
+                            .join(", ")
+                    );
+                }
+
+                unsafe { gl::MemoryBarrier(level.barrier) };
+            }
+
+            for index in level.passes {
+                let pass = &self.passes[index];
+
+                for binding in pass.bindings.iter() {
+                    binding.resource.bind_for(binding.binding, binding.access);
+                }
+
+                let groups = &work_groups[index];
+                pass.shader.launch(groups.x, groups.y, groups.z);
+            }
+        }
+
+        let mut barrier = 0;
+        for (resource, output_barrier) in self.outputs.iter() {
+            let written = self
+                .passes
+                .iter()
+                .any(|pass| pass.accesses(*resource).is_some_and(|a| a.writes()));
+
+            if written {
+                barrier |= output_barrier;
+            } else if self.debug {
+                println!(
+                    "Compute graph: output {} is never written by any pass",
+                    resource
+                );
+            }
+        }
+
+        if barrier != 0 {
+            if self.debug {
+                println!(
+                    "Compute graph: barrier {} for outputs",
+                    barrier_to_string(barrier)
+                );
+            }
+
+            unsafe { gl::MemoryBarrier(barrier) };
+        }
+
+        Ok(())
+    }
+}
+
+fn barrier_to_string(barrier: gl::types::GLbitfield) -> String {
+    const BITS: [(gl::types::GLbitfield, &str); 14] = [
+        (gl::VERTEX_ATTRIB_ARRAY_BARRIER_BIT, "VERTEX_ATTRIB_ARRAY"),
+        (gl::ELEMENT_ARRAY_BARRIER_BIT, "ELEMENT_ARRAY"),
+        (gl::UNIFORM_BARRIER_BIT, "UNIFORM"),
+        (gl::TEXTURE_FETCH_BARRIER_BIT, "TEXTURE_FETCH"),
+        (gl::SHADER_IMAGE_ACCESS_BARRIER_BIT, "SHADER_IMAGE_ACCESS"),
+        (gl::COMMAND_BARRIER_BIT, "COMMAND"),
+        (gl::PIXEL_BUFFER_BARRIER_BIT, "PIXEL_BUFFER"),
+        (gl::TEXTURE_UPDATE_BARRIER_BIT, "TEXTURE_UPDATE"),
+        (gl::BUFFER_UPDATE_BARRIER_BIT, "BUFFER_UPDATE"),
+        (gl::FRAMEBUFFER_BARRIER_BIT, "FRAMEBUFFER"),
+        (gl::TRANSFORM_FEEDBACK_BARRIER_BIT, "TRANSFORM_FEEDBACK"),
+        (gl::ATOMIC_COUNTER_BARRIER_BIT, "ATOMIC_COUNTER"),
+        (gl::SHADER_STORAGE_BARRIER_BIT, "SHADER_STORAGE"),
+        (gl::CLIENT_MAPPED_BUFFER_BARRIER_BIT, "CLIENT_MAPPED_BUFFER"),
+    ];
+
+    BITS.iter()
+        .filter(|(bit, _)| barrier & bit != 0)
+        .map(|(_, name)| *name)
+        .collect::<Vec<&str>>()
+        .join(" | ")
+}
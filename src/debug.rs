@@ -7,6 +7,20 @@ pub fn enable() {
     }
 }
 
+/// Inserts a message of the library into the debug output, so it is only reported after enable()
+pub(crate) fn message(text: &str) {
+    unsafe {
+        gl::DebugMessageInsert(
+            gl::DEBUG_SOURCE_APPLICATION,
+            gl::DEBUG_TYPE_OTHER,
+            0,
+            gl::DEBUG_SEVERITY_MEDIUM,
+            text.len() as i32,
+            text.as_ptr() as *const i8,
+        );
+    }
+}
+
 extern "system" fn gl_callback(
     source: u32,
     gltype: u32,
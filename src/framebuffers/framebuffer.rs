@@ -0,0 +1,265 @@
+use std::rc::Rc;
+
+use crate::{
+    textures::{Texture, Texture2D},
+    Color, Window,
+};
+
+use super::{FramebufferBuilder, Renderbuffer};
+
+/// Image attached to a framebuffer
+pub enum Attachment {
+    Texture(Rc<Texture2D>),
+    Renderbuffer(Renderbuffer),
+}
+
+impl Attachment {
+    pub fn internal_format(&self) -> gl::types::GLenum {
+        match self {
+            Attachment::Texture(texture) => texture.internal_format(),
+            Attachment::Renderbuffer(renderbuffer) => renderbuffer.internal_format(),
+        }
+    }
+}
+
+/// An off-screen render target with one or more color attachments
+pub struct Framebuffer {
+    pub(super) id: u32,
+    pub(super) width: u32,
+    pub(super) height: u32,
+    pub(super) samples: u32,
+    pub(super) colors: Vec<Attachment>,
+    pub(super) depth_stencil: Option<Attachment>,
+}
+
+impl Drop for Framebuffer {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteFramebuffers(1, &self.id);
+        }
+    }
+}
+
+impl Framebuffer {
+    /// Starts building a framebuffer of the given size.
+    /// Color attachments are numbered in the order they are added.
+    ///
+    /// # Examples
+    /// ```
+    /// let scene = Rc::new(Texture2D::new(800, 600, gl::RGBA8));
+    ///
+    /// let framebuffer = Framebuffer::builder(800, 600)
+    ///     .color_texture(scene.clone())
+    ///     .depth_stencil(gl::DEPTH24_STENCIL8)
+    ///     .build()?;
+    /// ```
+    pub fn builder(width: u32, height: u32) -> FramebufferBuilder {
+        FramebufferBuilder {
+            width,
+            height,
+            samples: 0,
+            colors: Vec::new(),
+            depth_stencil: None,
+        }
+    }
+
+    /// Redirects all drawing into this framebuffer until the returned guard is dropped.
+    /// The viewport is set to the size of the framebuffer, shapes keep using window coordinates.
+    ///
+    /// # Examples
+    /// ```
+    /// {
+    ///     let _target = framebuffer.bind();
+    ///     window.clear(color::BLACK);
+    ///     window.draw(&shape);
+    /// }
+    ///
+    /// // Back to drawing into the window
+    /// window.draw(&fullscreen_quad);
+    /// ```
+    pub fn bind(&self) -> BoundFramebuffer<'_> {
+        let mut previous = 0;
+        let mut viewport = [0; 4];
+        unsafe {
+            gl::GetIntegerv(gl::DRAW_FRAMEBUFFER_BINDING, &mut previous);
+            gl::GetIntegerv(gl::VIEWPORT, viewport.as_mut_ptr());
+
+            gl::BindFramebuffer(gl::DRAW_FRAMEBUFFER, self.id);
+            gl::Viewport(0, 0, self.width as i32, self.height as i32);
+        }
+
+        BoundFramebuffer {
+            _framebuffer: self,
+            previous: previous as u32,
+            viewport,
+        }
+    }
+
+    /// Clears all color attachments to the given color, and depth and stencil to 1 and 0
+    pub fn clear(&self, color: Color) {
+        let color: [f32; 4] = color.into();
+        unsafe {
+            for i in 0..self.colors.len() as i32 {
+                gl::ClearNamedFramebufferfv(self.id, gl::COLOR, i, color.as_ptr());
+            }
+
+            if self.depth_stencil.is_some() {
+                gl::ClearNamedFramebufferfi(self.id, gl::DEPTH_STENCIL, 0, 1.0, 0);
+            }
+        }
+    }
+
+    /// Copies all color attachments into the attachments of the target with the same index,
+    /// averaging the samples of a multisampled framebuffer. Depth and stencil are copied as well
+    /// if both framebuffers have them. Attachments should have matching formats.
+    ///
+    /// # Examples
+    /// ```
+    /// let resolved = Rc::new(Texture2D::new(800, 600, gl::RGBA8));
+    /// let target = Framebuffer::builder(800, 600)
+    ///     .color_texture(resolved.clone())
+    ///     .build()?;
+    ///
+    /// {
+    ///     let _msaa = msaa.bind();
+    ///     window.draw(&shape);
+    /// }
+    ///
+    /// msaa.resolve(&target)?;
+    /// ```
+    pub fn resolve(&self, target: &Framebuffer) -> Result<(), Box<dyn std::error::Error>> {
+        if (self.width, self.height) != (target.width, target.height) {
+            Err(format!(
+                "Cannot resolve a {}x{} framebuffer into a {}x{} one",
+                self.width, self.height, target.width, target.height
+            ))?;
+        }
+
+        if target.samples > 0 {
+            Err("The target of a resolve must not be multisampled")?;
+        }
+
+        let count = self.colors.len().min(target.colors.len()) as u32;
+        unsafe {
+            for i in 0..count {
+                gl::NamedFramebufferReadBuffer(self.id, gl::COLOR_ATTACHMENT0 + i);
+                gl::NamedFramebufferDrawBuffer(target.id, gl::COLOR_ATTACHMENT0 + i);
+                self.blit(target.id, gl::COLOR_BUFFER_BIT);
+            }
+
+            // Restore the read buffer and all draw buffers
+            if count > 0 {
+                let draw_buffers: Vec<gl::types::GLenum> = (0..target.colors.len() as u32)
+                    .map(|i| gl::COLOR_ATTACHMENT0 + i)
+                    .collect();
+                gl::NamedFramebufferReadBuffer(self.id, gl::COLOR_ATTACHMENT0);
+                gl::NamedFramebufferDrawBuffers(
+                    target.id,
+                    draw_buffers.len() as i32,
+                    draw_buffers.as_ptr(),
+                );
+            }
+        }
+
+        if self.depth_stencil.is_some() && target.depth_stencil.is_some() {
+            self.blit(target.id, gl::DEPTH_BUFFER_BIT | gl::STENCIL_BUFFER_BIT);
+        }
+
+        Ok(())
+    }
+
+    /// Copies the first color attachment into the window, averaging the samples of a multisampled framebuffer.
+    /// The framebuffer needs to have the size of the window.
+    pub fn resolve_to_window(&self, window: &Window) -> Result<(), Box<dyn std::error::Error>> {
+        let (width, height) = window.size();
+        if (self.width, self.height) != (width, height) {
+            Err(format!(
+                "Cannot resolve a {}x{} framebuffer into the {}x{} window",
+                self.width, self.height, width, height
+            ))?;
+        }
+
+        self.blit(0, gl::COLOR_BUFFER_BIT);
+        Ok(())
+    }
+
+    /// Highest sample count supported for multisampled framebuffers
+    pub fn max_samples() -> u32 {
+        let mut max = 0;
+        unsafe {
+            gl::GetIntegerv(gl::MAX_SAMPLES, &mut max);
+        }
+        max as u32
+    }
+
+    fn blit(&self, target: u32, mask: gl::types::GLbitfield) {
+        let (width, height) = (self.width as i32, self.height as i32);
+        unsafe {
+            gl::BlitNamedFramebuffer(
+                self.id,
+                target,
+                0,
+                0,
+                width,
+                height,
+                0,
+                0,
+                width,
+                height,
+                mask,
+                gl::NEAREST,
+            );
+        }
+    }
+
+    /// The attachment at COLOR_ATTACHMENT0 + index
+    pub fn color(&self, index: usize) -> Option<&Attachment> {
+        self.colors.get(index)
+    }
+
+    /// The texture at COLOR_ATTACHMENT0 + index, if it is one
+    pub fn color_texture(&self, index: usize) -> Option<&Rc<Texture2D>> {
+        match self.colors.get(index) {
+            Some(Attachment::Texture(texture)) => Some(texture),
+            _ => None,
+        }
+    }
+
+    pub fn depth_stencil(&self) -> Option<&Attachment> {
+        self.depth_stencil.as_ref()
+    }
+
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Samples per pixel, 0 if the framebuffer isn't multisampled
+    pub fn samples(&self) -> u32 {
+        self.samples
+    }
+}
+
+/// Keeps a framebuffer bound as draw target, restores the previous target and viewport when dropped
+pub struct BoundFramebuffer<'a> {
+    _framebuffer: &'a Framebuffer,
+    previous: u32,
+    viewport: [i32; 4],
+}
+
+impl Drop for BoundFramebuffer<'_> {
+    fn drop(&mut self) {
+        let [x, y, width, height] = self.viewport;
+        unsafe {
+            gl::BindFramebuffer(gl::DRAW_FRAMEBUFFER, self.previous);
+            gl::Viewport(x, y, width, height);
+        }
+    }
+}
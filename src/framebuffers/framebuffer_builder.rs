@@ -0,0 +1,234 @@
+use std::rc::Rc;
+
+use crate::textures::{Texture, Texture2D};
+
+use super::{Attachment, Framebuffer, Renderbuffer};
+
+/// An attachment which is created when the framebuffer is built
+pub(super) enum AttachmentSource {
+    Texture(Rc<Texture2D>),
+    Renderbuffer(gl::types::GLenum),
+}
+
+/// Collects the attachments of a framebuffer, see Framebuffer::builder()
+pub struct FramebufferBuilder {
+    pub(super) width: u32,
+    pub(super) height: u32,
+    pub(super) samples: u32,
+    pub(super) colors: Vec<AttachmentSource>,
+    pub(super) depth_stencil: Option<AttachmentSource>,
+}
+
+impl FramebufferBuilder {
+    /// Adds a texture as next color attachment, so the rendered image can be sampled afterwards
+    pub fn color_texture(mut self, texture: Rc<Texture2D>) -> FramebufferBuilder {
+        self.colors.push(AttachmentSource::Texture(texture));
+        self
+    }
+
+    /// Adds a renderbuffer of the given format as next color attachment
+    pub fn color_renderbuffer(mut self, internal_format: gl::types::GLenum) -> FramebufferBuilder {
+        self.colors
+            .push(AttachmentSource::Renderbuffer(internal_format));
+        self
+    }
+
+    /// Adds a depth and/or stencil renderbuffer
+    ///
+    /// # Arguments
+    /// * `internal_format` - E.g. DEPTH24_STENCIL8, DEPTH_COMPONENT32F or STENCIL_INDEX8
+    pub fn depth_stencil(self, internal_format: gl::types::GLenum) -> FramebufferBuilder {
+        FramebufferBuilder {
+            depth_stencil: Some(AttachmentSource::Renderbuffer(internal_format)),
+            ..self
+        }
+    }
+
+    /// Adds a depth and/or stencil texture, e.g. for shadow mapping
+    pub fn depth_texture(self, texture: Rc<Texture2D>) -> FramebufferBuilder {
+        FramebufferBuilder {
+            depth_stencil: Some(AttachmentSource::Texture(texture)),
+            ..self
+        }
+    }
+
+    /// Number of samples per pixel for multisample anti-aliasing, 0 disables it.
+    /// Multisampled framebuffers can only have renderbuffer attachments,
+    /// resolve them into a regular framebuffer to sample the result.
+    ///
+    /// # Examples
+    /// ```
+    /// let msaa = Framebuffer::builder(800, 600)
+    ///     .samples(4.min(Framebuffer::max_samples()))
+    ///     .color_renderbuffer(gl::RGBA8)
+    ///     .depth_stencil(gl::DEPTH24_STENCIL8)
+    ///     .build()?;
+    /// ```
+    pub fn samples(self, samples: u32) -> FramebufferBuilder {
+        FramebufferBuilder { samples, ..self }
+    }
+
+    /// Creates the framebuffer and checks it for completeness
+    pub fn build(self) -> Result<Framebuffer, Box<dyn std::error::Error>> {
+        let mut max_attachments = 0;
+        unsafe {
+            gl::GetIntegerv(gl::MAX_COLOR_ATTACHMENTS, &mut max_attachments);
+        }
+
+        if self.colors.len() > max_attachments as usize {
+            Err(format!(
+                "{} color attachments were given, but only {} are supported",
+                self.colors.len(),
+                max_attachments
+            ))?;
+        }
+
+        let max_samples = Framebuffer::max_samples();
+        if self.samples > max_samples {
+            Err(format!(
+                "{} samples were requested, but only {} are supported",
+                self.samples, max_samples
+            ))?;
+        }
+
+        let mut colors = Vec::new();
+        for (i, source) in self.colors.iter().enumerate() {
+            colors.push(self.create(&format!("Color attachment {}", i), source)?);
+        }
+
+        let depth_stencil = match &self.depth_stencil {
+            Some(source) => Some(self.create("Depth attachment", source)?),
+            None => None,
+        };
+
+        let mut id = 0;
+        unsafe {
+            gl::CreateFramebuffers(1, &mut id);
+        }
+
+        // Dropping the framebuffer deletes it if anything below fails
+        let framebuffer = Framebuffer {
+            id,
+            width: self.width,
+            height: self.height,
+            samples: self.samples,
+            colors,
+            depth_stencil,
+        };
+
+        let mut draw_buffers = Vec::new();
+        for (i, attachment) in framebuffer.colors.iter().enumerate() {
+            let point = gl::COLOR_ATTACHMENT0 + i as u32;
+            attach(id, point, attachment);
+            draw_buffers.push(point);
+        }
+
+        if let Some(attachment) = &framebuffer.depth_stencil {
+            let point = match attachment.internal_format() {
+                gl::DEPTH24_STENCIL8 | gl::DEPTH32F_STENCIL8 => gl::DEPTH_STENCIL_ATTACHMENT,
+                gl::STENCIL_INDEX8 => gl::STENCIL_ATTACHMENT,
+                _ => gl::DEPTH_ATTACHMENT,
+            };
+            attach(id, point, attachment);
+        }
+
+        unsafe {
+            if draw_buffers.is_empty() {
+                gl::NamedFramebufferDrawBuffer(id, gl::NONE);
+                gl::NamedFramebufferReadBuffer(id, gl::NONE);
+            } else {
+                gl::NamedFramebufferDrawBuffers(
+                    id,
+                    draw_buffers.len() as i32,
+                    draw_buffers.as_ptr(),
+                );
+                gl::NamedFramebufferReadBuffer(id, gl::COLOR_ATTACHMENT0);
+            }
+        }
+
+        let status = unsafe { gl::CheckNamedFramebufferStatus(id, gl::DRAW_FRAMEBUFFER) };
+        if status != gl::FRAMEBUFFER_COMPLETE {
+            Err(format!(
+                "Framebuffer is incomplete: {}",
+                status_to_string(status)
+            ))?;
+        }
+
+        Ok(framebuffer)
+    }
+
+    fn create(
+        &self,
+        name: &str,
+        source: &AttachmentSource,
+    ) -> Result<Attachment, Box<dyn std::error::Error>> {
+        match source {
+            AttachmentSource::Texture(texture) => {
+                if self.samples > 0 {
+                    Err(format!(
+                        "{} is a texture, but multisampled framebuffers only support renderbuffers",
+                        name
+                    ))?;
+                }
+
+                if (texture.width(), texture.height()) != (self.width, self.height) {
+                    Err(format!(
+                        "{} is {}x{}, but the framebuffer is {}x{}",
+                        name,
+                        texture.width(),
+                        texture.height(),
+                        self.width,
+                        self.height
+                    ))?;
+                }
+
+                Ok(Attachment::Texture(texture.clone()))
+            }
+            AttachmentSource::Renderbuffer(internal_format) => Ok(Attachment::Renderbuffer(
+                Renderbuffer::multisampled(self.width, self.height, *internal_format, self.samples),
+            )),
+        }
+    }
+}
+
+fn attach(framebuffer: u32, point: gl::types::GLenum, attachment: &Attachment) {
+    unsafe {
+        match attachment {
+            Attachment::Texture(texture) => {
+                gl::NamedFramebufferTexture(framebuffer, point, texture.id(), 0)
+            }
+            Attachment::Renderbuffer(renderbuffer) => gl::NamedFramebufferRenderbuffer(
+                framebuffer,
+                point,
+                gl::RENDERBUFFER,
+                renderbuffer.id(),
+            ),
+        }
+    }
+}
+
+fn status_to_string(status: gl::types::GLenum) -> String {
+    match status {
+        gl::FRAMEBUFFER_UNDEFINED => "no framebuffer is bound".to_string(),
+        gl::FRAMEBUFFER_INCOMPLETE_ATTACHMENT => {
+            "an attachment is incomplete, e.g. its format isn't renderable".to_string()
+        }
+        gl::FRAMEBUFFER_INCOMPLETE_MISSING_ATTACHMENT => "no image is attached".to_string(),
+        gl::FRAMEBUFFER_INCOMPLETE_DRAW_BUFFER => {
+            "a draw buffer points to an empty attachment".to_string()
+        }
+        gl::FRAMEBUFFER_INCOMPLETE_READ_BUFFER => {
+            "the read buffer points to an empty attachment".to_string()
+        }
+        gl::FRAMEBUFFER_UNSUPPORTED => {
+            "the combination of formats is not supported by the driver".to_string()
+        }
+        gl::FRAMEBUFFER_INCOMPLETE_MULTISAMPLE => {
+            "the attachments have different sample counts".to_string()
+        }
+        gl::FRAMEBUFFER_INCOMPLETE_LAYER_TARGETS => {
+            "layered and non layered attachments are mixed".to_string()
+        }
+        _ => format!("unknown status {:#x}", status),
+    }
+}
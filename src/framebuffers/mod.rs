@@ -0,0 +1,10 @@
+//! This module abstracts OpenGL framebuffer objects for off-screen rendering.
+//! Framebuffers and renderbuffers implement the drop trait for automatic clean-up.
+
+mod framebuffer;
+mod framebuffer_builder;
+mod renderbuffer;
+
+pub use framebuffer::{Attachment, BoundFramebuffer, Framebuffer};
+pub use framebuffer_builder::FramebufferBuilder;
+pub use renderbuffer::Renderbuffer;
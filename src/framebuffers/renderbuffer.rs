@@ -0,0 +1,88 @@
+/// Image storage which can only be rendered to, not sampled.
+/// Cheaper than a texture for attachments that are never read, e.g. a depth buffer.
+pub struct Renderbuffer {
+    id: u32,
+    width: u32,
+    height: u32,
+    samples: u32,
+    internal_format: gl::types::GLenum,
+}
+
+impl Drop for Renderbuffer {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteRenderbuffers(1, &self.id);
+        }
+    }
+}
+
+impl Renderbuffer {
+    /// Allocates a renderbuffer
+    ///
+    /// # Arguments
+    /// * `width` - Width in pixels
+    /// * `height` - Height in pixels
+    /// * `internal_format` - Sized internal format, e.g. RGBA8 or DEPTH24_STENCIL8
+    ///
+    /// # Examples
+    /// ```
+    /// let depth = Renderbuffer::new(800, 600, gl::DEPTH24_STENCIL8);
+    /// ```
+    pub fn new(width: u32, height: u32, internal_format: gl::types::GLenum) -> Renderbuffer {
+        Renderbuffer::multisampled(width, height, internal_format, 0)
+    }
+
+    /// Allocates a renderbuffer with multiple samples per pixel
+    ///
+    /// # Arguments
+    /// * `width` - Width in pixels
+    /// * `height` - Height in pixels
+    /// * `internal_format` - Sized internal format, e.g. RGBA8 or DEPTH24_STENCIL8
+    /// * `samples` - Samples per pixel, at most Framebuffer::max_samples(). 0 allocates a regular renderbuffer.
+    pub fn multisampled(
+        width: u32,
+        height: u32,
+        internal_format: gl::types::GLenum,
+        samples: u32,
+    ) -> Renderbuffer {
+        let mut id = 0;
+        unsafe {
+            gl::CreateRenderbuffers(1, &mut id);
+            gl::NamedRenderbufferStorageMultisample(
+                id,
+                samples as i32,
+                internal_format,
+                width as i32,
+                height as i32,
+            );
+        }
+
+        Renderbuffer {
+            id,
+            width,
+            height,
+            samples,
+            internal_format,
+        }
+    }
+
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn samples(&self) -> u32 {
+        self.samples
+    }
+
+    pub fn internal_format(&self) -> gl::types::GLenum {
+        self.internal_format
+    }
+}
@@ -1,31 +1,30 @@
+// Lets the derive macros refer to ::rusty_gl from inside this crate
+extern crate self as rusty_gl;
+
+pub mod color;
+pub mod compute_graph;
 pub mod debug;
+pub mod framebuffers;
 mod gpu;
+mod ping_pong;
+pub mod post_process;
+pub mod primitives;
+pub mod render_state;
+pub mod scene;
 mod shader;
 pub mod shapes;
 mod ssbo;
+pub mod textures;
+pub mod transform;
 pub mod vertices;
 mod windows;
 
 pub use color::Color;
 pub use gpu::GPU;
-pub use shader::{ComputeShader, PipelineShader, ShaderSource};
+pub use ping_pong::{PingPongSSBO, PingPongTexture};
+pub use shader::{
+    ComputeShader, DispatchIndirectCommand, PipelineShader, ShaderAttribute, ShaderSource,
+};
 pub use ssbo::SSBO;
+pub use transform::Transform2D;
 pub use windows::Window;
-
-pub mod color {
-    pub type Color<T> = vector::Vector3<T>;
-
-    pub const WHITE: Color<f32> = Color::new(1.0, 1.0, 1.0);
-    pub const BLACK: Color<f32> = Color::new(0.0, 0.0, 0.0);
-    pub const RED: Color<f32> = Color::new(1.0, 0.0, 0.0);
-    pub const GREEN: Color<f32> = Color::new(0.0, 1.0, 0.0);
-    pub const BLUE: Color<f32> = Color::new(0.0, 0.0, 1.0);
-    pub const YELLOW: Color<f32> = Color::new(1.0, 1.0, 0.0);
-    pub const CYAN: Color<f32> = Color::new(0.0, 1.0, 1.0);
-    pub const MAGENTA: Color<f32> = Color::new(1.0, 0.0, 1.0);
-    pub const PURPLE: Color<f32> = Color::new(0.5, 0.0, 0.5);
-    pub const ORANGE: Color<f32> = Color::new(1.0, 0.5, 0.0);
-    pub const PINK: Color<f32> = Color::new(1.0, 0.0, 0.5);
-    pub const LIME: Color<f32> = Color::new(0.0, 1.0, 0.5);
-    pub const TEAL: Color<f32> = Color::new(0.5, 1.0, 0.5);
-}
@@ -0,0 +1,182 @@
+//! This module implements double buffering for iterative compute shaders.
+//! One buffer is read while the other one is written, swap() flips their roles.
+
+use std::ops::{Deref, DerefMut};
+
+use crate::{
+    textures::{Texture, Texture2D},
+    GPU, SSBO,
+};
+
+pub struct PingPongSSBO<T> {
+    buffers: [SSBO<T>; 2],
+    read_binding: u32,
+    write_binding: u32,
+    current: usize,
+}
+
+impl<T: GPU> Deref for PingPongSSBO<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.buffers[self.current]
+    }
+}
+
+impl<T: GPU> DerefMut for PingPongSSBO<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.buffers[self.current]
+    }
+}
+
+impl<T: GPU + Clone> PingPongSSBO<T> {
+    /// Creates two ssbos on the gpu which both start out with the objects data
+    ///
+    /// # Arguments
+    /// * `read_binding` - Binding index the current buffer is bound to
+    /// * `write_binding` - Binding index the other buffer is bound to
+    /// * `object` - The object to be moved to gpu memory
+    /// * `usage` - Memory usage pattern. See https://www.khronos.org/registry/OpenGL-Refpages/gl4/html/glBufferData.xhtml
+    ///
+    /// # Examples
+    /// ```
+    /// let mut cells = PingPongSSBO::create_from(0, 1, vec![0 as u32; 64 * 64], gl::DYNAMIC_DRAW);
+    ///
+    /// for _ in 0..100 {
+    ///     shader.dispatch(64, 64, 1, gl::SHADER_STORAGE_BARRIER_BIT);
+    ///     cells.swap();
+    /// }
+    ///
+    /// cells.load();
+    /// ```
+    pub fn create_from(
+        read_binding: u32,
+        write_binding: u32,
+        object: T,
+        usage: gl::types::GLenum,
+    ) -> PingPongSSBO<T> {
+        let read = SSBO::create_from(read_binding, object.clone(), usage);
+        let write = SSBO::create_from(write_binding, object, usage);
+
+        PingPongSSBO {
+            buffers: [read, write],
+            read_binding,
+            write_binding,
+            current: 0,
+        }
+    }
+}
+
+impl<T: GPU> PingPongSSBO<T> {
+    /// Flips the buffers, the last written one becomes the current one
+    pub fn swap(&mut self) {
+        self.current = 1 - self.current;
+        self.bind();
+    }
+
+    /// Binds the current buffer to the read binding and the other one to the write binding
+    pub fn bind(&self) {
+        self.buffers[self.current].bind(self.read_binding);
+        self.buffers[1 - self.current].bind(self.write_binding);
+    }
+
+    /// The buffer bound to the read binding
+    pub fn current(&self) -> &SSBO<T> {
+        &self.buffers[self.current]
+    }
+
+    /// The buffer bound to the read binding
+    pub fn current_mut(&mut self) -> &mut SSBO<T> {
+        &mut self.buffers[self.current]
+    }
+
+    /// The buffer bound to the write binding
+    pub fn other(&self) -> &SSBO<T> {
+        &self.buffers[1 - self.current]
+    }
+
+    /// Sends the content of the current buffer to the gpu
+    pub fn update(&mut self) {
+        self.current_mut().update();
+    }
+
+    /// Retrieves the data of the current buffer from the gpu
+    pub fn load(&mut self) {
+        self.current_mut().load();
+    }
+}
+
+/// Two textures of the same size, bound as images.
+/// The current one is bound read only, the other one write only.
+pub struct PingPongTexture {
+    textures: [Texture2D; 2],
+    read_unit: u32,
+    write_unit: u32,
+    current: usize,
+}
+
+impl PingPongTexture {
+    /// Allocates two textures and binds them to the given image units
+    ///
+    /// # Arguments
+    /// * `width` - Width in pixels
+    /// * `height` - Height in pixels
+    /// * `internal_format` - Sized internal format, also used as image format
+    /// * `read_unit` - Image unit the current texture is bound to
+    /// * `write_unit` - Image unit the other texture is bound to
+    ///
+    /// # Examples
+    /// ```
+    /// let mut field = PingPongTexture::new(256, 256, gl::R32F, 0, 1);
+    ///
+    /// for _ in 0..100 {
+    ///     shader.dispatch_threads(256, 256, 1, gl::SHADER_IMAGE_ACCESS_BARRIER_BIT)?;
+    ///     field.swap();
+    /// }
+    /// ```
+    pub fn new(
+        width: u32,
+        height: u32,
+        internal_format: gl::types::GLenum,
+        read_unit: u32,
+        write_unit: u32,
+    ) -> PingPongTexture {
+        let textures = PingPongTexture {
+            textures: [
+                Texture2D::new(width, height, internal_format),
+                Texture2D::new(width, height, internal_format),
+            ],
+            read_unit,
+            write_unit,
+            current: 0,
+        };
+
+        textures.bind();
+        textures
+    }
+
+    /// Flips the textures, the last written one becomes the current one
+    pub fn swap(&mut self) {
+        self.current = 1 - self.current;
+        self.bind();
+    }
+
+    /// Binds the current texture to the read unit and the other one to the write unit
+    pub fn bind(&self) {
+        let current = &self.textures[self.current];
+        let other = &self.textures[1 - self.current];
+
+        current.bind_image(self.read_unit, 0, gl::READ_ONLY, current.internal_format());
+        other.bind_image(self.write_unit, 0, gl::WRITE_ONLY, other.internal_format());
+    }
+
+    /// The texture bound to the read unit
+    pub fn current(&self) -> &Texture2D {
+        &self.textures[self.current]
+    }
+
+    /// The texture bound to the write unit
+    pub fn other(&self) -> &Texture2D {
+        &self.textures[1 - self.current]
+    }
+}
@@ -0,0 +1,163 @@
+//! Shader sources of the post processing chain.
+//! Every fragment pass reads the previous result from `source` at the interpolated `uv`.
+
+/// Covers the viewport with a single triangle, no vertex buffer needed
+pub(super) const FULLSCREEN_VERTEX: &str = "
+#version 430
+
+out vec2 uv;
+
+void main() {
+    uv = vec2((gl_VertexID << 1) & 2, gl_VertexID & 2);
+    gl_Position = vec4(uv * 2.0 - 1.0, 0.0, 1.0);
+}
+";
+
+/// One direction of a separable gaussian blur
+pub(super) const BLUR: &str = "
+#version 430
+
+in vec2 uv;
+out vec4 FragColor;
+
+uniform sampler2D source;
+uniform vec2 resolution;
+uniform vec2 direction;
+uniform float radius;
+
+void main() {
+    float sigma = max(radius / 3.0, 0.001);
+    vec2 texel = direction / resolution;
+    int r = int(ceil(radius));
+
+    vec4 sum = vec4(0.0);
+    float total = 0.0;
+    for (int i = -r; i <= r; i++) {
+        float weight = exp(-0.5 * float(i * i) / (sigma * sigma));
+        sum += texture(source, uv + float(i) * texel) * weight;
+        total += weight;
+    }
+
+    FragColor = sum / total;
+}
+";
+
+/// Keeps only the pixels brighter than the threshold
+pub(super) const BRIGHT_PASS: &str = "
+#version 430
+
+in vec2 uv;
+out vec4 FragColor;
+
+uniform sampler2D source;
+uniform float threshold;
+
+void main() {
+    vec4 color = texture(source, uv);
+    float luminance = dot(color.rgb, vec3(0.2126, 0.7152, 0.0722));
+    FragColor = vec4(color.rgb * step(threshold, luminance), color.a);
+}
+";
+
+/// Adds the blurred bright pixels on top of the image
+pub(super) const BLOOM_COMPOSITE: &str = "
+#version 430
+
+in vec2 uv;
+out vec4 FragColor;
+
+uniform sampler2D source;
+uniform sampler2D bloom;
+uniform float intensity;
+
+void main() {
+    vec4 color = texture(source, uv);
+    FragColor = vec4(color.rgb + texture(bloom, uv).rgb * intensity, color.a);
+}
+";
+
+/// Darkens the image towards the corners
+pub(super) const VIGNETTE: &str = "
+#version 430
+
+in vec2 uv;
+out vec4 FragColor;
+
+uniform sampler2D source;
+uniform float radius;
+uniform float strength;
+
+void main() {
+    vec4 color = texture(source, uv);
+    float falloff = 1.0 - smoothstep(radius * 0.5, radius, distance(uv, vec2(0.5)));
+    FragColor = vec4(color.rgb * mix(1.0, falloff, strength), color.a);
+}
+";
+
+/// Maps every color through a 3D lookup table
+pub(super) const COLOR_GRADING: &str = "
+#version 430
+
+in vec2 uv;
+out vec4 FragColor;
+
+uniform sampler2D source;
+uniform sampler3D lut;
+
+void main() {
+    vec4 color = texture(source, uv);
+
+    // Sample at texel centers, so black and white map to the outermost entries
+    float size = float(textureSize(lut, 0).x);
+    vec3 position = clamp(color.rgb, 0.0, 1.0) * (size - 1.0) / size + 0.5 / size;
+
+    FragColor = vec4(texture(lut, position).rgb, color.a);
+}
+";
+
+/// Fast approximate anti-aliasing, blurs along detected edges
+pub(super) const FXAA: &str = "
+#version 430
+
+in vec2 uv;
+out vec4 FragColor;
+
+uniform sampler2D source;
+uniform vec2 resolution;
+
+const float SPAN_MAX = 8.0;
+const float REDUCE_MUL = 1.0 / 8.0;
+const float REDUCE_MIN = 1.0 / 128.0;
+const vec3 LUMA = vec3(0.299, 0.587, 0.114);
+
+void main() {
+    vec2 texel = 1.0 / resolution;
+
+    float nw = dot(texture(source, uv + vec2(-1.0, -1.0) * texel).rgb, LUMA);
+    float ne = dot(texture(source, uv + vec2(1.0, -1.0) * texel).rgb, LUMA);
+    float sw = dot(texture(source, uv + vec2(-1.0, 1.0) * texel).rgb, LUMA);
+    float se = dot(texture(source, uv + vec2(1.0, 1.0) * texel).rgb, LUMA);
+    vec4 center = texture(source, uv);
+    float m = dot(center.rgb, LUMA);
+
+    float luma_min = min(m, min(min(nw, ne), min(sw, se)));
+    float luma_max = max(m, max(max(nw, ne), max(sw, se)));
+
+    vec2 dir = vec2(-((nw + ne) - (sw + se)), (nw + sw) - (ne + se));
+    float reduce = max((nw + ne + sw + se) * 0.25 * REDUCE_MUL, REDUCE_MIN);
+    float scale = 1.0 / (min(abs(dir.x), abs(dir.y)) + reduce);
+    dir = clamp(dir * scale, vec2(-SPAN_MAX), vec2(SPAN_MAX)) * texel;
+
+    vec3 a = 0.5 * (
+        texture(source, uv + dir * (1.0 / 3.0 - 0.5)).rgb +
+        texture(source, uv + dir * (2.0 / 3.0 - 0.5)).rgb
+    );
+    vec3 b = a * 0.5 + 0.25 * (
+        texture(source, uv - dir * 0.5).rgb +
+        texture(source, uv + dir * 0.5).rgb
+    );
+
+    float luma_b = dot(b, LUMA);
+    FragColor = vec4(luma_b < luma_min || luma_b > luma_max ? a : b, center.a);
+}
+";
@@ -0,0 +1,399 @@
+//! This module implements a chain of fullscreen effects.
+//! The scene is drawn into an off-screen target, every pass reads the result of the previous one
+//! and the last pass writes into the window.
+
+mod effects;
+
+use std::{rc::Rc, time::Instant};
+
+use vector::Vector2;
+
+use crate::{
+    framebuffers::{BoundFramebuffer, Framebuffer},
+    render_state::RenderState,
+    shader::Uniform,
+    textures::{Sampler, Texture, Texture2D, Texture3D, TextureUnits},
+    vertices::VAO,
+    PipelineShader, ShaderSource, Window,
+};
+
+use effects::{
+    BLOOM_COMPOSITE, BLUR, BRIGHT_PASS, COLOR_GRADING, FULLSCREEN_VERTEX, FXAA, VIGNETTE,
+};
+
+/// Format of the scene and all intermediate targets, keeps values above 1.0 for bloom
+const TARGET_FORMAT: gl::types::GLenum = gl::RGBA16F;
+
+/// A pass of the chain
+pub enum Effect {
+    /// Separable gaussian blur, the radius is given in pixels
+    GaussianBlur { radius: f32 },
+    /// Blurs the pixels brighter than the threshold luminance and adds them on top
+    Bloom {
+        threshold: f32,
+        intensity: f32,
+        radius: f32,
+    },
+    /// Darkens the corners. Radius is the distance from the center in uv space where darkening starts.
+    Vignette { radius: f32, strength: f32 },
+    /// Maps every color through a lookup table, e.g. a 16x16x16 RGB8 Texture3D
+    ColorGrading { lut: Rc<Texture3D> },
+    /// Fast approximate anti-aliasing
+    Fxaa,
+    /// A user fragment shader. It can use the following inputs:
+    /// * `in vec2 uv` - Texture coordinate of the fragment
+    /// * `uniform sampler2D source` - Result of the previous pass
+    /// * `uniform sampler2D scene` - The untouched scene
+    /// * `uniform vec2 resolution` - Size of the target in pixels
+    /// * `uniform float time` - Seconds since the chain was created
+    Custom(ShaderSource),
+}
+
+/// Texture a step samples from
+enum Input {
+    Chain,
+    Scene,
+    Buffer(usize),
+    Texture(Rc<dyn Texture>),
+}
+
+/// Target a step renders into
+enum Output {
+    Chain,
+    Buffer(usize),
+}
+
+struct Step {
+    shader: PipelineShader,
+    inputs: Vec<(&'static str, Input)>,
+    output: Output,
+    // Used for the Texture inputs, so the parameters of textures owned by the user stay untouched
+    sampler: Option<Sampler>,
+}
+
+/// Renders the scene into an off-screen target and applies a list of effects before presenting it.
+///
+/// # Examples
+/// ```
+/// let mut post = PostProcess::new(&window)?;
+/// post.add(Effect::Bloom { threshold: 0.8, intensity: 1.0, radius: 8.0 })?;
+/// let vignette = post.add(Effect::Vignette { radius: 0.75, strength: 0.5 })?;
+///
+/// 'main: loop {
+///     {
+///         let _scene = post.begin();
+///         window.clear(color::BLACK);
+///         window.draw(&shape);
+///     }
+///
+///     post.set_uniform(vignette, "strength", pulse);
+///     post.present(&window)?;
+///     window.gl_swap();
+/// }
+/// ```
+pub struct PostProcess {
+    width: u32,
+    height: u32,
+
+    scene: Framebuffer,
+    chain: [Framebuffer; 2],
+    buffers: Vec<Framebuffer>,
+
+    passes: Vec<Vec<Step>>,
+
+    vao: VAO,
+    start: Instant,
+}
+
+impl PostProcess {
+    /// Creates the off-screen targets with the size of the window
+    pub fn new(window: &Window) -> Result<PostProcess, Box<dyn std::error::Error>> {
+        let (width, height) = window.size();
+
+        let scene = Framebuffer::builder(width, height)
+            .color_texture(target_texture(width, height))
+            .depth_stencil(gl::DEPTH24_STENCIL8)
+            .build()?;
+
+        let chain = [
+            target_framebuffer(width, height)?,
+            target_framebuffer(width, height)?,
+        ];
+
+        Ok(PostProcess {
+            width,
+            height,
+            scene,
+            chain,
+            buffers: Vec::new(),
+            passes: Vec::new(),
+            vao: VAO::new(),
+            start: Instant::now(),
+        })
+    }
+
+    /// Appends an effect to the chain and returns its index for set_uniform()
+    ///
+    /// # Examples
+    /// ```
+    /// const INVERT: &str = "
+    ///     #version 430
+    ///     in vec2 uv;
+    ///     out vec4 FragColor;
+    ///     uniform sampler2D source;
+    ///
+    ///     void main() {
+    ///         FragColor = vec4(1.0 - texture(source, uv).rgb, 1.0);
+    ///     }
+    /// ";
+    ///
+    /// post.add(Effect::Custom(ShaderSource::String(INVERT)))?;
+    /// ```
+    pub fn add(&mut self, effect: Effect) -> Result<usize, Box<dyn std::error::Error>> {
+        let steps = match effect {
+            Effect::GaussianBlur { radius } => vec![
+                blur_step(radius, Vector2::new(1.0, 0.0), Input::Chain, Output::Chain)?,
+                blur_step(radius, Vector2::new(0.0, 1.0), Input::Chain, Output::Chain)?,
+            ],
+            Effect::Bloom {
+                threshold,
+                intensity,
+                radius,
+            } => {
+                let a = self.add_buffer()?;
+                let b = self.add_buffer()?;
+
+                let bright = step(
+                    BRIGHT_PASS,
+                    vec![("source", Input::Chain)],
+                    Output::Buffer(a),
+                )?;
+                bright.shader.set_uniform("threshold", threshold);
+
+                let composite = step(
+                    BLOOM_COMPOSITE,
+                    vec![("source", Input::Chain), ("bloom", Input::Buffer(a))],
+                    Output::Chain,
+                )?;
+                composite.shader.set_uniform("intensity", intensity);
+
+                vec![
+                    bright,
+                    blur_step(
+                        radius,
+                        Vector2::new(1.0, 0.0),
+                        Input::Buffer(a),
+                        Output::Buffer(b),
+                    )?,
+                    blur_step(
+                        radius,
+                        Vector2::new(0.0, 1.0),
+                        Input::Buffer(b),
+                        Output::Buffer(a),
+                    )?,
+                    composite,
+                ]
+            }
+            Effect::Vignette { radius, strength } => {
+                let vignette = step(VIGNETTE, vec![("source", Input::Chain)], Output::Chain)?;
+                vignette.shader.set_uniform("radius", radius);
+                vignette.shader.set_uniform("strength", strength);
+                vec![vignette]
+            }
+            Effect::ColorGrading { lut } => {
+                let mut grading = step(
+                    COLOR_GRADING,
+                    vec![("source", Input::Chain), ("lut", Input::Texture(lut))],
+                    Output::Chain,
+                )?;
+                // Interpolating towards the repeated opposite edge would shift the extremes
+                grading.sampler = Some(Sampler::new().wrap(
+                    gl::CLAMP_TO_EDGE,
+                    gl::CLAMP_TO_EDGE,
+                    gl::CLAMP_TO_EDGE,
+                ));
+                vec![grading]
+            }
+            Effect::Fxaa => vec![step(FXAA, vec![("source", Input::Chain)], Output::Chain)?],
+            Effect::Custom(source) => vec![Step {
+                shader: PipelineShader::create(
+                    Some(ShaderSource::String(FULLSCREEN_VERTEX)),
+                    Some(source),
+                )?,
+                inputs: vec![("source", Input::Chain), ("scene", Input::Scene)],
+                output: Output::Chain,
+                sampler: None,
+            }],
+        };
+
+        self.passes.push(steps);
+        Ok(self.passes.len() - 1)
+    }
+
+    /// Sets a uniform on all shaders of a pass, e.g. to animate the parameters of an effect.
+    /// The parameters of the built-in effects are uniforms with the same names.
+    pub fn set_uniform<T: Uniform + Copy>(&self, pass: usize, name: &str, value: T) {
+        if let Some(steps) = self.passes.get(pass) {
+            for step in steps {
+                step.shader.set_uniform(name, value);
+            }
+        }
+    }
+
+    /// Redirects drawing into the scene target until the returned guard is dropped
+    pub fn begin(&self) -> BoundFramebuffer<'_> {
+        self.scene.bind()
+    }
+
+    /// Runs all passes and writes the result into the window
+    pub fn present(&self, window: &Window) -> Result<(), Box<dyn std::error::Error>> {
+        let (window_width, window_height) = window.size();
+
+        let last = self
+            .passes
+            .iter()
+            .flatten()
+            .enumerate()
+            .filter(|(_, step)| matches!(step.output, Output::Chain))
+            .map(|(i, _)| i)
+            .last();
+
+        let last = match last {
+            Some(last) => last,
+            None => {
+                // Nothing to apply, the scene is copied as is
+                unsafe {
+                    gl::BlitNamedFramebuffer(
+                        self.scene.id(),
+                        0,
+                        0,
+                        0,
+                        self.width as i32,
+                        self.height as i32,
+                        0,
+                        0,
+                        window_width as i32,
+                        window_height as i32,
+                        gl::COLOR_BUFFER_BIT,
+                        gl::LINEAR,
+                    );
+                }
+                return Ok(());
+            }
+        };
+
+        // Fullscreen passes must not be affected by depth, blending, culling or masks
+        let previous = RenderState::current();
+        RenderState::default().apply();
+
+        let time = self.start.elapsed().as_secs_f32();
+        let scene = self.scene.color_texture(0).unwrap();
+
+        // The chain starts with the scene and alternates between the two chain targets
+        let mut current: &Texture2D = scene;
+        let mut next = 0;
+
+        for (i, step) in self.passes.iter().flatten().enumerate() {
+            let mut units = TextureUnits::starting_at(0);
+            for (name, input) in &step.inputs {
+                let (texture, sampler): (&dyn Texture, _) = match input {
+                    Input::Chain => (current, None),
+                    Input::Scene => (scene.as_ref(), None),
+                    Input::Buffer(index) => (
+                        self.buffers[*index].color_texture(0).unwrap().as_ref(),
+                        None,
+                    ),
+                    Input::Texture(texture) => (texture.as_ref(), step.sampler.as_ref()),
+                };
+                units.add(name, texture, sampler);
+            }
+            units.bind(&step.shader)?;
+
+            let (framebuffer, width, height) = match step.output {
+                Output::Chain if i == last => (0, window_width, window_height),
+                Output::Chain => (self.chain[next].id(), self.width, self.height),
+                Output::Buffer(index) => (self.buffers[index].id(), self.width, self.height),
+            };
+
+            step.shader
+                .set_uniform("resolution", Vector2::new(width as f32, height as f32));
+            step.shader.set_uniform("time", time);
+
+            unsafe {
+                gl::BindFramebuffer(gl::DRAW_FRAMEBUFFER, framebuffer);
+                gl::Viewport(0, 0, width as i32, height as i32);
+            }
+
+            self.vao.draw(gl::TRIANGLES, 3, None);
+
+            if let Output::Chain = step.output {
+                current = self.chain[next].color_texture(0).unwrap();
+                next = 1 - next;
+            }
+        }
+
+        unsafe {
+            gl::BindFramebuffer(gl::DRAW_FRAMEBUFFER, 0);
+            gl::Viewport(0, 0, window_width as i32, window_height as i32);
+        }
+
+        match previous {
+            Some(state) => state.apply(),
+            None => RenderState::invalidate(),
+        }
+
+        Ok(())
+    }
+
+    /// The texture the scene is drawn into
+    pub fn scene(&self) -> &Rc<Texture2D> {
+        self.scene.color_texture(0).unwrap()
+    }
+
+    fn add_buffer(&mut self) -> Result<usize, Box<dyn std::error::Error>> {
+        self.buffers
+            .push(target_framebuffer(self.width, self.height)?);
+        Ok(self.buffers.len() - 1)
+    }
+}
+
+fn target_texture(width: u32, height: u32) -> Rc<Texture2D> {
+    let texture = Texture2D::new(width, height, TARGET_FORMAT);
+    // Blurs sample beyond the border
+    texture.set_wrap(gl::CLAMP_TO_EDGE, gl::CLAMP_TO_EDGE);
+    Rc::new(texture)
+}
+
+fn target_framebuffer(width: u32, height: u32) -> Result<Framebuffer, Box<dyn std::error::Error>> {
+    Framebuffer::builder(width, height)
+        .color_texture(target_texture(width, height))
+        .build()
+}
+
+fn step(
+    fragment: &'static str,
+    inputs: Vec<(&'static str, Input)>,
+    output: Output,
+) -> Result<Step, Box<dyn std::error::Error>> {
+    Ok(Step {
+        shader: PipelineShader::create(
+            Some(ShaderSource::String(FULLSCREEN_VERTEX)),
+            Some(ShaderSource::String(fragment)),
+        )?,
+        inputs,
+        output,
+        sampler: None,
+    })
+}
+
+fn blur_step(
+    radius: f32,
+    direction: Vector2<f32>,
+    input: Input,
+    output: Output,
+) -> Result<Step, Box<dyn std::error::Error>> {
+    let blur = step(BLUR, vec![("source", input)], output)?;
+    blur.shader.set_uniform("radius", radius);
+    blur.shader.set_uniform("direction", direction);
+    Ok(blur)
+}
@@ -0,0 +1,246 @@
+//! Compute shader sources of the parallel primitives.
+//! The version line and the defines for T, OP, IDENTITY, KEY, INTEGER and GROUP_SIZE are prepended at runtime.
+
+/// Number of invocations per work group, shared by all kernels. Must be a power of two for the reduction.
+pub(super) const GROUP_SIZE: u32 = 256;
+
+/// Reduces every work group to a single value
+pub(super) const REDUCE: &str = "
+layout(local_size_x = GROUP_SIZE) in;
+
+layout(std430, binding = 0) readonly buffer Source { T src[]; };
+layout(std430, binding = 1) writeonly buffer Destination { T dst[]; };
+
+uniform uint count;
+
+shared T partial[GROUP_SIZE];
+
+void main() {
+    uint i = gl_GlobalInvocationID.x;
+    uint l = gl_LocalInvocationID.x;
+
+    partial[l] = i < count ? src[i] : IDENTITY;
+    barrier();
+
+    for (uint stride = uint(GROUP_SIZE) / 2u; stride > 0u; stride >>= 1) {
+        if (l < stride) {
+            partial[l] = OP(partial[l], partial[l + stride]);
+        }
+        barrier();
+    }
+
+    if (l == 0u) {
+        dst[gl_WorkGroupID.x] = partial[0];
+    }
+}
+";
+
+/// Inclusive scan of every work group, the total of each group is written to sums
+pub(super) const SCAN_BLOCK: &str = "
+layout(local_size_x = GROUP_SIZE) in;
+
+layout(std430, binding = 0) readonly buffer Source { T src[]; };
+layout(std430, binding = 1) writeonly buffer Destination { T dst[]; };
+layout(std430, binding = 2) writeonly buffer Sums { T sums[]; };
+
+uniform uint count;
+
+shared T temp[GROUP_SIZE];
+
+void main() {
+    uint i = gl_GlobalInvocationID.x;
+    uint l = gl_LocalInvocationID.x;
+
+    temp[l] = i < count ? src[i] : IDENTITY;
+    barrier();
+
+    for (uint offset = 1u; offset < uint(GROUP_SIZE); offset <<= 1) {
+        T value = temp[l];
+        if (l >= offset) {
+            value = OP(temp[l - offset], value);
+        }
+        barrier();
+        temp[l] = value;
+        barrier();
+    }
+
+    if (i < count) {
+        dst[i] = temp[l];
+    }
+
+    if (l == uint(GROUP_SIZE) - 1u) {
+        sums[gl_WorkGroupID.x] = temp[GROUP_SIZE - 1];
+    }
+}
+";
+
+/// Adds the scanned totals of all previous work groups
+pub(super) const SCAN_ADD: &str = "
+layout(local_size_x = GROUP_SIZE) in;
+
+layout(std430, binding = 1) buffer Destination { T dst[]; };
+layout(std430, binding = 2) readonly buffer Sums { T sums[]; };
+
+uniform uint count;
+
+void main() {
+    uint i = gl_GlobalInvocationID.x;
+    if (i >= count || gl_WorkGroupID.x == 0u) return;
+
+    dst[i] = OP(sums[gl_WorkGroupID.x - 1u], dst[i]);
+}
+";
+
+/// Turns an inclusive scan into an exclusive one
+pub(super) const SCAN_SHIFT: &str = "
+layout(local_size_x = GROUP_SIZE) in;
+
+layout(std430, binding = 0) readonly buffer Source { T src[]; };
+layout(std430, binding = 1) writeonly buffer Destination { T dst[]; };
+
+uniform uint count;
+
+void main() {
+    uint i = gl_GlobalInvocationID.x;
+    if (i >= count) return;
+
+    dst[i] = i == 0u ? IDENTITY : src[i - 1u];
+}
+";
+
+/// Converts arbitrary flags into 0 or 1
+pub(super) const FLAGS: &str = "
+layout(local_size_x = GROUP_SIZE) in;
+
+layout(std430, binding = 0) readonly buffer Source { uint src[]; };
+layout(std430, binding = 1) writeonly buffer Destination { uint dst[]; };
+
+uniform uint count;
+
+void main() {
+    uint i = gl_GlobalInvocationID.x;
+    if (i >= count) return;
+
+    dst[i] = src[i] != 0u ? 1u : 0u;
+}
+";
+
+/// Moves every flagged element to its position given by the inclusive scan of the flags
+pub(super) const COMPACT_SCATTER: &str = "
+layout(local_size_x = GROUP_SIZE) in;
+
+layout(std430, binding = 0) readonly buffer Source { T src[]; };
+layout(std430, binding = 1) writeonly buffer Destination { T dst[]; };
+layout(std430, binding = 2) readonly buffer Flags { uint flags[]; };
+layout(std430, binding = 3) readonly buffer Positions { uint positions[]; };
+
+uniform uint count;
+
+void main() {
+    uint i = gl_GlobalInvocationID.x;
+    if (i >= count || flags[i] == 0u) return;
+
+    dst[positions[i] - 1u] = src[i];
+}
+";
+
+/// Marks every key whose current radix bit is zero
+pub(super) const SORT_FLAGS: &str = "
+layout(local_size_x = GROUP_SIZE) in;
+
+layout(std430, binding = 0) readonly buffer Keys { T keys[]; };
+layout(std430, binding = 1) writeonly buffer Flags { uint flags[]; };
+
+uniform uint count;
+uniform uint bit;
+
+void main() {
+    uint i = gl_GlobalInvocationID.x;
+    if (i >= count) return;
+
+    flags[i] = ((KEY(keys[i]) >> bit) & 1u) == 0u ? 1u : 0u;
+}
+";
+
+/// Stable split of keys and values by the current radix bit
+pub(super) const SORT_SCATTER: &str = "
+layout(local_size_x = GROUP_SIZE) in;
+
+layout(std430, binding = 0) readonly buffer Keys { T keys[]; };
+layout(std430, binding = 1) writeonly buffer SortedKeys { T sorted_keys[]; };
+layout(std430, binding = 2) readonly buffer Positions { uint positions[]; };
+layout(std430, binding = 3) readonly buffer Values { uint values[]; };
+layout(std430, binding = 4) writeonly buffer SortedValues { uint sorted_values[]; };
+
+uniform uint count;
+uniform uint bit;
+uniform uint has_values;
+
+void main() {
+    uint i = gl_GlobalInvocationID.x;
+    if (i >= count) return;
+
+    uint zeros = positions[count - 1u];
+    uint target;
+    if (((KEY(keys[i]) >> bit) & 1u) == 0u) {
+        target = positions[i] - 1u;
+    } else {
+        target = zeros + i - positions[i];
+    }
+
+    sorted_keys[target] = keys[i];
+    if (has_values != 0u) {
+        sorted_values[target] = values[i];
+    }
+}
+";
+
+/// Counts the values falling into each of the equally sized bins between lower and upper
+pub(super) const HISTOGRAM: &str = "
+layout(local_size_x = GROUP_SIZE) in;
+
+layout(std430, binding = 0) readonly buffer Source { T src[]; };
+layout(std430, binding = 1) buffer Bins { uint bins[]; };
+
+uniform uint count;
+uniform uint bin_count;
+uniform T lower;
+uniform T upper;
+
+void main() {
+    uint i = gl_GlobalInvocationID.x;
+    if (i >= count) return;
+
+    T value = src[i];
+    if (value < lower || value > upper) return;
+
+#if INTEGER
+    // Exact: the largest bin with bin * range <= offset * bin_count, comparing 64 bit products.
+    // The keys keep the ordering, so their differences are the exact distances.
+    uint offset = KEY(value) - KEY(lower);
+    uint range = KEY(upper) - KEY(lower);
+
+    uint target_high, target_low;
+    umulExtended(offset, bin_count, target_high, target_low);
+
+    uint bin = 0u;
+    uint last = bin_count - 1u;
+    while (bin < last) {
+        uint mid = bin + (last - bin + 1u) / 2u;
+        uint high, low;
+        umulExtended(mid, range, high, low);
+        if (high < target_high || (high == target_high && low <= target_low)) {
+            bin = mid;
+        } else {
+            last = mid - 1u;
+        }
+    }
+#else
+    // Converted first, the differences may not fit into T
+    float position = (float(value) - float(lower)) / (float(upper) - float(lower));
+    uint bin = min(uint(position * float(bin_count)), bin_count - 1u);
+#endif
+
+    atomicAdd(bins[bin], 1u);
+}
+";
@@ -0,0 +1,560 @@
+//! This module implements common parallel primitives on top of ComputeShader and SSBO:
+//! reductions, prefix scans, radix sort, stream compaction and histograms.
+//! All primitives work on ssbos of u32, i32 or f32 and leave the cpu side content untouched,
+//! call SSBO::load to retrieve the results.
+//!
+//! The primitives bind their buffers to the shader storage binding indices 0 to 4,
+//! the previous bindings are restored afterwards.
+
+mod kernels;
+
+use std::{ffi::c_void, marker::PhantomData};
+
+use crate::{shader::Uniform, ComputeShader, SSBO};
+
+use kernels::GROUP_SIZE;
+
+/// Barrier issued after every dispatch, so the next kernel and buffer reads see the results
+const BARRIER: gl::types::GLbitfield =
+    gl::SHADER_STORAGE_BARRIER_BIT | gl::BUFFER_UPDATE_BARRIER_BIT;
+
+/// Number of binding indices used by the kernels
+const BINDINGS: usize = 5;
+
+/// GLSL expressions combining a and b
+const SUM: &str = "((a) + (b))";
+const MIN: &str = "min((a), (b))";
+const MAX: &str = "max((a), (b))";
+
+/// Implemented by the scalar types the primitives support
+pub trait Scalar: Copy + Default + PartialOrd + Uniform {
+    /// Name of the type in GLSL
+    const GLSL_TYPE: &'static str;
+    /// GLSL literal of zero
+    const ZERO: &'static str;
+    /// GLSL expression of the smallest value
+    const LOWEST: &'static str;
+    /// GLSL expression of the largest value
+    const HIGHEST: &'static str;
+    /// GLSL expression mapping x to an uint with the same ordering
+    const KEY: &'static str;
+    /// Whether the type is an integer, for which KEY also keeps the distances between values
+    const INTEGER: bool;
+}
+
+impl Scalar for u32 {
+    const GLSL_TYPE: &'static str = "uint";
+    const ZERO: &'static str = "0u";
+    const LOWEST: &'static str = "0u";
+    const HIGHEST: &'static str = "0xFFFFFFFFu";
+    const KEY: &'static str = "(x)";
+    const INTEGER: bool = true;
+}
+
+impl Scalar for i32 {
+    const GLSL_TYPE: &'static str = "int";
+    const ZERO: &'static str = "0";
+    const LOWEST: &'static str = "(-2147483647 - 1)";
+    const HIGHEST: &'static str = "2147483647";
+    const KEY: &'static str = "(uint(x) ^ 0x80000000u)";
+    const INTEGER: bool = true;
+}
+
+impl Scalar for f32 {
+    const GLSL_TYPE: &'static str = "float";
+    const ZERO: &'static str = "0.0";
+    const LOWEST: &'static str = "uintBitsToFloat(0xFF800000u)";
+    const HIGHEST: &'static str = "uintBitsToFloat(0x7F800000u)";
+    const KEY: &'static str =
+        "(floatBitsToUint(x) ^ ((floatBitsToUint(x) >> 31) == 1u ? 0xFFFFFFFFu : 0x80000000u))";
+    const INTEGER: bool = false;
+}
+
+/// Compiled kernels of all primitives for one scalar type
+///
+/// # Examples
+/// ```
+/// let primitives = Primitives::<f32>::new()?;
+///
+/// let values = SSBO::create_from(0, vec![3.0, 1.0, 2.0], gl::DYNAMIC_DRAW);
+/// assert_eq!(primitives.sum(&values)?, 6.0);
+///
+/// primitives.sort(&values)?;
+/// values.load();
+/// ```
+pub struct Primitives<T: Scalar> {
+    sum: ComputeShader,
+    min: ComputeShader,
+    max: ComputeShader,
+
+    scan_block: ComputeShader,
+    scan_add: ComputeShader,
+    scan_shift: ComputeShader,
+
+    // Scans of positions, used by sort and compaction
+    index_scan_block: ComputeShader,
+    index_scan_add: ComputeShader,
+
+    flags: ComputeShader,
+    compact_scatter: ComputeShader,
+    sort_flags: ComputeShader,
+    sort_scatter: ComputeShader,
+    histogram: ComputeShader,
+
+    scalar: PhantomData<T>,
+}
+
+impl<T: Scalar> Primitives<T> {
+    /// Compiles all kernels for the scalar type T
+    pub fn new() -> Result<Primitives<T>, Box<dyn std::error::Error>> {
+        Ok(Primitives {
+            sum: kernel::<T>(SUM, T::ZERO, kernels::REDUCE)?,
+            min: kernel::<T>(MIN, T::HIGHEST, kernels::REDUCE)?,
+            max: kernel::<T>(MAX, T::LOWEST, kernels::REDUCE)?,
+
+            scan_block: kernel::<T>(SUM, T::ZERO, kernels::SCAN_BLOCK)?,
+            scan_add: kernel::<T>(SUM, T::ZERO, kernels::SCAN_ADD)?,
+            scan_shift: kernel::<T>(SUM, T::ZERO, kernels::SCAN_SHIFT)?,
+
+            index_scan_block: kernel::<u32>(SUM, u32::ZERO, kernels::SCAN_BLOCK)?,
+            index_scan_add: kernel::<u32>(SUM, u32::ZERO, kernels::SCAN_ADD)?,
+
+            flags: kernel::<u32>(SUM, u32::ZERO, kernels::FLAGS)?,
+            compact_scatter: kernel::<T>(SUM, T::ZERO, kernels::COMPACT_SCATTER)?,
+            sort_flags: kernel::<T>(SUM, T::ZERO, kernels::SORT_FLAGS)?,
+            sort_scatter: kernel::<T>(SUM, T::ZERO, kernels::SORT_SCATTER)?,
+            histogram: kernel::<T>(SUM, T::ZERO, kernels::HISTOGRAM)?,
+
+            scalar: PhantomData,
+        })
+    }
+
+    /// Sum of all elements
+    pub fn sum(&self, input: &SSBO<Vec<T>>) -> Result<T, Box<dyn std::error::Error>> {
+        self.reduce(&self.sum, input)
+    }
+
+    /// Smallest element
+    pub fn min(&self, input: &SSBO<Vec<T>>) -> Result<T, Box<dyn std::error::Error>> {
+        self.reduce(&self.min, input)
+    }
+
+    /// Largest element
+    pub fn max(&self, input: &SSBO<Vec<T>>) -> Result<T, Box<dyn std::error::Error>> {
+        self.reduce(&self.max, input)
+    }
+
+    /// Writes the inclusive prefix sum of input to output.
+    /// output[i] = input[0] + ... + input[i]
+    ///
+    /// # Arguments
+    /// * `input` - The elements to be scanned
+    /// * `output` - Receives the scan, needs to be as long as input
+    pub fn inclusive_scan(
+        &self,
+        input: &SSBO<Vec<T>>,
+        output: &SSBO<Vec<T>>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        check_len("scan output", output.len(), input.len())?;
+
+        let _bindings = SavedBindings::new();
+        scan(
+            &self.scan_block,
+            &self.scan_add,
+            input.id(),
+            output.id(),
+            input.len(),
+        )
+    }
+
+    /// Writes the exclusive prefix sum of input to output.
+    /// output[0] = 0, output[i] = input[0] + ... + input[i - 1]
+    ///
+    /// # Arguments
+    /// * `input` - The elements to be scanned
+    /// * `output` - Receives the scan, needs to be as long as input
+    pub fn exclusive_scan(
+        &self,
+        input: &SSBO<Vec<T>>,
+        output: &SSBO<Vec<T>>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        check_len("scan output", output.len(), input.len())?;
+
+        let count = input.len();
+        if count == 0 {
+            return Ok(());
+        }
+
+        let _bindings = SavedBindings::new();
+
+        let inclusive = Scratch::new(count);
+        scan(
+            &self.scan_block,
+            &self.scan_add,
+            input.id(),
+            inclusive.id,
+            count,
+        )?;
+
+        bind(0, inclusive.id);
+        bind(1, output.id());
+        run(&self.scan_shift, count, &[])
+    }
+
+    /// Sorts the keys in ascending order
+    pub fn sort(&self, keys: &SSBO<Vec<T>>) -> Result<(), Box<dyn std::error::Error>> {
+        let _bindings = SavedBindings::new();
+        self.radix_sort(keys.id(), None, keys.len())
+    }
+
+    /// Sorts the keys in ascending order and moves the values along with their keys.
+    /// The sort is stable, values of equal keys keep their order.
+    ///
+    /// # Arguments
+    /// * `keys` - The keys to sort by
+    /// * `values` - One value per key
+    pub fn sort_by_key<V: Scalar>(
+        &self,
+        keys: &SSBO<Vec<T>>,
+        values: &SSBO<Vec<V>>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        check_len("sort values", values.len(), keys.len())?;
+
+        let _bindings = SavedBindings::new();
+        self.radix_sort(keys.id(), Some(values.id()), keys.len())
+    }
+
+    /// Copies all elements with a non zero flag to the front of output, keeping their order.
+    /// Returns the number of copied elements.
+    ///
+    /// # Arguments
+    /// * `input` - The elements to be filtered
+    /// * `flags` - One flag per element, elements with a flag of 0 are dropped
+    /// * `output` - Receives the kept elements, needs to be as long as input
+    pub fn compact(
+        &self,
+        input: &SSBO<Vec<T>>,
+        flags: &SSBO<Vec<u32>>,
+        output: &SSBO<Vec<T>>,
+    ) -> Result<usize, Box<dyn std::error::Error>> {
+        check_len("compaction flags", flags.len(), input.len())?;
+        check_len("compaction output", output.len(), input.len())?;
+
+        let count = input.len();
+        if count == 0 {
+            return Ok(0);
+        }
+
+        let _bindings = SavedBindings::new();
+
+        let normalized = Scratch::new(count);
+        bind(0, flags.id());
+        bind(1, normalized.id);
+        run(&self.flags, count, &[])?;
+
+        let positions = Scratch::new(count);
+        scan(
+            &self.index_scan_block,
+            &self.index_scan_add,
+            normalized.id,
+            positions.id,
+            count,
+        )?;
+
+        bind(0, input.id());
+        bind(1, output.id());
+        bind(2, normalized.id);
+        bind(3, positions.id);
+        run(&self.compact_scatter, count, &[])?;
+
+        Ok(positions.read::<u32>(count - 1, 1)[0] as usize)
+    }
+
+    /// Counts the elements falling into each of the equally sized bins between lower and upper.
+    /// Elements outside of the range are ignored, upper belongs to the last bin.
+    /// An element falls into bin `(value - lower) * bins / (upper - lower)`, which is exact for integers.
+    ///
+    /// # Arguments
+    /// * `input` - The elements to be counted
+    /// * `bins` - Number of bins
+    /// * `lower` - Lower bound of the first bin
+    /// * `upper` - Upper bound of the last bin
+    pub fn histogram(
+        &self,
+        input: &SSBO<Vec<T>>,
+        bins: u32,
+        lower: T,
+        upper: T,
+    ) -> Result<Vec<u32>, Box<dyn std::error::Error>> {
+        if bins == 0 {
+            Err("A histogram needs at least one bin")?
+        }
+
+        if lower.partial_cmp(&upper) != Some(std::cmp::Ordering::Less) {
+            Err("The lower bound of a histogram needs to be smaller than the upper bound")?
+        }
+
+        let _bindings = SavedBindings::new();
+
+        let counts = Scratch::with_data(&vec![0u32; bins as usize]);
+        if input.is_empty() {
+            return Ok(counts.read::<u32>(0, bins as usize));
+        }
+
+        self.histogram.set_uniform("bin_count", bins);
+        self.histogram.set_uniform("lower", lower);
+        self.histogram.set_uniform("upper", upper);
+
+        bind(0, input.id());
+        bind(1, counts.id);
+        run(&self.histogram, input.len(), &[])?;
+
+        Ok(counts.read::<u32>(0, bins as usize))
+    }
+
+    fn reduce(
+        &self,
+        shader: &ComputeShader,
+        input: &SSBO<Vec<T>>,
+    ) -> Result<T, Box<dyn std::error::Error>> {
+        if input.is_empty() {
+            Err("Can't reduce an empty buffer")?
+        }
+
+        let _bindings = SavedBindings::new();
+
+        // Every pass reduces each work group to a single value
+        let mut count = input.len();
+        let mut partials = Scratch::new(count.div_ceil(GROUP_SIZE as usize));
+        bind(0, input.id());
+        bind(1, partials.id);
+        run(shader, count, &[])?;
+
+        count = count.div_ceil(GROUP_SIZE as usize);
+        while count > 1 {
+            let next = Scratch::new(count.div_ceil(GROUP_SIZE as usize));
+            bind(0, partials.id);
+            bind(1, next.id);
+            run(shader, count, &[])?;
+
+            partials = next;
+            count = count.div_ceil(GROUP_SIZE as usize);
+        }
+
+        Ok(partials.read::<T>(0, 1)[0])
+    }
+
+    fn radix_sort(
+        &self,
+        keys: u32,
+        values: Option<u32>,
+        count: usize,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        if count < 2 {
+            return Ok(());
+        }
+
+        let key_buffer = Scratch::new(count);
+        let value_buffer = Scratch::new(if values.is_some() { count } else { 1 });
+        let flags = Scratch::new(count);
+        let positions = Scratch::new(count);
+
+        let (mut keys_in, mut keys_out) = (keys, key_buffer.id);
+        let (mut values_in, mut values_out) = match values {
+            Some(values) => (values, value_buffer.id),
+            None => (value_buffer.id, value_buffer.id),
+        };
+
+        let has_values = values.is_some() as u32;
+
+        // One stable split per bit, after an even number of passes the result is back in keys
+        for bit in 0..32u32 {
+            bind(0, keys_in);
+            bind(1, flags.id);
+            run(&self.sort_flags, count, &[("bit", bit)])?;
+
+            scan(
+                &self.index_scan_block,
+                &self.index_scan_add,
+                flags.id,
+                positions.id,
+                count,
+            )?;
+
+            bind(0, keys_in);
+            bind(1, keys_out);
+            bind(2, positions.id);
+            bind(3, values_in);
+            bind(4, values_out);
+            run(
+                &self.sort_scatter,
+                count,
+                &[("bit", bit), ("has_values", has_values)],
+            )?;
+
+            std::mem::swap(&mut keys_in, &mut keys_out);
+            std::mem::swap(&mut values_in, &mut values_out);
+        }
+
+        Ok(())
+    }
+}
+
+/// Compiles one kernel for the scalar type S
+///
+/// # Arguments
+/// * `op` - GLSL expression combining a and b
+/// * `identity` - Identity element of op
+/// * `body` - Kernel source without version and defines
+fn kernel<S: Scalar>(
+    op: &str,
+    identity: &str,
+    body: &str,
+) -> Result<ComputeShader, Box<dyn std::error::Error>> {
+    let source = format!(
+        "#version 430\n#define T {}\n#define OP(a, b) {}\n#define IDENTITY {}\n#define KEY(x) {}\n#define INTEGER {}\n#define GROUP_SIZE {}\n{}",
+        S::GLSL_TYPE,
+        op,
+        identity,
+        S::KEY,
+        S::INTEGER as u32,
+        GROUP_SIZE,
+        body
+    );
+
+    ComputeShader::from_source(source)
+}
+
+/// Inclusive scan from src to dst, scanning the work group totals recursively
+fn scan(
+    block: &ComputeShader,
+    add: &ComputeShader,
+    src: u32,
+    dst: u32,
+    count: usize,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if count == 0 {
+        return Ok(());
+    }
+
+    let groups = count.div_ceil(GROUP_SIZE as usize);
+    let sums = Scratch::new(groups);
+
+    bind(0, src);
+    bind(1, dst);
+    bind(2, sums.id);
+    run(block, count, &[])?;
+
+    if groups > 1 {
+        let scanned_sums = Scratch::new(groups);
+        scan(block, add, sums.id, scanned_sums.id, groups)?;
+
+        bind(1, dst);
+        bind(2, scanned_sums.id);
+        run(add, count, &[])?;
+    }
+
+    Ok(())
+}
+
+/// Dispatches one thread per element
+fn run(
+    shader: &ComputeShader,
+    count: usize,
+    uniforms: &[(&str, u32)],
+) -> Result<(), Box<dyn std::error::Error>> {
+    shader.set_uniform("count", count as u32);
+    for (name, value) in uniforms {
+        shader.set_uniform(name, *value);
+    }
+
+    shader.dispatch_threads(count as u32, 1, 1, BARRIER)
+}
+
+fn bind(binding: u32, buffer: u32) {
+    unsafe { gl::BindBufferBase(gl::SHADER_STORAGE_BUFFER, binding, buffer) };
+}
+
+fn check_len(name: &str, len: usize, expected: usize) -> Result<(), Box<dyn std::error::Error>> {
+    if len < expected {
+        Err(format!(
+            "The {} holds {} elements, but needs at least {}",
+            name, len, expected
+        ))?
+    }
+
+    Ok(())
+}
+
+/// Temporary gpu buffer of 4 byte elements
+struct Scratch {
+    id: u32,
+}
+
+impl Drop for Scratch {
+    fn drop(&mut self) {
+        unsafe { gl::DeleteBuffers(1, &self.id) }
+    }
+}
+
+impl Scratch {
+    fn new(len: usize) -> Scratch {
+        Scratch::create((len.max(1) * 4) as isize, std::ptr::null())
+    }
+
+    fn with_data(data: &[u32]) -> Scratch {
+        Scratch::create(
+            (data.len().max(1) * 4) as isize,
+            data.as_ptr() as *const c_void,
+        )
+    }
+
+    fn create(size: isize, data: *const c_void) -> Scratch {
+        let mut id = 0;
+        unsafe {
+            gl::CreateBuffers(1, &mut id);
+            gl::NamedBufferData(id, size, data, gl::DYNAMIC_COPY);
+        }
+
+        Scratch { id }
+    }
+
+    /// Reads len elements starting at the given element index
+    fn read<S: Copy + Default>(&self, index: usize, len: usize) -> Vec<S> {
+        let mut data = vec![S::default(); len];
+        let size = std::mem::size_of::<S>();
+        unsafe {
+            gl::GetNamedBufferSubData(
+                self.id,
+                (index * size) as isize,
+                (len * size) as isize,
+                data.as_mut_ptr() as *mut c_void,
+            );
+        }
+        data
+    }
+}
+
+/// Restores the shader storage bindings used by the kernels when dropped
+struct SavedBindings {
+    buffers: [i32; BINDINGS],
+}
+
+impl SavedBindings {
+    fn new() -> SavedBindings {
+        let mut buffers = [0; BINDINGS];
+        for (binding, buffer) in buffers.iter_mut().enumerate() {
+            unsafe { gl::GetIntegeri_v(gl::SHADER_STORAGE_BUFFER_BINDING, binding as u32, buffer) };
+        }
+
+        SavedBindings { buffers }
+    }
+}
+
+impl Drop for SavedBindings {
+    fn drop(&mut self) {
+        for (binding, buffer) in self.buffers.iter().enumerate() {
+            bind(binding as u32, *buffer as u32);
+        }
+    }
+}
@@ -0,0 +1,308 @@
+//! This module bundles the fixed function state of a draw call.
+//! The last applied state is remembered, so only differing parts are sent to OpenGL.
+
+use std::cell::Cell;
+
+thread_local! {
+    /// State applied last on this thread's context
+    static CURRENT: Cell<Option<RenderState>> = const { Cell::new(None) };
+}
+
+/// How the output of the fragment shader is combined with the framebuffer content
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlendMode {
+    /// The output overwrites the framebuffer
+    None,
+    /// Classic transparency using the alpha of the output
+    Alpha,
+    /// Transparency for colors which are already multiplied with their alpha
+    Premultiplied,
+    /// Adds the output weighted by its alpha, e.g. for light or particles
+    Additive,
+    /// Multiplies the framebuffer with the output, e.g. for shadows
+    Multiply,
+    /// Any blend function. See https://www.khronos.org/registry/OpenGL-Refpages/gl4/html/glBlendFunc.xhtml
+    Custom {
+        src: gl::types::GLenum,
+        dst: gl::types::GLenum,
+        equation: gl::types::GLenum,
+    },
+}
+
+impl BlendMode {
+    /// Source and destination factors for color and alpha, and the equation
+    fn functions(self) -> Option<[gl::types::GLenum; 5]> {
+        match self {
+            BlendMode::None => None,
+            BlendMode::Alpha => Some([
+                gl::SRC_ALPHA,
+                gl::ONE_MINUS_SRC_ALPHA,
+                gl::ONE,
+                gl::ONE_MINUS_SRC_ALPHA,
+                gl::FUNC_ADD,
+            ]),
+            BlendMode::Premultiplied => Some([
+                gl::ONE,
+                gl::ONE_MINUS_SRC_ALPHA,
+                gl::ONE,
+                gl::ONE_MINUS_SRC_ALPHA,
+                gl::FUNC_ADD,
+            ]),
+            BlendMode::Additive => Some([gl::SRC_ALPHA, gl::ONE, gl::ONE, gl::ONE, gl::FUNC_ADD]),
+            BlendMode::Multiply => Some([
+                gl::DST_COLOR,
+                gl::ZERO,
+                gl::DST_ALPHA,
+                gl::ZERO,
+                gl::FUNC_ADD,
+            ]),
+            BlendMode::Custom { src, dst, equation } => Some([src, dst, src, dst, equation]),
+        }
+    }
+}
+
+/// Stencil test and operations
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StencilState {
+    /// Comparison against the stencil buffer, e.g. ALWAYS or EQUAL
+    pub func: gl::types::GLenum,
+    /// Value compared against and written by REPLACE
+    pub reference: i32,
+    /// Bits taking part in the comparison
+    pub read_mask: u32,
+    /// Bits which can be written
+    pub write_mask: u32,
+    /// Operation if the stencil test fails, e.g. KEEP
+    pub fail: gl::types::GLenum,
+    /// Operation if the stencil test passes but the depth test fails
+    pub depth_fail: gl::types::GLenum,
+    /// Operation if both tests pass, e.g. REPLACE
+    pub pass: gl::types::GLenum,
+}
+
+impl StencilState {
+    /// Writes the reference value wherever something is drawn, e.g. to create a mask
+    pub fn write(reference: i32) -> StencilState {
+        StencilState {
+            func: gl::ALWAYS,
+            reference,
+            read_mask: 0xFF,
+            write_mask: 0xFF,
+            fail: gl::KEEP,
+            depth_fail: gl::KEEP,
+            pass: gl::REPLACE,
+        }
+    }
+
+    /// Only draws where the stencil buffer equals the reference value, leaving it untouched
+    pub fn equal(reference: i32) -> StencilState {
+        StencilState {
+            func: gl::EQUAL,
+            reference,
+            read_mask: 0xFF,
+            write_mask: 0x00,
+            fail: gl::KEEP,
+            depth_fail: gl::KEEP,
+            pass: gl::KEEP,
+        }
+    }
+}
+
+/// Depth, blending, culling, polygon mode, scissor, stencil and color mask of a draw call.
+/// The default matches the initial OpenGL state.
+///
+/// # Examples
+/// ```
+/// let transparent = RenderState::default()
+///     .blend(BlendMode::Alpha)
+///     .depth_test(Some(gl::LESS))
+///     .depth_write(false);
+///
+/// window.draw_with(&glass, &transparent);
+///
+/// // Every following draw of the window is rendered as wireframe
+/// window.set_render_state(RenderState::default().polygon_mode(gl::LINE));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RenderState {
+    blend: BlendMode,
+    depth_test: Option<gl::types::GLenum>,
+    depth_write: bool,
+    cull_face: Option<gl::types::GLenum>,
+    front_face: gl::types::GLenum,
+    polygon_mode: gl::types::GLenum,
+    scissor: Option<[i32; 4]>,
+    stencil: Option<StencilState>,
+    color_mask: [bool; 4],
+}
+
+impl Default for RenderState {
+    fn default() -> Self {
+        Self {
+            blend: BlendMode::None,
+            depth_test: None,
+            depth_write: true,
+            cull_face: None,
+            front_face: gl::CCW,
+            polygon_mode: gl::FILL,
+            scissor: None,
+            stencil: None,
+            color_mask: [true; 4],
+        }
+    }
+}
+
+impl RenderState {
+    pub fn blend(self, blend: BlendMode) -> RenderState {
+        RenderState { blend, ..self }
+    }
+
+    /// Depth comparison, e.g. LESS or LEQUAL. None disables the depth test.
+    pub fn depth_test(self, depth_test: Option<gl::types::GLenum>) -> RenderState {
+        RenderState { depth_test, ..self }
+    }
+
+    /// Whether passing fragments write their depth
+    pub fn depth_write(self, depth_write: bool) -> RenderState {
+        RenderState {
+            depth_write,
+            ..self
+        }
+    }
+
+    /// Faces to be discarded, e.g. BACK. None disables culling.
+    pub fn cull_face(self, cull_face: Option<gl::types::GLenum>) -> RenderState {
+        RenderState { cull_face, ..self }
+    }
+
+    /// Winding order of front faces, CCW or CW
+    pub fn front_face(self, front_face: gl::types::GLenum) -> RenderState {
+        RenderState { front_face, ..self }
+    }
+
+    /// FILL, LINE or POINT
+    pub fn polygon_mode(self, polygon_mode: gl::types::GLenum) -> RenderState {
+        RenderState {
+            polygon_mode,
+            ..self
+        }
+    }
+
+    /// Restricts drawing to a rectangle given as x, y, width, height from the bottom left corner
+    pub fn scissor(self, scissor: Option<[i32; 4]>) -> RenderState {
+        RenderState { scissor, ..self }
+    }
+
+    /// Stencil test and operations. None disables the stencil test.
+    pub fn stencil(self, stencil: Option<StencilState>) -> RenderState {
+        RenderState { stencil, ..self }
+    }
+
+    /// Which of the red, green, blue and alpha channels are written
+    pub fn color_mask(self, color_mask: [bool; 4]) -> RenderState {
+        RenderState { color_mask, ..self }
+    }
+
+    /// Makes this the current state. Only the parts that differ from the last applied state are changed.
+    pub fn apply(&self) {
+        let previous = CURRENT.with(|current| current.replace(Some(*self)));
+        let changed = |same: fn(&RenderState, &RenderState) -> bool| match &previous {
+            Some(previous) => !same(previous, self),
+            None => true,
+        };
+
+        unsafe {
+            if changed(|a, b| a.blend == b.blend) {
+                match self.blend.functions() {
+                    Some([src, dst, src_alpha, dst_alpha, equation]) => {
+                        gl::Enable(gl::BLEND);
+                        gl::BlendFuncSeparate(src, dst, src_alpha, dst_alpha);
+                        gl::BlendEquation(equation);
+                    }
+                    None => gl::Disable(gl::BLEND),
+                }
+            }
+
+            if changed(|a, b| a.depth_test == b.depth_test) {
+                match self.depth_test {
+                    Some(func) => {
+                        gl::Enable(gl::DEPTH_TEST);
+                        gl::DepthFunc(func);
+                    }
+                    None => gl::Disable(gl::DEPTH_TEST),
+                }
+            }
+
+            if changed(|a, b| a.depth_write == b.depth_write) {
+                gl::DepthMask(gl_bool(self.depth_write));
+            }
+
+            if changed(|a, b| a.cull_face == b.cull_face) {
+                match self.cull_face {
+                    Some(face) => {
+                        gl::Enable(gl::CULL_FACE);
+                        gl::CullFace(face);
+                    }
+                    None => gl::Disable(gl::CULL_FACE),
+                }
+            }
+
+            if changed(|a, b| a.front_face == b.front_face) {
+                gl::FrontFace(self.front_face);
+            }
+
+            if changed(|a, b| a.polygon_mode == b.polygon_mode) {
+                gl::PolygonMode(gl::FRONT_AND_BACK, self.polygon_mode);
+            }
+
+            if changed(|a, b| a.scissor == b.scissor) {
+                match self.scissor {
+                    Some([x, y, width, height]) => {
+                        gl::Enable(gl::SCISSOR_TEST);
+                        gl::Scissor(x, y, width, height);
+                    }
+                    None => gl::Disable(gl::SCISSOR_TEST),
+                }
+            }
+
+            if changed(|a, b| a.stencil == b.stencil) {
+                match self.stencil {
+                    Some(stencil) => {
+                        gl::Enable(gl::STENCIL_TEST);
+                        gl::StencilFunc(stencil.func, stencil.reference, stencil.read_mask);
+                        gl::StencilMask(stencil.write_mask);
+                        gl::StencilOp(stencil.fail, stencil.depth_fail, stencil.pass);
+                    }
+                    None => {
+                        gl::Disable(gl::STENCIL_TEST);
+                        gl::StencilMask(0xFF);
+                    }
+                }
+            }
+
+            if changed(|a, b| a.color_mask == b.color_mask) {
+                let [r, g, b, a] = self.color_mask;
+                gl::ColorMask(gl_bool(r), gl_bool(g), gl_bool(b), gl_bool(a));
+            }
+        }
+    }
+
+    /// Forgets the last applied state, so the next apply() sets everything.
+    /// Needed after changing the state with raw OpenGL calls or creating a new context.
+    pub fn invalidate() {
+        CURRENT.with(|current| current.set(None));
+    }
+
+    /// The last applied state, if it is still known
+    pub(crate) fn current() -> Option<RenderState> {
+        CURRENT.with(|current| current.get())
+    }
+}
+
+fn gl_bool(value: bool) -> gl::types::GLboolean {
+    if value {
+        gl::TRUE
+    } else {
+        gl::FALSE
+    }
+}
@@ -0,0 +1,395 @@
+//! This module implements a scene graph. Every node is placed relative to its parent,
+//! so moving, rotating or hiding a node affects everything attached to it.
+
+use std::{cell::Cell, error::Error};
+
+use crate::{
+    shapes::{Drawable, Shape2D},
+    transform::Placement,
+    PipelineShader, Transform2D,
+};
+
+/// Handle of a node in a Scene.
+/// Handles of removed nodes stay invalid, even when their slot is reused by a new node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NodeId {
+    index: usize,
+    generation: u32,
+}
+
+struct Node {
+    placement: Placement, // relative to the parent
+    drawable: Option<Box<dyn Drawable>>,
+
+    parent: Option<NodeId>,
+    children: Vec<NodeId>, // sorted by order
+
+    visible: bool,
+    order: i32,
+
+    // Transform into the space of the scene, None when it has to be computed again.
+    // A node only has a cached transform if its parent has one as well.
+    world: Cell<Option<Transform2D>>,
+}
+
+struct Slot {
+    generation: u32,
+    node: Option<Node>,
+}
+
+/// A tree of nodes, each with a local placement, an optional drawable and children.
+/// The drawable of a node is placed inside the node, with the placement of the drawable applied first.
+/// World transforms are computed lazily when the scene is drawn or queried, and only for nodes that changed.
+///
+/// A node is drawn before its children, siblings are drawn in ascending order.
+/// Hidden nodes are skipped together with their children.
+///
+/// # Examples
+/// ```
+/// let mut scene = Scene::new();
+///
+/// let body = scene.add(None, Placement::new(Vector2::new(200.0, 200.0)))?;
+/// scene.set_drawable(body, Rect::new(body_corners))?;
+///
+/// // The arm swings around the shoulder, the hand follows
+/// let arm = scene.add(Some(body), Placement::new(Vector2::new(20.0, -30.0)))?;
+/// scene.set_drawable(arm, Rect::new(arm_corners))?;
+/// let hand = scene.add(Some(arm), Placement::new(Vector2::new(0.0, 40.0)))?;
+/// scene.set_drawable(hand, Rect::new(hand_corners))?;
+///
+/// // Drawn behind the body
+/// let shadow = scene.add(Some(body), Placement::default())?;
+/// scene.set_drawable(shadow, shadow_shape)?.set_order(shadow, -1)?;
+///
+/// loop {
+///     scene.node_placement_mut(arm).unwrap().angle = time.sin();
+///     scene.translate(Vector2::new(1.0, 0.0));
+///     window.draw(&scene);
+/// }
+/// ```
+pub struct Scene {
+    placement: Placement,
+
+    slots: Vec<Slot>,
+    free: Vec<usize>,
+    roots: Vec<NodeId>, // sorted by order
+}
+
+impl Drawable for Scene {
+    fn draw_in(&self, shader: &PipelineShader, parent: &Transform2D) {
+        let root = *parent * self.transform();
+        for &id in &self.roots {
+            self.draw_node(shader, id, &root);
+        }
+    }
+
+    fn placement(&self) -> &Placement {
+        &self.placement
+    }
+
+    fn placement_mut(&mut self) -> &mut Placement {
+        &mut self.placement
+    }
+}
+
+impl Shape2D for Scene {}
+
+impl Default for Scene {
+    fn default() -> Scene {
+        Scene::new()
+    }
+}
+
+impl Scene {
+    pub fn new() -> Scene {
+        Scene {
+            placement: Placement::default(),
+            slots: Vec::new(),
+            free: Vec::new(),
+            roots: Vec::new(),
+        }
+    }
+
+    /// Adds an empty, visible node, which can hold a drawable and group other nodes.
+    ///
+    /// # Arguments
+    /// * `parent` - The node to attach to, None for a top level node
+    /// * `placement` - Placement relative to the parent
+    pub fn add(
+        &mut self,
+        parent: Option<NodeId>,
+        placement: Placement,
+    ) -> Result<NodeId, Box<dyn Error>> {
+        if let Some(parent) = parent {
+            self.node(parent)?;
+        }
+
+        let node = Node {
+            placement,
+            drawable: None,
+            parent,
+            children: Vec::new(),
+            visible: true,
+            order: 0,
+            world: Cell::new(None),
+        };
+
+        let id = match self.free.pop() {
+            Some(index) => {
+                let slot = &mut self.slots[index];
+                slot.node = Some(node);
+                NodeId {
+                    index,
+                    generation: slot.generation,
+                }
+            }
+            None => {
+                self.slots.push(Slot {
+                    generation: 0,
+                    node: Some(node),
+                });
+                NodeId {
+                    index: self.slots.len() - 1,
+                    generation: 0,
+                }
+            }
+        };
+
+        self.siblings_mut(parent).push(id);
+        self.sort_siblings(parent);
+
+        Ok(id)
+    }
+
+    /// Removes the node together with all of its children
+    pub fn remove(&mut self, id: NodeId) -> Result<(), Box<dyn Error>> {
+        let parent = self.node(id)?.parent;
+        self.siblings_mut(parent).retain(|&sibling| sibling != id);
+
+        let mut stack = vec![id];
+        while let Some(id) = stack.pop() {
+            let slot = &mut self.slots[id.index];
+            if let Some(node) = slot.node.take() {
+                stack.extend(node.children);
+            }
+            slot.generation = slot.generation.wrapping_add(1);
+            self.free.push(id.index);
+        }
+
+        Ok(())
+    }
+
+    /// Attaches the node to another parent, keeping its local placement
+    ///
+    /// # Arguments
+    /// * `id` - The node to move, together with its children
+    /// * `parent` - The new parent, None to make it a top level node
+    pub fn set_parent(
+        &mut self,
+        id: NodeId,
+        parent: Option<NodeId>,
+    ) -> Result<&mut Self, Box<dyn Error>> {
+        let old = self.node(id)?.parent;
+
+        // Walks up from the new parent, the node must not be one of its ancestors
+        let mut ancestor = parent;
+        while let Some(current) = ancestor {
+            if current == id {
+                Err("A node can't be attached to itself or one of its children")?;
+            }
+            ancestor = self.node(current)?.parent;
+        }
+
+        self.siblings_mut(old).retain(|&sibling| sibling != id);
+        self.siblings_mut(parent).push(id);
+        self.sort_siblings(parent);
+
+        self.node_mut(id)?.parent = parent;
+        self.invalidate(id);
+
+        Ok(self)
+    }
+
+    /// Sets the drawable of the node, replacing the previous one
+    pub fn set_drawable<T: Drawable + 'static>(
+        &mut self,
+        id: NodeId,
+        drawable: T,
+    ) -> Result<&mut Self, Box<dyn Error>> {
+        self.node_mut(id)?.drawable = Some(Box::new(drawable));
+        Ok(self)
+    }
+
+    /// Removes the drawable from the node and returns it, the node and its children stay in the scene
+    pub fn take_drawable(&mut self, id: NodeId) -> Option<Box<dyn Drawable>> {
+        self.get_mut(id)?.drawable.take()
+    }
+
+    pub fn drawable(&self, id: NodeId) -> Option<&dyn Drawable> {
+        self.get(id)?.drawable.as_deref()
+    }
+
+    pub fn drawable_mut(&mut self, id: NodeId) -> Option<&mut dyn Drawable> {
+        match &mut self.get_mut(id)?.drawable {
+            Some(drawable) => Some(drawable.as_mut()),
+            None => None,
+        }
+    }
+
+    /// Placement of the node relative to its parent
+    pub fn node_placement(&self, id: NodeId) -> Option<&Placement> {
+        self.get(id).map(|node| &node.placement)
+    }
+
+    /// Gives mutable access to the placement of the node.
+    /// The world transforms of the node and its children are computed again when they are needed next.
+    pub fn node_placement_mut(&mut self, id: NodeId) -> Option<&mut Placement> {
+        self.get(id)?;
+        self.invalidate(id);
+        self.get_mut(id).map(|node| &mut node.placement)
+    }
+
+    /// Hidden nodes aren't drawn, neither are their children
+    pub fn set_visible(&mut self, id: NodeId, visible: bool) -> Result<&mut Self, Box<dyn Error>> {
+        self.node_mut(id)?.visible = visible;
+        Ok(self)
+    }
+
+    pub fn is_visible(&self, id: NodeId) -> Option<bool> {
+        self.get(id).map(|node| node.visible)
+    }
+
+    /// Sets the position of the node among its siblings, lower orders are drawn first.
+    /// Siblings with the same order are drawn in the order they were added.
+    pub fn set_order(&mut self, id: NodeId, order: i32) -> Result<&mut Self, Box<dyn Error>> {
+        let node = self.node_mut(id)?;
+        node.order = order;
+        let parent = node.parent;
+        self.sort_siblings(parent);
+        Ok(self)
+    }
+
+    pub fn order(&self, id: NodeId) -> Option<i32> {
+        self.get(id).map(|node| node.order)
+    }
+
+    pub fn parent(&self, id: NodeId) -> Option<NodeId> {
+        self.get(id)?.parent
+    }
+
+    /// The children of the node in draw order, empty if the node doesn't exist
+    pub fn children(&self, id: NodeId) -> &[NodeId] {
+        self.get(id).map_or(&[], |node| &node.children)
+    }
+
+    /// The top level nodes in draw order
+    pub fn roots(&self) -> &[NodeId] {
+        &self.roots
+    }
+
+    pub fn contains(&self, id: NodeId) -> bool {
+        self.get(id).is_some()
+    }
+
+    /// Number of nodes in the scene
+    pub fn len(&self) -> usize {
+        self.slots.len() - self.free.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The transform from the node into the window, including the placement of the scene
+    pub fn world_transform(&self, id: NodeId) -> Option<Transform2D> {
+        self.get(id)?;
+        Some(self.transform() * self.scene_transform(id))
+    }
+
+    /// Transform from the node into the space of the scene, cached until the node or an ancestor moves
+    fn scene_transform(&self, id: NodeId) -> Transform2D {
+        let node = self.slots[id.index].node.as_ref().unwrap();
+        if let Some(world) = node.world.get() {
+            return world;
+        }
+
+        let parent = node
+            .parent
+            .map_or(Transform2D::IDENTITY, |parent| self.scene_transform(parent));
+        let world = parent * node.placement.transform();
+        node.world.set(Some(world));
+        world
+    }
+
+    fn draw_node(&self, shader: &PipelineShader, id: NodeId, root: &Transform2D) {
+        let node = match self.get(id) {
+            Some(node) if node.visible => node,
+            _ => return,
+        };
+
+        if let Some(drawable) = &node.drawable {
+            drawable.draw_in(shader, &(*root * self.scene_transform(id)));
+        }
+
+        for &child in &node.children {
+            self.draw_node(shader, child, root);
+        }
+    }
+
+    /// Drops the cached transforms of the node and its children.
+    /// Stops at nodes without a cached transform, their children don't have one either.
+    fn invalidate(&self, id: NodeId) {
+        let mut stack = vec![id];
+        while let Some(id) = stack.pop() {
+            if let Some(node) = self.get(id) {
+                if node.world.take().is_some() {
+                    stack.extend_from_slice(&node.children);
+                }
+            }
+        }
+    }
+
+    fn siblings_mut(&mut self, parent: Option<NodeId>) -> &mut Vec<NodeId> {
+        match parent {
+            Some(parent) => &mut self.get_mut(parent).unwrap().children,
+            None => &mut self.roots,
+        }
+    }
+
+    fn sort_siblings(&mut self, parent: Option<NodeId>) {
+        let mut siblings = std::mem::take(self.siblings_mut(parent));
+        // Stable, so equal orders keep the order the nodes were added in
+        siblings.sort_by_key(|&sibling| self.order(sibling).unwrap_or(0));
+        *self.siblings_mut(parent) = siblings;
+    }
+
+    fn get(&self, id: NodeId) -> Option<&Node> {
+        self.slots
+            .get(id.index)
+            .filter(|slot| slot.generation == id.generation)?
+            .node
+            .as_ref()
+    }
+
+    fn get_mut(&mut self, id: NodeId) -> Option<&mut Node> {
+        self.slots
+            .get_mut(id.index)
+            .filter(|slot| slot.generation == id.generation)?
+            .node
+            .as_mut()
+    }
+
+    fn node(&self, id: NodeId) -> Result<&Node, Box<dyn Error>> {
+        match self.get(id) {
+            Some(node) => Ok(node),
+            None => Err(format!("{:?} isn't part of the scene", id))?,
+        }
+    }
+
+    fn node_mut(&mut self, id: NodeId) -> Result<&mut Node, Box<dyn Error>> {
+        match self.get_mut(id) {
+            Some(node) => Ok(node),
+            None => Err(format!("{:?} isn't part of the scene", id))?,
+        }
+    }
+}
@@ -1,9 +1,26 @@
 use std::ffi::CString;
 
+use vector::Vector3;
+
+use crate::{textures::Texture, GPU, SSBO};
+
 use super::{compile_shader, link_program, uniform::Uniform, ShaderSource};
 
+/// Layout of the parameters read by glDispatchComputeIndirect.
+/// See https://www.khronos.org/registry/OpenGL-Refpages/gl4/html/glDispatchComputeIndirect.xhtml
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct DispatchIndirectCommand {
+    pub num_groups_x: u32,
+    pub num_groups_y: u32,
+    pub num_groups_z: u32,
+}
+
+impl GPU for DispatchIndirectCommand {}
+
 pub struct ComputeShader {
     id: u32,
+    local_size: Vector3<u32>,
 }
 
 impl Drop for ComputeShader {
@@ -37,6 +54,11 @@ impl ComputeShader {
             ShaderSource::String(source_code) => String::from(source_code),
         };
 
+        ComputeShader::from_source(src)
+    }
+
+    /// Constructs a compute shader from source code generated at runtime
+    pub(crate) fn from_source(src: String) -> Result<ComputeShader, Box<dyn std::error::Error>> {
         let shader = unsafe { gl::CreateShader(gl::COMPUTE_SHADER) };
         compile_shader(&src, shader)?;
 
@@ -51,7 +73,53 @@ impl ComputeShader {
             gl::DeleteShader(shader);
         };
 
-        Ok(ComputeShader { id: program_id })
+        let mut local_size = [0 as gl::types::GLint; 3];
+        unsafe {
+            gl::GetProgramiv(
+                program_id,
+                gl::COMPUTE_WORK_GROUP_SIZE,
+                local_size.as_mut_ptr(),
+            );
+        }
+
+        Ok(ComputeShader {
+            id: program_id,
+            local_size: Vector3::new(
+                local_size[0] as u32,
+                local_size[1] as u32,
+                local_size[2] as u32,
+            ),
+        })
+    }
+
+    /// The work group size declared by the shaders layout(local_size_x = ...) qualifier
+    pub fn local_size(&self) -> Vector3<u32> {
+        self.local_size
+    }
+
+    /// Maximum number of work groups which can be dispatched in each dimension
+    pub fn max_work_group_count() -> Vector3<u32> {
+        Vector3::new(
+            indexed_limit(gl::MAX_COMPUTE_WORK_GROUP_COUNT, 0),
+            indexed_limit(gl::MAX_COMPUTE_WORK_GROUP_COUNT, 1),
+            indexed_limit(gl::MAX_COMPUTE_WORK_GROUP_COUNT, 2),
+        )
+    }
+
+    /// Maximum local size of a work group in each dimension
+    pub fn max_work_group_size() -> Vector3<u32> {
+        Vector3::new(
+            indexed_limit(gl::MAX_COMPUTE_WORK_GROUP_SIZE, 0),
+            indexed_limit(gl::MAX_COMPUTE_WORK_GROUP_SIZE, 1),
+            indexed_limit(gl::MAX_COMPUTE_WORK_GROUP_SIZE, 2),
+        )
+    }
+
+    /// Maximum number of invocations in a single work group
+    pub fn max_work_group_invocations() -> u32 {
+        let mut value = 0;
+        unsafe { gl::GetIntegerv(gl::MAX_COMPUTE_WORK_GROUP_INVOCATIONS, &mut value) };
+        value as u32
     }
 
     /// Dispatch this compute shader
@@ -77,11 +145,217 @@ impl ComputeShader {
     /// shader.dispatch(1,1,1, gl::SHADER_STORAGE_BARRIER_BIT);
     /// ```
     pub fn dispatch(&self, num_groups_x: u32, num_groups_y: u32, num_groups_z: u32, barrier: u32) {
+        self.launch(num_groups_x, num_groups_y, num_groups_z);
+        unsafe {
+            gl::MemoryBarrier(barrier);
+        }
+    }
+
+    /// Dispatch this compute shader without issuing a memory barrier afterwards
+    pub(crate) fn launch(&self, num_groups_x: u32, num_groups_y: u32, num_groups_z: u32) {
         unsafe {
             gl::UseProgram(self.id);
             gl::DispatchCompute(num_groups_x, num_groups_y, num_groups_z);
+        }
+    }
+
+    /// Dispatch enough work groups to cover the given number of threads.
+    /// The group counts are derived from the shaders local size and rounded up,
+    /// so the shader has to ignore invocations outside of the requested range.
+    ///
+    /// # Arguments
+    /// * `threads_x` - Number of threads in the x dimension
+    /// * `threads_y` - Number of threads in the y dimension
+    /// * `threads_z` - Number of threads in the z dimension
+    /// * `barrier` - The type of memory barrier to be used. See https://www.khronos.org/registry/OpenGL-Refpages/gl4/html/glMemoryBarrier.xhtml
+    ///
+    /// # Examples
+    /// ```
+    /// const SHADER_SOURCE: &str = "
+    ///     #version 430
+    ///     layout(local_size_x = 64) in;
+    ///
+    ///     uniform uint count;
+    ///
+    ///     void main() {
+    ///         if (gl_GlobalInvocationID.x >= count) return;
+    ///     }
+    /// ";
+    ///
+    /// let shader = ComputeShader::create(ShaderSource::String(SHADER_SOURCE))?;
+    /// shader.set_uniform("count", 1000 as u32);
+    /// shader.dispatch_threads(1000, 1, 1, gl::SHADER_STORAGE_BARRIER_BIT)?; // 16 work groups
+    /// ```
+    pub fn dispatch_threads(
+        &self,
+        threads_x: u32,
+        threads_y: u32,
+        threads_z: u32,
+        barrier: u32,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let groups = self.work_groups_for(threads_x, threads_y, threads_z)?;
+        self.dispatch(groups.x, groups.y, groups.z, barrier);
+
+        Ok(())
+    }
+
+    /// Calculates the number of work groups needed to cover the given number of threads.
+    /// Fails if the shaders local size or the resulting group counts exceed the implementation limits.
+    ///
+    /// # Arguments
+    /// * `threads_x` - Number of threads in the x dimension
+    /// * `threads_y` - Number of threads in the y dimension
+    /// * `threads_z` - Number of threads in the z dimension
+    pub fn work_groups_for(
+        &self,
+        threads_x: u32,
+        threads_y: u32,
+        threads_z: u32,
+    ) -> Result<Vector3<u32>, Box<dyn std::error::Error>> {
+        let local = self.local_size;
+        if local.x == 0 || local.y == 0 || local.z == 0 {
+            Err("Can't query the work group size of the compute shader")?
+        }
+
+        let max_size = ComputeShader::max_work_group_size();
+        if local.x > max_size.x || local.y > max_size.y || local.z > max_size.z {
+            Err(format!(
+                "Work group size ({}, {}, {}) exceeds GL_MAX_COMPUTE_WORK_GROUP_SIZE ({}, {}, {})",
+                local.x, local.y, local.z, max_size.x, max_size.y, max_size.z
+            ))?
+        }
+
+        let max_invocations = ComputeShader::max_work_group_invocations();
+        if local.x * local.y * local.z > max_invocations {
+            Err(format!(
+                "Work group size ({}, {}, {}) exceeds GL_MAX_COMPUTE_WORK_GROUP_INVOCATIONS ({})",
+                local.x, local.y, local.z, max_invocations
+            ))?
+        }
+
+        let groups = Vector3::new(
+            threads_x.div_ceil(local.x),
+            threads_y.div_ceil(local.y),
+            threads_z.div_ceil(local.z),
+        );
+
+        let max_count = ComputeShader::max_work_group_count();
+        if groups.x > max_count.x || groups.y > max_count.y || groups.z > max_count.z {
+            Err(format!(
+                "Dispatching ({}, {}, {}) threads needs ({}, {}, {}) work groups, \
+                which exceeds GL_MAX_COMPUTE_WORK_GROUP_COUNT ({}, {}, {})",
+                threads_x,
+                threads_y,
+                threads_z,
+                groups.x,
+                groups.y,
+                groups.z,
+                max_count.x,
+                max_count.y,
+                max_count.z
+            ))?
+        }
+
+        Ok(groups)
+    }
+
+    /// Dispatch this compute shader with group counts read from a buffer on the gpu,
+    /// e.g. written by a previous compute pass.
+    ///
+    /// # Arguments
+    /// * `buffer` - Ssbo containing a DispatchIndirectCommand at the given offset
+    /// * `offset` - Byte offset of the command inside the buffer, must be a multiple of 4
+    /// * `barrier` - The type of memory barrier to be used. See https://www.khronos.org/registry/OpenGL-Refpages/gl4/html/glMemoryBarrier.xhtml
+    ///
+    /// # Examples
+    /// ```
+    /// let command = DispatchIndirectCommand { num_groups_x: 10, num_groups_y: 1, num_groups_z: 1 };
+    /// let buffer = SSBO::create_from(3, command, gl::DYNAMIC_DRAW);
+    ///
+    /// shader.dispatch_indirect(&buffer, 0, gl::SHADER_STORAGE_BARRIER_BIT)?;
+    /// ```
+    pub fn dispatch_indirect<T: GPU>(
+        &self,
+        buffer: &SSBO<T>,
+        offset: usize,
+        barrier: u32,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        if offset & 3 != 0 {
+            Err(format!(
+                "Indirect dispatch offset {} is not a multiple of 4",
+                offset
+            ))?
+        }
+
+        let (_, len) = buffer.raw();
+        let command_len = std::mem::size_of::<DispatchIndirectCommand>();
+        if offset + command_len > len as usize {
+            Err(format!(
+                "Indirect dispatch command at offset {} doesn't fit into a buffer of {} bytes",
+                offset, len
+            ))?
+        }
+
+        unsafe {
+            gl::UseProgram(self.id);
+            gl::BindBuffer(gl::DISPATCH_INDIRECT_BUFFER, buffer.id());
+            gl::DispatchComputeIndirect(offset as isize);
+            gl::BindBuffer(gl::DISPATCH_INDIRECT_BUFFER, 0);
             gl::MemoryBarrier(barrier);
         }
+
+        Ok(())
+    }
+
+    /// Binds a texture as image, so the shader can load and store its texels.
+    ///
+    /// # Arguments
+    /// * `name` - Name of the image2D/image3D uniform
+    /// * `unit` - Image unit to be used
+    /// * `texture` - The texture to be bound
+    /// * `access` - READ_ONLY, WRITE_ONLY or READ_WRITE
+    /// * `format` - Format declared by the uniform's layout qualifier, e.g. RGBA32F
+    ///
+    /// # Examples
+    /// ```
+    /// const SHADER_SOURCE: &str = "
+    ///     #version 430
+    ///     layout(local_size_x = 8, local_size_y = 8) in;
+    ///
+    ///     layout(rgba32f) uniform writeonly image2D target;
+    ///
+    ///     void main() {
+    ///         imageStore(target, ivec2(gl_GlobalInvocationID.xy), vec4(1.0, 0.0, 0.0, 1.0));
+    ///     }
+    /// ";
+    ///
+    /// let shader = ComputeShader::create(ShaderSource::String(SHADER_SOURCE))?;
+    /// let texture = Texture2D::new(400, 400, gl::RGBA32F);
+    ///
+    /// shader.bind_image("target", 0, &texture, gl::WRITE_ONLY, gl::RGBA32F);
+    /// shader.dispatch_threads(400, 400, 1, gl::SHADER_IMAGE_ACCESS_BARRIER_BIT)?;
+    /// ```
+    pub fn bind_image<T: Texture>(
+        &self,
+        name: &str,
+        unit: u32,
+        texture: &T,
+        access: gl::types::GLenum,
+        format: gl::types::GLenum,
+    ) {
+        texture.bind_image(unit, 0, access, format);
+        self.set_uniform(name, unit as i32);
+    }
+
+    /// Binds a texture to a texture unit, so the shader can sample it.
+    ///
+    /// # Arguments
+    /// * `name` - Name of the sampler uniform
+    /// * `unit` - Texture unit to be used
+    /// * `texture` - The texture to be bound
+    pub fn bind_texture<T: Texture>(&self, name: &str, unit: u32, texture: &T) {
+        texture.bind(unit);
+        self.set_uniform(name, unit as i32);
     }
 
     pub fn set_uniform<T: Uniform>(&self, name: &str, value: T) {
@@ -93,3 +367,10 @@ impl ComputeShader {
         }
     }
 }
+
+/// Reads one component of an indexed implementation limit
+fn indexed_limit(limit: gl::types::GLenum, index: u32) -> u32 {
+    let mut value = 0;
+    unsafe { gl::GetIntegeri_v(limit, index, &mut value) };
+    value as u32
+}
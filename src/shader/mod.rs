@@ -3,8 +3,9 @@ mod pipeline_shader;
 mod uniform;
 
 pub use base::{compile_shader, link_program, string_from_shader_source, ShaderSource};
-pub use compute_shader::ComputeShader;
-pub use pipeline_shader::PipelineShader;
+pub use compute_shader::{ComputeShader, DispatchIndirectCommand};
+pub use pipeline_shader::{PipelineShader, ShaderAttribute};
+pub(crate) use uniform::Uniform;
 
 mod base {
     /// Enables loading shaders from either a file or a hardcoded str
@@ -6,6 +6,33 @@ use super::{
 
 pub struct PipelineShader {
     id: u32,
+    attributes: Vec<ShaderAttribute>,
+}
+
+/// An active vertex input of a pipeline shader, as reported by the driver
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShaderAttribute {
+    pub name: String,
+    pub location: u32,
+    /// GLSL type of the input (FLOAT_VEC3, UNSIGNED_INT ...)
+    pub gl_type: gl::types::GLenum,
+}
+
+impl ShaderAttribute {
+    /// Whether the shader reads the attribute as int or uint
+    pub fn is_integer(&self) -> bool {
+        matches!(
+            self.gl_type,
+            gl::INT
+                | gl::INT_VEC2
+                | gl::INT_VEC3
+                | gl::INT_VEC4
+                | gl::UNSIGNED_INT
+                | gl::UNSIGNED_INT_VEC2
+                | gl::UNSIGNED_INT_VEC3
+                | gl::UNSIGNED_INT_VEC4
+        )
+    }
 }
 
 impl Drop for PipelineShader {
@@ -88,7 +115,10 @@ impl PipelineShader {
             };
         }
 
-        Ok(PipelineShader { id: program_id })
+        Ok(PipelineShader {
+            id: program_id,
+            attributes: active_attributes(program_id),
+        })
     }
 
     /// Use this pipeline shaders.
@@ -110,6 +140,20 @@ impl PipelineShader {
         }
     }
 
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// The vertex inputs the shader actually reads, sorted by location
+    pub fn attributes(&self) -> &[ShaderAttribute] {
+        &self.attributes
+    }
+
+    /// Looks up a vertex input by its name in the shader
+    pub fn attribute(&self, name: &str) -> Option<&ShaderAttribute> {
+        self.attributes.iter().find(|a| a.name == name)
+    }
+
     pub fn set_uniform<T: Uniform>(&self, name: &str, value: T) {
         unsafe {
             gl::UseProgram(self.id);
@@ -119,3 +163,49 @@ impl PipelineShader {
         }
     }
 }
+
+/// Queries the active vertex inputs of a linked program
+fn active_attributes(program_id: u32) -> Vec<ShaderAttribute> {
+    let mut count = 0;
+    let mut max_length = 0;
+    unsafe {
+        gl::GetProgramiv(program_id, gl::ACTIVE_ATTRIBUTES, &mut count);
+        gl::GetProgramiv(program_id, gl::ACTIVE_ATTRIBUTE_MAX_LENGTH, &mut max_length);
+    }
+
+    let mut attributes = Vec::new();
+    for index in 0..count as u32 {
+        let mut name = vec![0u8; max_length.max(1) as usize];
+        let mut length = 0;
+        let mut size = 0;
+        let mut gl_type = 0;
+
+        let location = unsafe {
+            gl::GetActiveAttrib(
+                program_id,
+                index,
+                name.len() as i32,
+                &mut length,
+                &mut size,
+                &mut gl_type,
+                name.as_mut_ptr() as *mut _,
+            );
+            gl::GetAttribLocation(program_id, name.as_ptr() as *const _)
+        };
+
+        // Built-in inputs like gl_VertexID have no location
+        if location < 0 {
+            continue;
+        }
+
+        name.truncate(length as usize);
+        attributes.push(ShaderAttribute {
+            name: String::from_utf8_lossy(&name).into_owned(),
+            location: location as u32,
+            gl_type,
+        });
+    }
+
+    attributes.sort_by_key(|a| a.location);
+    attributes
+}
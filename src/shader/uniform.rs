@@ -2,6 +2,8 @@ use std::ffi::CString;
 
 use vector::{Vector2, Vector3, Vector4};
 
+use crate::{Color, Transform2D};
+
 pub trait Uniform {
     fn transfer(&self, shader_id: u32, name: CString);
 }
@@ -156,5 +158,32 @@ impl Uniform for Vector4<f32> {
     }
 }
 
+impl Uniform for Color {
+    fn transfer(&self, shader_id: u32, name: CString) {
+        unsafe {
+            gl::Uniform4f(
+                gl::GetUniformLocation(shader_id, name.as_ptr() as *const i8),
+                self.r,
+                self.g,
+                self.b,
+                self.a,
+            );
+        }
+    }
+}
+
+impl Uniform for Transform2D {
+    fn transfer(&self, shader_id: u32, name: CString) {
+        unsafe {
+            gl::UniformMatrix3fv(
+                gl::GetUniformLocation(shader_id, name.as_ptr() as *const i8),
+                1,
+                gl::FALSE,
+                self.to_mat3().as_ptr(),
+            );
+        }
+    }
+}
+
 // TODO implement other types of uniforms
 // maybe use a macro to generate the transfer function?
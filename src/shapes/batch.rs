@@ -0,0 +1,321 @@
+use std::cell::{Cell, RefCell};
+
+use vector::{Vector2, Vector3};
+
+use crate::{
+    render_state::{BlendMode, RenderState},
+    transform::Placement,
+    vertices::{IndexType, Vertex, EBO, VAO, VBO},
+    Color, PipelineShader, Transform2D,
+};
+
+use super::{binding::ShaderBinding, material::Material, Drawable, Shape2D};
+
+/// Shapes which can be merged into a Batch2D
+pub trait Batchable {
+    /// Appends the geometry of the shape, transformed by its placement
+    fn batch(&self, batch: &mut Batch2D);
+}
+
+/// Geometry of one shape handed to Batch2D::add
+pub(crate) struct Geometry<'a> {
+    pub(crate) mode: gl::types::GLenum,
+    pub(crate) vertices: &'a [Vertex],
+    pub(crate) indices: Option<&'a [u32]>,
+    pub(crate) transform: Transform2D,
+    pub(crate) material: &'a Material,
+}
+
+/// A range of indices drawn with one draw call
+struct Batch {
+    mode: gl::types::GLenum,
+    material: Material,
+    blend: Option<BlendMode>,
+    first: usize,
+    count: usize,
+}
+
+/// Collects many shapes into one shared vertex and index buffer, so they are drawn in as few draw calls as possible.
+/// The shapes are transformed on the cpu when they are pushed, later changes to them aren't picked up.
+///
+/// Strips, fans and loops are turned into lists, so shapes with different draw modes can share a draw call.
+/// A new draw call is needed whenever the primitive type (points, lines, triangles), the texture or the blend mode changes.
+/// Other draw modes, e.g. with adjacency, are drawn on their own.
+///
+/// # Examples
+/// ```
+/// let mut batch = Batch2D::new();
+///
+/// loop {
+///     batch.clear();
+///     for particle in &particles {
+///         batch.push(&particle.shape);
+///     }
+///
+///     batch.set_blend(Some(BlendMode::Additive));
+///     for light in &lights {
+///         batch.push(light);
+///     }
+///
+///     window.draw(&batch);
+/// }
+/// ```
+pub struct Batch2D {
+    placement: Placement,
+
+    vertices: Vec<Vertex>,
+    indices: Vec<u32>,
+    batches: Vec<Batch>,
+    blend: Option<BlendMode>,
+    dirty: Cell<bool>,
+
+    binding: ShaderBinding,
+    vao: VAO,
+    vbo: VBO,
+    ebo: RefCell<EBO>,
+}
+
+impl Drawable for Batch2D {
+    fn draw_in(&self, shader: &PipelineShader, parent: &Transform2D) {
+        if self.dirty.replace(false) {
+            self.vbo.transfer(&self.vertices);
+            self.ebo.borrow_mut().update(&self.indices);
+        }
+
+        shader.set_uniform("transform", *parent * self.transform());
+
+        shader.set_uniform("instanced", 0i32);
+
+        self.binding.bind(&self.vao, &self.vbo, shader, None);
+
+        // Batches without a blend mode use the state of the target, the default state if it is unknown
+        let previous = RenderState::current();
+        let ebo = self.ebo.borrow();
+        for batch in &self.batches {
+            match batch.blend {
+                Some(blend) => previous.unwrap_or_default().blend(blend).apply(),
+                None => previous.unwrap_or_default().apply(),
+            }
+
+            batch.material.apply(shader);
+            self.vao.draw_range(
+                batch.mode,
+                batch.first as i32,
+                batch.count as i32,
+                Some(&ebo),
+            );
+        }
+
+        match previous {
+            Some(previous) => previous.apply(),
+            None => RenderState::invalidate(),
+        }
+    }
+
+    fn placement(&self) -> &Placement {
+        &self.placement
+    }
+
+    fn placement_mut(&mut self) -> &mut Placement {
+        &mut self.placement
+    }
+}
+
+impl Shape2D for Batch2D {}
+
+impl Default for Batch2D {
+    fn default() -> Batch2D {
+        Batch2D::new()
+    }
+}
+
+impl Batch2D {
+    /// Creates an empty batch. The buffers grow as shapes are pushed and are kept when the batch is cleared.
+    pub fn new() -> Batch2D {
+        let vao = VAO::new();
+        let vbo = VBO::with_usage::<Vertex>(&[], gl::STREAM_DRAW);
+        let ebo = EBO::with_usage::<u32>(&[], gl::STREAM_DRAW);
+
+        Batch2D {
+            placement: Placement::default(),
+            vertices: Vec::new(),
+            indices: Vec::new(),
+            batches: Vec::new(),
+            blend: None,
+            dirty: Cell::new(false),
+            binding: ShaderBinding::default(),
+            vao,
+            vbo,
+            ebo: RefCell::new(ebo),
+        }
+    }
+
+    /// Adds a shape in its current position to the batch
+    pub fn push<T: Batchable>(&mut self, shape: &T) -> &mut Self {
+        shape.batch(self);
+        self
+    }
+
+    /// Sets the blend mode of the shapes pushed from now on.
+    /// With None, they are drawn with the render state of the target, e.g. the one of the window,
+    /// or with the default render state if the state of the target is unknown.
+    pub fn set_blend(&mut self, blend: Option<BlendMode>) -> &mut Self {
+        self.blend = blend;
+        self
+    }
+
+    /// Removes all shapes, keeping the buffers
+    pub fn clear(&mut self) -> &mut Self {
+        self.vertices.clear();
+        self.indices.clear();
+        self.batches.clear();
+        self.dirty.set(true);
+        self
+    }
+
+    /// Number of draw calls needed for the shapes pushed so far
+    pub fn draw_calls(&self) -> usize {
+        self.batches.len()
+    }
+
+    pub fn vertex_count(&self) -> usize {
+        self.vertices.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.batches.is_empty()
+    }
+
+    /// Transforms the geometry into the shared buffers and extends the last batch if possible
+    pub(crate) fn add(&mut self, geometry: Geometry) {
+        let base = self.vertices.len() as u32;
+        let textured = geometry.material.texture.is_some();
+        let tint = geometry.material.tint;
+
+        // Same transform as the default shader
+        self.vertices.extend(geometry.vertices.iter().map(|vertex| {
+            let position = vertex.position();
+            let transformed = geometry
+                .transform
+                .apply(Vector2::new(position.x, position.y));
+            let color = vertex.color();
+
+            Vertex::new(
+                Vector3::new(transformed.x, transformed.y, position.z),
+                // The default shader ignores the vertex colors of textured shapes
+                if textured {
+                    *color
+                } else {
+                    Color::new(
+                        color.r * tint.r,
+                        color.g * tint.g,
+                        color.b * tint.b,
+                        color.a * tint.a,
+                    )
+                },
+                *vertex.texture_coord(),
+            )
+        }));
+
+        let sequential: Vec<u32>;
+        let indices = match geometry.indices {
+            Some(indices) => indices,
+            None => {
+                sequential = (0..geometry.vertices.len() as u32).collect();
+                &sequential
+            }
+        };
+
+        let first = self.indices.len();
+        let mode = match list_mode(geometry.mode) {
+            Some(list) => {
+                for run in indices.split(|&index| index == u32::RESTART) {
+                    append_list(geometry.mode, run, &mut self.indices);
+                }
+                list
+            }
+            None => {
+                self.indices.extend_from_slice(indices);
+                geometry.mode
+            }
+        };
+
+        for index in &mut self.indices[first..] {
+            if *index != u32::RESTART {
+                *index += base;
+            }
+        }
+
+        // Untextured tints are already part of the vertex colors
+        let material = if textured {
+            geometry.material.clone()
+        } else {
+            Material::default()
+        };
+
+        let count = self.indices.len() - first;
+        match self.batches.last_mut() {
+            Some(last)
+                if last.mode == mode
+                    && list_mode(mode).is_some()
+                    && last.blend == self.blend
+                    && last.material.same(&material) =>
+            {
+                last.count += count
+            }
+            _ => self.batches.push(Batch {
+                mode,
+                material,
+                blend: self.blend,
+                first,
+                count,
+            }),
+        }
+
+        self.dirty.set(true);
+    }
+}
+
+/// The list mode a draw mode can be turned into
+fn list_mode(mode: gl::types::GLenum) -> Option<gl::types::GLenum> {
+    match mode {
+        gl::POINTS => Some(gl::POINTS),
+        gl::LINES | gl::LINE_STRIP | gl::LINE_LOOP => Some(gl::LINES),
+        gl::TRIANGLES | gl::TRIANGLE_STRIP | gl::TRIANGLE_FAN => Some(gl::TRIANGLES),
+        _ => None,
+    }
+}
+
+/// Appends the indices of one strip, fan or list as list indices
+fn append_list(mode: gl::types::GLenum, run: &[u32], out: &mut Vec<u32>) {
+    let n = run.len();
+    match mode {
+        gl::POINTS => out.extend_from_slice(run),
+        gl::LINES => run
+            .chunks_exact(2)
+            .for_each(|line| out.extend_from_slice(line)),
+        gl::LINE_STRIP | gl::LINE_LOOP => {
+            run.windows(2).for_each(|line| out.extend_from_slice(line));
+            if mode == gl::LINE_LOOP && n > 2 {
+                out.extend_from_slice(&[run[n - 1], run[0]]);
+            }
+        }
+        gl::TRIANGLES => run
+            .chunks_exact(3)
+            .for_each(|triangle| out.extend_from_slice(triangle)),
+        gl::TRIANGLE_STRIP | gl::TRIANGLE_FAN => {
+            for i in 0..n.saturating_sub(2) {
+                // Every second strip triangle is flipped to keep the winding order
+                let [a, b, c] = match mode {
+                    gl::TRIANGLE_FAN => [run[0], run[i + 1], run[i + 2]],
+                    _ if i % 2 == 0 => [run[i], run[i + 1], run[i + 2]],
+                    _ => [run[i + 1], run[i], run[i + 2]],
+                };
+                if a != b && b != c && a != c {
+                    out.extend_from_slice(&[a, b, c]);
+                }
+            }
+        }
+        _ => (),
+    }
+}
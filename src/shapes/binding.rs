@@ -0,0 +1,76 @@
+use std::cell::{Cell, RefCell};
+
+use crate::{
+    debug,
+    vertices::{Attribute, AttributeMismatch, Vertex, VertexLayout, VertexSource, VAO, VBO},
+    PipelineShader,
+};
+
+/// Inputs of the default shader which are only fed per instance, by SSBOShape2D or Instanced
+const INSTANCE_INPUTS: [&str; 5] = ["vOffset", "iCenter", "iAngle", "iScale", "iColor"];
+
+/// Binds the vertex attributes of a shape to whichever shader it is drawn with, by name
+pub(crate) struct ShaderBinding {
+    attributes: Vec<Attribute>,
+    shader: Cell<Option<(u32, u32)>>, // shader and instance vbo of the last bind
+    reported: RefCell<Vec<u32>>,
+}
+
+impl Default for ShaderBinding {
+    fn default() -> ShaderBinding {
+        ShaderBinding {
+            attributes: Vertex::attributes(),
+            shader: Cell::new(None),
+            reported: RefCell::new(Vec::new()),
+        }
+    }
+}
+
+impl ShaderBinding {
+    /// Rebinds the attributes if the shader or instances changed since the last draw.
+    /// Mismatches are reported to the debug output the first time a shape is drawn with a shader.
+    ///
+    /// # Arguments
+    /// * `instances` - Per instance attributes, when the shape is drawn instanced
+    pub(crate) fn bind(
+        &self,
+        vao: &VAO,
+        vbo: &VBO,
+        shader: &PipelineShader,
+        instances: Option<&VertexSource>,
+    ) {
+        let key = (shader.id(), instances.map_or(0, |source| source.vbo.id()));
+        if self.shader.get() == Some(key) {
+            return;
+        }
+        self.shader.set(Some(key));
+
+        let mut sources = vec![VertexSource::new::<Vertex>(vbo, &self.attributes)];
+        sources.extend(instances.copied());
+
+        let mismatches: Vec<AttributeMismatch> = vao
+            .bind_sources(&sources, shader)
+            .into_iter()
+            .filter(|mismatch| match mismatch {
+                AttributeMismatch::Missing { name, .. } => !INSTANCE_INPUTS.contains(&&name[..]),
+                _ => true,
+            })
+            .collect();
+
+        let mut reported = self.reported.borrow_mut();
+        if !mismatches.is_empty() && !reported.contains(&shader.id()) {
+            reported.push(shader.id());
+            for mismatch in mismatches {
+                debug::message(&format!("Shape attributes: {}", mismatch));
+            }
+        }
+    }
+
+    /// Renames the position, color and uv attributes and forces a rebind on the next draw
+    pub(crate) fn set_names(&mut self, names: [&'static str; 3]) {
+        for (attribute, name) in self.attributes.iter_mut().zip(names.iter()) {
+            attribute.name = name;
+        }
+        self.shader.set(None);
+    }
+}
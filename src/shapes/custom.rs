@@ -1,60 +1,108 @@
-use std::ffi::c_void;
+use std::rc::Rc;
 
-use vector::{Vector2, Vector3};
+use vector::Vector2;
 
 use crate::{
-    vertices::{Vertex, VAO, VBO},
-    PipelineShader,
+    textures::Texture2D,
+    transform::Placement,
+    vertices::{IndexType, Vertex, VertexSource, EBO, VAO, VBO},
+    Color, PipelineShader, Transform2D,
 };
 
-use super::{Drawable, Shape2D};
+use super::{
+    batch::{Batch2D, Batchable, Geometry},
+    binding::ShaderBinding,
+    material::Material,
+    vertices::{ShapeVertices, VerticesMut},
+    Drawable, Instanceable, Shape2D,
+};
 
 pub struct CustomShape2D {
-    center: Vector2<f32>,
-    angle: f32,
-    scale: f32,
+    placement: Placement,
+
+    material: Material,
+    binding: ShaderBinding,
 
-    vertices: Vec<Vertex>, // position is relative to center
+    vertices: ShapeVertices, // position is relative to center
 
     draw_mode: gl::types::GLenum,
 
     vao: VAO,
     vbo: VBO,
+    ebo: Option<EBO>,
+    indices: Vec<u32>, // copy of the ebo content, for batching
 }
 
 impl Drawable for CustomShape2D {
-    fn draw(&self, shader: &PipelineShader) {
-        let v3: Vector3<f32> = self.center.into();
-        shader.set_uniform("center", v3);
-
-        shader.set_uniform("angle", self.angle);
-
-        shader.set_uniform("scale", self.scale);
-
+    fn draw_in(&self, shader: &PipelineShader, parent: &Transform2D) {
+        self.prepare(shader, parent, None);
         self.vao
-            .draw(self.draw_mode, self.vertices.len() as i32, false);
+            .draw(self.draw_mode, self.count(), self.ebo.as_ref());
     }
 
-    fn center_mut(&mut self) -> &mut Vector2<f32> {
-        &mut self.center
+    fn placement(&self) -> &Placement {
+        &self.placement
     }
 
-    fn angle_mut(&mut self) -> &mut f32 {
-        &mut self.angle
+    fn placement_mut(&mut self) -> &mut Placement {
+        &mut self.placement
     }
+}
 
-    fn scale_mut(&mut self) -> &mut f32 {
-        &mut self.scale
+impl Instanceable for CustomShape2D {
+    fn draw_instanced(
+        &self,
+        shader: &PipelineShader,
+        parent: &Transform2D,
+        count: i32,
+        instances: &VertexSource,
+    ) {
+        self.prepare(shader, parent, Some(instances));
+        self.vao
+            .draw_instanced(self.draw_mode, self.count(), count, self.ebo.as_ref());
     }
+}
 
-    fn vbo(&self) -> &VBO {
-        &self.vbo
+impl Batchable for CustomShape2D {
+    fn batch(&self, batch: &mut Batch2D) {
+        batch.add(Geometry {
+            mode: self.draw_mode,
+            vertices: self.vertices.get(),
+            indices: self.ebo.as_ref().map(|_| &self.indices[..]),
+            transform: self.transform(),
+            material: &self.material,
+        });
     }
 }
 
 impl Shape2D for CustomShape2D {}
 
 impl CustomShape2D {
+    /// Sets the uniforms and binds the attributes for the next draw call
+    fn prepare(
+        &self,
+        shader: &PipelineShader,
+        parent: &Transform2D,
+        instances: Option<&VertexSource>,
+    ) {
+        shader.set_uniform("transform", *parent * self.transform());
+
+        shader.set_uniform("instanced", instances.is_some() as i32);
+
+        self.material.apply(shader);
+
+        self.vertices.upload(&self.vbo);
+        self.binding.bind(&self.vao, &self.vbo, shader, instances);
+    }
+
+    /// Number of vertices, or indices if the shape has an index list
+    fn count(&self) -> i32 {
+        match &self.ebo {
+            Some(ebo) => ebo.len() as i32,
+            None => self.vertices.get().len() as i32,
+        }
+    }
+
     /// Create a new custom shape.
     /// # Arguments
     /// * `vertices` - The vertices of the shape. If center is given, the vertices are relative to the center. Otherwise, they are relative to the origin and the center will be the average of the vertices.
@@ -68,26 +116,46 @@ impl CustomShape2D {
     /// const VERT_SHADER: &str = "
     ///     #version 430
     ///     layout (location = 4) in vec3 vPos;
-    ///     layout (location = 5) in vec3 vColor;
-    ///     layout (location = 6) in vec2 vTexCoord;
-    ///     out vec3 outColor;
+    ///     layout (location = 5) in vec4 vColor;
+    ///     out vec4 outColor;
     ///     void main() {
     ///         gl_Position = vec4(vPos.x, vPos.y, vPos.z, 1.0);
     ///         outColor = vColor;
     ///     }
     /// ";
     ///
-    /// let custom_shape_points = CustomShape::new(vertices, gl::TRIANGLE_FAN)
-    ///     .position_shader_location(4)
-    ///     .color_shader_location(5)
-    ///     .uv_shader_location(6);
+    /// // The inputs are found by name, whatever their location is
+    /// let custom_shape_points = CustomShape2D::new(vertices, gl::TRIANGLE_FAN);
+    /// custom_shape_points.draw(&shader);
     /// ```
     pub fn new(vertices: Vec<Vertex>, draw_mode: gl::types::GLenum) -> CustomShape2D {
+        CustomShape2D::with_usage(vertices, draw_mode, gl::STATIC_DRAW)
+    }
+
+    /// Creates a custom shape whose vertices are expected to change, see set_vertices.
+    /// # Arguments
+    /// * `vertices` - The vertices of the shape, may be empty
+    /// * `draw_mode` - The draw mode to use (POINTS, LINES, LINE_STRIP ...).
+    /// * `usage` - DYNAMIC_DRAW for vertices changing now and then, STREAM_DRAW for vertices changing every frame
+    ///
+    /// # Example
+    /// ```
+    /// let mut trail = CustomShape2D::with_usage(Vec::new(), gl::LINE_STRIP, gl::STREAM_DRAW);
+    /// ```
+    pub fn with_usage(
+        vertices: Vec<Vertex>,
+        draw_mode: gl::types::GLenum,
+        usage: gl::types::GLenum,
+    ) -> CustomShape2D {
         // Calculate center by averaging the vertices
         let center = vertices
             .iter()
             .fold(Vector2::default(), |acc, v| acc + (*v.position()).into());
-        let center = center * (1.0 / vertices.len() as f32);
+        let center = if vertices.is_empty() {
+            center
+        } else {
+            center * (1.0 / vertices.len() as f32)
+        };
 
         // Translate the vertices to the center
         let vertices = vertices
@@ -97,46 +165,170 @@ impl CustomShape2D {
                 let pos = *pos - center.into();
                 Vertex::new(pos.into(), *v.color(), *v.texture_coord())
             })
-            .collect();
+            .collect::<Vec<Vertex>>();
 
         let vao = VAO::new();
-        let vbo = VBO::new(Some(&vertices));
-
-        vbo.set_attributes(
-            0, // Default shader position location
-            3,
-            gl::FLOAT,
-            gl::FALSE,
-            std::mem::size_of::<Vertex>() as i32,
-            std::ptr::null(),
-        );
-
-        vbo.set_attributes(
-            1, // Default shader color location
-            3,
-            gl::FLOAT,
-            gl::FALSE,
-            std::mem::size_of::<Vertex>() as i32,
-            unsafe { std::ptr::null::<Vector3<f32>>().add(1) as *const c_void },
-        );
-
-        vbo.set_attributes(
-            2, // Default shader uv location
-            2,
-            gl::FLOAT,
-            gl::FALSE,
-            std::mem::size_of::<Vertex>() as i32,
-            unsafe { std::ptr::null::<Vector3<f32>>().add(2) as *const c_void },
-        );
+        let vbo = VBO::with_usage(&vertices, usage);
 
         CustomShape2D {
-            center,
-            angle: 0.0,
-            scale: 1.0,
-            vertices,
+            placement: Placement::new(center),
+            material: Material::default(),
+            binding: ShaderBinding::default(),
+            vertices: ShapeVertices::new(vertices),
             draw_mode,
             vao,
             vbo,
+            ebo: None,
+            indices: Vec::new(),
         }
     }
+
+    /// Creates a custom shape drawn through an index list, so vertices can be shared
+    /// # Arguments
+    /// * `vertices` - The vertices of the shape
+    /// * `indices` - Indices into the vertices, as u8, u16 or u32. The largest value of the type starts a new strip or fan.
+    /// * `draw_mode` - The draw mode to use (TRIANGLES, TRIANGLE_STRIP ...).
+    ///
+    /// # Example
+    /// ```
+    /// // A quad from 4 instead of 6 vertices
+    /// let quad = CustomShape2D::with_indices(corners, &[0u8, 1, 2, 2, 1, 3], gl::TRIANGLES);
+    ///
+    /// // Two separate strips in one shape
+    /// let strips = CustomShape2D::with_indices(
+    ///     vertices,
+    ///     &[0u16, 1, 2, 3, u16::RESTART, 4, 5, 6, 7],
+    ///     gl::TRIANGLE_STRIP,
+    /// );
+    /// ```
+    pub fn with_indices<T: IndexType>(
+        vertices: Vec<Vertex>,
+        indices: &[T],
+        draw_mode: gl::types::GLenum,
+    ) -> CustomShape2D {
+        let mut shape = CustomShape2D::new(vertices, draw_mode);
+        shape.set_indices(indices);
+        shape
+    }
+
+    /// Draws the shape through the given indices from now on, reusing the ebo if there is one
+    pub fn set_indices<T: IndexType>(&mut self, indices: &[T]) -> &mut Self {
+        self.indices = indices.iter().map(|index| index.widen()).collect();
+        match &mut self.ebo {
+            Some(ebo) => ebo.update(indices),
+            None => {
+                // The ebo is bound to the VAO which is bound during creation
+                self.vao.bind();
+                self.ebo = Some(EBO::with_usage(indices, self.vbo.usage()));
+            }
+        }
+        self
+    }
+
+    /// Draws all vertices in order again
+    pub fn clear_indices(&mut self) -> &mut Self {
+        self.ebo = None;
+        self.indices.clear();
+        self
+    }
+
+    pub fn vbo(&self) -> &VBO {
+        &self.vbo
+    }
+
+    pub fn ebo(&self) -> Option<&EBO> {
+        self.ebo.as_ref()
+    }
+
+    /// Replaces the vertices, reusing the vbo of the shape. It only grows when the vertices don't fit.
+    /// # Arguments
+    /// * `vertices` - The new vertices, relative to the center of the shape
+    ///
+    /// # Example
+    /// ```
+    /// let points = (0..width)
+    ///     .map(|x| Vertex::new(Vector3::new(x as f32, f(x), 0.0), color::GREEN, Vector2::default()))
+    ///     .collect();
+    /// plot.set_vertices(points);
+    /// ```
+    pub fn set_vertices(&mut self, vertices: Vec<Vertex>) -> &mut Self {
+        self.vertices.set(vertices, &self.vbo);
+        self
+    }
+
+    /// Sets the texture sampled by the default shader using the vertex uvs.
+    /// Without a texture, the vertex colors are used.
+    ///
+    /// # Arguments
+    /// * `texture` - Texture to be drawn, may be shared between shapes
+    ///
+    /// # Example
+    /// ```
+    /// let texture = Rc::new(Texture2D::from_file("assets/crate.png", gl::RGBA8)?);
+    /// shape.set_texture(Some(texture.clone())).set_tint(color::ORANGE);
+    /// ```
+    pub fn set_texture(&mut self, texture: Option<Rc<Texture2D>>) -> &mut Self {
+        self.material.texture = texture;
+        self
+    }
+
+    /// Sets the color the texture or vertex colors are multiplied with, WHITE by default
+    pub fn set_tint(&mut self, tint: Color) -> &mut Self {
+        self.material.tint = tint;
+        self
+    }
+
+    /// Sets the names of the shader inputs the position, color and uv coordinates are bound to.
+    /// By default they are bound to vPos, vColor and vTexCoord, in any shader the shape is drawn with.
+    ///
+    /// # Examples
+    /// ```
+    /// const VERT_SHADER: &str = "
+    ///     #version 430
+    ///     in vec3 aPosition;
+    ///     in vec4 aColor;
+    ///     out vec4 color;
+    ///     void main() {
+    ///         gl_Position = vec4(aPosition, 1.0);
+    ///         color = aColor;
+    ///     }
+    /// ";
+    ///
+    /// shape.set_attribute_names("aPosition", "aColor", "aUv");
+    /// shape.draw(&shader);
+    /// ```
+    pub fn set_attribute_names(
+        &mut self,
+        position: &'static str,
+        color: &'static str,
+        uv: &'static str,
+    ) -> &mut Self {
+        self.binding.set_names([position, color, uv]);
+        self
+    }
+
+    /// The vertices of the shape, positions are relative to its center
+    pub fn vertices(&self) -> &[Vertex] {
+        self.vertices.get()
+    }
+
+    /// Gives mutable access to the vertices, positions are relative to the center of the shape.
+    /// Changes are uploaded before the shape is drawn the next time.
+    ///
+    /// # Example
+    /// ```
+    /// // Fades the first corner out
+    /// *shape.vertices_mut()[0].color_mut() = color::WHITE.lerp(color::TRANSPARENT, t);
+    /// ```
+    pub fn vertices_mut(&mut self) -> VerticesMut<'_> {
+        self.vertices.get_mut()
+    }
+
+    pub fn texture(&self) -> Option<&Rc<Texture2D>> {
+        self.material.texture.as_ref()
+    }
+
+    pub fn tint(&self) -> Color {
+        self.material.tint
+    }
 }
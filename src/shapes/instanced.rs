@@ -0,0 +1,175 @@
+use std::cell::Cell;
+
+use vector::Vector2;
+
+use crate::{
+    color,
+    transform::Placement,
+    vertices::{Attribute, Vertex, VertexLayout, VertexSource, VBO},
+    Color, PipelineShader, Transform2D,
+};
+
+use super::{Drawable, Instanceable, Shape2D};
+
+/// Per instance data read by the default shader.
+/// The instance is placed inside the shape, before the transform of the shape is applied.
+/// The color is multiplied with the vertex color.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Vertex)]
+pub struct Instance {
+    /// Offset from the center of the shape, in the coordinates of its vertices
+    #[vertex(name = "iCenter")]
+    pub center: Vector2<f32>,
+    #[vertex(name = "iAngle")]
+    pub angle: f32,
+    #[vertex(name = "iScale")]
+    pub scale: f32,
+    #[vertex(name = "iColor")]
+    pub color: Color,
+}
+
+impl Instance {
+    /// Creates an unrotated, unscaled white instance at the given offset
+    pub fn new(center: Vector2<f32>) -> Instance {
+        Instance {
+            center,
+            angle: 0.0,
+            scale: 1.0,
+            color: color::WHITE,
+        }
+    }
+
+    pub fn angle(self, angle: f32) -> Instance {
+        Instance { angle, ..self }
+    }
+
+    pub fn scale(self, scale: f32) -> Instance {
+        Instance { scale, ..self }
+    }
+
+    pub fn color(self, color: Color) -> Instance {
+        Instance { color, ..self }
+    }
+}
+
+/// Draws a shape many times in a single draw call, with per instance attributes read from a vbo.
+/// The instance type can be any VertexLayout, its fields are bound to the shader inputs with the same names.
+/// Attributes with a divisor of 0 are advanced once per instance.
+///
+/// # Examples
+/// ```
+/// let quad = Rect::new(vec![...]);
+/// let instances = (0..1000)
+///     .map(|i| Instance::new(Vector2::new((i % 40) as f32 * 10.0, (i / 40) as f32 * 10.0)))
+///     .collect();
+///
+/// let mut markers = Instanced::new(quad, instances);
+/// markers.instances_mut()[0].color = color::RED;
+/// window.draw(&markers);
+/// ```
+///
+/// Custom instance data, for a custom shader:
+/// ```
+/// #[repr(C)]
+/// #[derive(Clone, Copy, Vertex)]
+/// struct Sprite {
+///     #[vertex(name = "iCenter")]
+///     center: Vector2<f32>,
+///     #[vertex(name = "iFrame")]
+///     frame: u32,
+///     // One palette entry shared by every 4 instances
+///     #[vertex(name = "iPalette", divisor = 4)]
+///     palette: u32,
+/// }
+///
+/// let sprites: Instanced<Rect, Sprite> = Instanced::new(quad, sprites);
+/// sprites.draw(&sprite_shader);
+/// ```
+pub struct Instanced<T: Instanceable, I: VertexLayout = Instance> {
+    shape: T,
+
+    instances: Vec<I>,
+    attributes: Vec<Attribute>,
+    dirty: Cell<bool>,
+
+    vbo: VBO,
+}
+
+impl<T: Instanceable, I: VertexLayout> Drawable for Instanced<T, I> {
+    fn draw_in(&self, shader: &PipelineShader, parent: &Transform2D) {
+        if self.instances.is_empty() {
+            return;
+        }
+
+        if self.dirty.replace(false) {
+            self.vbo.transfer(&self.instances);
+        }
+
+        self.shape.draw_instanced(
+            shader,
+            parent,
+            self.instances.len() as i32,
+            &VertexSource::new::<I>(&self.vbo, &self.attributes),
+        );
+    }
+
+    fn placement(&self) -> &Placement {
+        self.shape.placement()
+    }
+
+    fn placement_mut(&mut self) -> &mut Placement {
+        self.shape.placement_mut()
+    }
+}
+
+impl<T: Instanceable, I: VertexLayout> Shape2D for Instanced<T, I> {}
+
+impl<T: Instanceable, I: VertexLayout> Instanced<T, I> {
+    /// Creates instances of a shape
+    ///
+    /// # Arguments
+    /// * `shape` - The shape to be drawn for every instance
+    /// * `instances` - Per instance data, may be empty
+    pub fn new(shape: T, instances: Vec<I>) -> Instanced<T, I> {
+        let attributes = I::attributes()
+            .into_iter()
+            .map(|attribute| {
+                let divisor = attribute.instance_divisor().max(1);
+                attribute.divisor(divisor)
+            })
+            .collect();
+
+        Instanced {
+            vbo: VBO::from_data(&instances, gl::DYNAMIC_DRAW),
+            shape,
+            instances,
+            attributes,
+            dirty: Cell::new(false),
+        }
+    }
+
+    pub fn shape(&self) -> &T {
+        &self.shape
+    }
+
+    pub fn shape_mut(&mut self) -> &mut T {
+        &mut self.shape
+    }
+
+    pub fn instances(&self) -> &[I] {
+        &self.instances
+    }
+
+    /// Gives mutable access to the instances, they are uploaded before the next draw
+    pub fn instances_mut(&mut self) -> &mut Vec<I> {
+        self.dirty.set(true);
+        &mut self.instances
+    }
+
+    /// Replaces all instances, they are uploaded before the next draw
+    pub fn set_instances(&mut self, instances: Vec<I>) -> &mut Self {
+        self.instances = instances;
+        self.dirty.set(true);
+        self
+    }
+}
@@ -0,0 +1,53 @@
+use std::rc::Rc;
+
+use crate::{
+    color,
+    textures::{Sampler, Texture, Texture2D},
+    Color, PipelineShader,
+};
+
+/// Texture unit the default shader samples shape textures from
+const TEXTURE_UNIT: u32 = 0;
+
+/// Optional texture and tint of a shape, as understood by the default shader
+#[derive(Clone)]
+pub(crate) struct Material {
+    pub(crate) texture: Option<Rc<Texture2D>>,
+    pub(crate) tint: Color,
+}
+
+impl Default for Material {
+    fn default() -> Material {
+        Material {
+            texture: None,
+            tint: color::WHITE,
+        }
+    }
+}
+
+impl Material {
+    /// Whether both materials draw the same, so shapes using them can share a draw call
+    pub(crate) fn same(&self, other: &Material) -> bool {
+        let same_texture = match (&self.texture, &other.texture) {
+            (Some(a), Some(b)) => Rc::ptr_eq(a, b),
+            (None, None) => true,
+            _ => false,
+        };
+        same_texture && self.tint == other.tint
+    }
+
+    /// Sets the texture uniforms, so state of a previously drawn shape doesn't leak
+    pub(crate) fn apply(&self, shader: &PipelineShader) {
+        match &self.texture {
+            Some(texture) => {
+                texture.bind(TEXTURE_UNIT);
+                Sampler::unbind(TEXTURE_UNIT);
+                shader.set_uniform("tex", TEXTURE_UNIT as i32);
+                shader.set_uniform("textured", 1i32);
+            }
+            None => shader.set_uniform("textured", 0i32),
+        }
+
+        shader.set_uniform("tint", self.tint);
+    }
+}
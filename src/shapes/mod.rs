@@ -1,7 +1,17 @@
+mod batch;
+mod binding;
 mod custom;
+mod instanced;
+mod material;
 mod rect;
 mod shape;
+mod ssbo_shape;
+mod vertices;
 
+pub use batch::{Batch2D, Batchable};
 pub use custom::CustomShape2D;
+pub use instanced::{Instance, Instanced};
 pub use rect::Rect;
-pub use shape::{Drawable, Shape2D};
+pub use shape::{Drawable, Instanceable, Shape2D};
+pub use ssbo_shape::SSBOShape2D;
+pub use vertices::VerticesMut;
@@ -1,20 +1,34 @@
-use std::ffi::c_void;
+use std::rc::Rc;
 
 use vector::{Vector2, Vector3};
 
 use crate::{
-    vertices::{Vertex, EBO, VAO, VBO},
-    PipelineShader,
+    textures::Texture2D,
+    transform::Placement,
+    vertices::{Vertex, VertexSource, EBO, VAO, VBO},
+    Color, PipelineShader, Transform2D,
 };
 
-use super::{Drawable, Shape2D};
+use super::{
+    batch::{Batch2D, Batchable, Geometry},
+    binding::ShaderBinding,
+    material::Material,
+    vertices::{ShapeVertices, VerticesMut},
+    Drawable, Instanceable, Shape2D,
+};
+
+//  0     0 - 1
+//  | \     \ |
+//  3 - 2     2
+const INDICES: [u32; 6] = [0, 1, 2, 1, 2, 3];
 
 pub struct Rect {
-    center: Vector2<f32>,
-    angle: f32,
-    scale: f32,
+    placement: Placement,
+
+    material: Material,
+    binding: ShaderBinding,
 
-    vertices: Vec<Vertex>,
+    vertices: ShapeVertices, // position is relative to center
 
     vao: VAO,
     vbo: VBO,
@@ -22,37 +36,66 @@ pub struct Rect {
 }
 
 impl Drawable for Rect {
-    fn draw(&self, shader: &PipelineShader) {
-        let v3: Vector3<f32> = self.center.into();
-        shader.set_uniform("center", v3);
-
-        shader.set_uniform("angle", self.angle);
-
-        shader.set_uniform("scale", self.scale);
-
-        self.vao.draw(gl::TRIANGLE_STRIP, 6, true);
+    fn draw_in(&self, shader: &PipelineShader, parent: &Transform2D) {
+        self.prepare(shader, parent, None);
+        self.vao.draw(gl::TRIANGLE_STRIP, 6, Some(&self.ebo));
     }
 
-    fn center_mut(&mut self) -> &mut Vector2<f32> {
-        &mut self.center
+    fn placement(&self) -> &Placement {
+        &self.placement
     }
 
-    fn angle_mut(&mut self) -> &mut f32 {
-        &mut self.angle
+    fn placement_mut(&mut self) -> &mut Placement {
+        &mut self.placement
     }
+}
 
-    fn scale_mut(&mut self) -> &mut f32 {
-        &mut self.scale
+impl Instanceable for Rect {
+    fn draw_instanced(
+        &self,
+        shader: &PipelineShader,
+        parent: &Transform2D,
+        count: i32,
+        instances: &VertexSource,
+    ) {
+        self.prepare(shader, parent, Some(instances));
+        self.vao
+            .draw_instanced(gl::TRIANGLE_STRIP, 6, count, Some(&self.ebo));
     }
+}
 
-    fn vbo(&self) -> &VBO {
-        &self.vbo
+impl Batchable for Rect {
+    fn batch(&self, batch: &mut Batch2D) {
+        batch.add(Geometry {
+            mode: gl::TRIANGLE_STRIP,
+            vertices: self.vertices.get(),
+            indices: Some(&INDICES),
+            transform: self.transform(),
+            material: &self.material,
+        });
     }
 }
 
 impl Shape2D for Rect {}
 
 impl Rect {
+    /// Sets the uniforms and binds the attributes for the next draw call
+    fn prepare(
+        &self,
+        shader: &PipelineShader,
+        parent: &Transform2D,
+        instances: Option<&VertexSource>,
+    ) {
+        shader.set_uniform("transform", *parent * self.transform());
+
+        shader.set_uniform("instanced", instances.is_some() as i32);
+
+        self.material.apply(shader);
+
+        self.vertices.upload(&self.vbo);
+        self.binding.bind(&self.vao, &self.vbo, shader, instances);
+    }
+
     /// Create a new rectangle shape.
     /// # Arguments
     /// * `vertices` - The vertices of the shape. Corners should be in clockwise order starting from the top left corner<br/>
@@ -82,47 +125,97 @@ impl Rect {
             .collect::<Vec<Vertex>>();
 
         let vao = VAO::new();
-        let vbo = VBO::new(Some(&vertices));
-        //  0     0 - 1
-        //  | \     \ |
-        //  3 - 2     2
-        let ebo = EBO::new(Some(&vec![0, 1, 2, 1, 2, 3]));
-
-        vbo.set_attributes(
-            0, // Default shader position location
-            3,
-            gl::FLOAT,
-            gl::FALSE,
-            std::mem::size_of::<Vertex>() as i32,
-            std::ptr::null(),
-        );
-
-        vbo.set_attributes(
-            1, // Default shader color location
-            3,
-            gl::FLOAT,
-            gl::FALSE,
-            std::mem::size_of::<Vertex>() as i32,
-            unsafe { std::ptr::null::<Vector3<f32>>().add(1) as *const c_void },
-        );
-
-        vbo.set_attributes(
-            2, // Default shader uv location
-            2,
-            gl::FLOAT,
-            gl::FALSE,
-            std::mem::size_of::<Vertex>() as i32,
-            unsafe { std::ptr::null::<Vector3<f32>>().add(2) as *const c_void },
-        );
+        let vbo = VBO::from_vertices(&vertices);
+        let ebo = EBO::with_usage(&INDICES, gl::STATIC_DRAW);
 
         Rect {
-            center,
-            angle: 0.0,
-            scale: 1.0,
-            vertices,
+            placement: Placement::new(center),
+            material: Material::default(),
+            binding: ShaderBinding::default(),
+            vertices: ShapeVertices::new(vertices),
             vao,
             vbo,
             ebo,
         }
     }
+
+    /// Sets the texture sampled by the default shader using the vertex uvs.
+    /// Without a texture, the vertex colors are used.
+    ///
+    /// # Arguments
+    /// * `texture` - Texture to be drawn, may be shared between shapes
+    ///
+    /// # Example
+    /// ```
+    /// let texture = Rc::new(Texture2D::from_file("assets/crate.png", gl::RGBA8)?);
+    /// shape.set_texture(Some(texture.clone())).set_tint(color::ORANGE);
+    /// ```
+    pub fn set_texture(&mut self, texture: Option<Rc<Texture2D>>) -> &mut Self {
+        self.material.texture = texture;
+        self
+    }
+
+    /// Sets the color the texture or vertex colors are multiplied with, WHITE by default
+    pub fn set_tint(&mut self, tint: Color) -> &mut Self {
+        self.material.tint = tint;
+        self
+    }
+
+    /// Sets the names of the shader inputs the position, color and uv coordinates are bound to.
+    /// By default they are bound to vPos, vColor and vTexCoord, in any shader the shape is drawn with.
+    ///
+    /// # Examples
+    /// ```
+    /// const VERT_SHADER: &str = "
+    ///     #version 430
+    ///     in vec3 aPosition;
+    ///     in vec4 aColor;
+    ///     out vec4 color;
+    ///     void main() {
+    ///         gl_Position = vec4(aPosition, 1.0);
+    ///         color = aColor;
+    ///     }
+    /// ";
+    ///
+    /// shape.set_attribute_names("aPosition", "aColor", "aUv");
+    /// shape.draw(&shader);
+    /// ```
+    pub fn set_attribute_names(
+        &mut self,
+        position: &'static str,
+        color: &'static str,
+        uv: &'static str,
+    ) -> &mut Self {
+        self.binding.set_names([position, color, uv]);
+        self
+    }
+
+    /// The vertices of the shape, positions are relative to its center
+    pub fn vertices(&self) -> &[Vertex] {
+        self.vertices.get()
+    }
+
+    /// Gives mutable access to the vertices, positions are relative to the center of the shape.
+    /// Changes are uploaded before the shape is drawn the next time.
+    ///
+    /// # Example
+    /// ```
+    /// // Fades the first corner out
+    /// *shape.vertices_mut()[0].color_mut() = color::WHITE.lerp(color::TRANSPARENT, t);
+    /// ```
+    pub fn vertices_mut(&mut self) -> VerticesMut<'_> {
+        self.vertices.get_mut()
+    }
+
+    pub fn vbo(&self) -> &VBO {
+        &self.vbo
+    }
+
+    pub fn texture(&self) -> Option<&Rc<Texture2D>> {
+        self.material.texture.as_ref()
+    }
+
+    pub fn tint(&self) -> Color {
+        self.material.tint
+    }
 }
@@ -1,20 +1,60 @@
-use std::ffi::c_void;
+use vector::Vector2;
 
-use vector::{Vector2, Vector3};
-
-use crate::{
-    vertices::{Vertex, VBO},
-    PipelineShader,
-};
+use crate::{transform::Placement, vertices::VertexSource, PipelineShader, Transform2D};
 
 pub trait Drawable {
-    fn draw(&self, shader: &PipelineShader);
+    /// Draws the object inside a parent transform, which is applied after the transform of the object.
+    /// Used by Scene to place objects relative to their parent node.
+    ///
+    /// # Arguments
+    /// * `shader` - The shader to draw with
+    /// * `parent` - Transform of the space the object is placed in
+    fn draw_in(&self, shader: &PipelineShader, parent: &Transform2D);
+
+    fn draw(&self, shader: &PipelineShader) {
+        self.draw_in(shader, &Transform2D::IDENTITY);
+    }
+
+    /// Center, angle, scale, shear and origin of the object
+    fn placement(&self) -> &Placement;
+    fn placement_mut(&mut self) -> &mut Placement;
 
-    fn center_mut(&mut self) -> &mut Vector2<f32>;
-    fn angle_mut(&mut self) -> &mut f32;
-    fn scale_mut(&mut self) -> &mut f32;
+    /// The matrix the default shader places the vertices with
+    fn transform(&self) -> Transform2D {
+        self.placement().transform()
+    }
 
-    fn vbo(&self) -> &VBO;
+    fn center_mut(&mut self) -> &mut Vector2<f32> {
+        &mut self.placement_mut().center
+    }
+
+    fn angle_mut(&mut self) -> &mut f32 {
+        &mut self.placement_mut().angle
+    }
+
+    fn scale_mut(&mut self) -> &mut Vector2<f32> {
+        &mut self.placement_mut().scale
+    }
+}
+
+/// Shapes which can be drawn many times in one draw call, see Instanced
+pub trait Instanceable: Drawable {
+    /// Draws `count` instances of the shape.
+    /// The per instance attributes are bound to the shader inputs with the same names,
+    /// the default shader reads iCenter, iAngle, iScale and iColor.
+    ///
+    /// # Arguments
+    /// * `shader` - The shader to draw with
+    /// * `parent` - Transform of the space the shape is placed in, see Drawable::draw_in
+    /// * `count` - Number of instances
+    /// * `instances` - The per instance attributes, their divisors should be at least 1
+    fn draw_instanced(
+        &self,
+        shader: &PipelineShader,
+        parent: &Transform2D,
+        count: i32,
+        instances: &VertexSource,
+    );
 }
 
 pub trait Shape2D: Drawable {
@@ -24,7 +64,7 @@ pub trait Shape2D: Drawable {
         self
     }
 
-    /// Rotates the shape around its center by the given angle
+    /// Rotates the shape around its origin by the given angle
     fn rotate(&mut self, angle: f32) -> &mut Self {
         *self.angle_mut() += angle;
         self
@@ -32,114 +72,37 @@ pub trait Shape2D: Drawable {
 
     /// Scales the shape by the given factor
     fn scale(&mut self, scl: f32) -> &mut Self {
-        *self.scale_mut() += scl;
+        let scale = self.scale_mut();
+        scale.x *= scl;
+        scale.y *= scl;
         self
     }
 
-    /// Optionally set the position shader location of the shape.
-    /// # Arguments
-    /// * `location` - The location of the position shader attribute.
-    ///
-    /// # Example
-    /// ```
-    /// const VERT_SHADER: &str = "
-    ///     #version 430
-    ///     layout (location = 4) in vec3 vPos;
-    ///     layout (location = 5) in vec3 vColor;
-    ///     layout (location = 6) in vec2 vTexCoord;
-    ///     out vec3 outColor;
-    ///     void main() {
-    ///         gl_Position = vec4(vPos.x, vPos.y, vPos.z, 1.0);
-    ///         outColor = vColor;
-    ///     }
-    /// ";
-    ///
-    /// let custom_shape_points = CustomShape::new(vertices, gl::TRIANGLE_FAN)
-    ///     .position_shader_location(4)
-    ///     .color_shader_location(5)
-    ///     .uv_shader_location(6);
-    /// ```
-    fn position_shader_location(&mut self, location: u32) -> &mut Self {
-        self.vbo().set_attributes(
-            location,
-            3,
-            gl::FLOAT,
-            gl::FALSE,
-            std::mem::size_of::<Vertex>() as i32,
-            std::ptr::null(),
-        );
+    /// Scales the shape by different factors along its x and y axis
+    fn scale_xy(&mut self, factors: Vector2<f32>) -> &mut Self {
+        let scale = self.scale_mut();
+        scale.x *= factors.x;
+        scale.y *= factors.y;
         self
     }
 
-    /// Optionally set the color shader location of the shape.
-    /// # Arguments
-    /// * `location` - The location of the position shader attribute.
-    ///
-    /// # Example
-    /// ```
-    /// const VERT_SHADER: &str = "
-    ///     #version 430
-    ///     layout (location = 4) in vec3 vPos;
-    ///     layout (location = 5) in vec3 vColor;
-    ///     layout (location = 6) in vec2 vTexCoord;
-    ///     out vec3 outColor;
-    ///     void main() {
-    ///         gl_Position = vec4(vPos.x, vPos.y, vPos.z, 1.0);
-    ///         outColor = vColor;
-    ///     }
-    /// ";
-    ///
-    /// let custom_shape_points = CustomShape::new(vertices, gl::TRIANGLE_FAN)
-    ///     .position_shader_location(4)
-    ///     .color_shader_location(5)
-    ///     .uv_shader_location(6);
-    /// ```
-    fn color_shader_location(&mut self, location: u32) -> &mut Self {
-        self.vbo().set_attributes(
-            location,
-            3,
-            gl::FLOAT,
-            gl::FALSE,
-            std::mem::size_of::<Vertex>() as i32,
-            unsafe { std::ptr::null::<Vector3<f32>>().add(1) as *const c_void },
-        );
-
+    /// Shears the shape further, see Transform2D::shearing
+    fn shear(&mut self, shear: Vector2<f32>) -> &mut Self {
+        self.placement_mut().shear += shear;
         self
     }
 
-    /// Optionally set the uv shader location of the shape.
-    /// # Arguments
-    /// * `location` - The location of the position shader attribute.
+    /// Sets the point the shape is rotated and scaled around, relative to its center.
+    /// The shape moves so that this point ends up at the center.
     ///
-    /// # Example
+    /// # Examples
     /// ```
-    /// const VERT_SHADER: &str = "
-    ///     #version 430
-    ///     layout (location = 4) in vec3 vPos;
-    ///     layout (location = 5) in vec3 vColor;
-    ///     layout (location = 6) in vec2 vTexCoord;
-    ///     out vec3 outColor;
-    ///     void main() {
-    ///         gl_Position = vec4(vPos.x, vPos.y, vPos.z, 1.0);
-    ///         outColor = vColor;
-    ///     }
-    /// ";
-    ///
-    /// let custom_shape_points = CustomShape::new(vertices, gl::TRIANGLE_FAN)
-    ///     .position_shader_location(4)
-    ///     .color_shader_location(5)
-    ///     .uv_shader_location(6);
+    /// // A clock hand turning around its bottom end
+    /// hand.set_origin(Vector2::new(0.0, length / 2.0));
+    /// hand.rotate(std::f32::consts::PI / 30.0);
     /// ```
-    fn uv_shader_location(&mut self, location: u32) -> &mut Self {
-        self.vbo().set_attributes(
-            location,
-            2,
-            gl::FLOAT,
-            gl::FALSE,
-            std::mem::size_of::<Vertex>() as i32,
-            unsafe { std::ptr::null::<Vector3<f32>>().add(2) as *const c_void },
-        );
-
+    fn set_origin(&mut self, origin: Vector2<f32>) -> &mut Self {
+        self.placement_mut().origin = origin;
         self
     }
 }
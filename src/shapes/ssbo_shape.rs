@@ -0,0 +1,205 @@
+use std::rc::Rc;
+
+use vector::{Vector2, Vector3};
+
+use crate::{
+    color,
+    transform::Placement,
+    vertices::{Attribute, Vertex, VAO, VBO},
+    PipelineShader, Transform2D, SSBO,
+};
+
+use super::{material::Material, Drawable, Shape2D};
+
+/// How the elements of the ssbo are turned into geometry
+enum SSBODrawMode {
+    Points,
+    InstancedQuads,
+}
+
+/// Number of elements of a ssbo, without knowing their type
+trait Elements {
+    fn len(&self) -> usize;
+}
+
+impl<T> Elements for SSBO<Vec<T>> {
+    fn len(&self) -> usize {
+        Vec::len(self)
+    }
+}
+
+/// Draws the content of a ssbo without copying it back to the cpu,
+/// either as one point per element or as one quad instance per element.
+/// The shape shares ownership of the ssbo, so the buffer lives as long as the shape draws it.
+pub struct SSBOShape2D {
+    placement: Placement,
+
+    ssbo: Rc<dyn Elements>,
+    limit: Option<usize>,
+    draw_mode: SSBODrawMode,
+
+    vao: VAO,
+    vbo: VBO, // quad corners, empty when drawing points
+}
+
+impl Drawable for SSBOShape2D {
+    fn draw_in(&self, shader: &PipelineShader, parent: &Transform2D) {
+        shader.set_uniform("transform", *parent * self.transform());
+
+        shader.set_uniform("instanced", 0i32);
+
+        Material::default().apply(shader);
+
+        let count = self.count() as i32;
+        match self.draw_mode {
+            SSBODrawMode::Points => self.vao.draw(gl::POINTS, count, None),
+            SSBODrawMode::InstancedQuads => {
+                self.vao.draw_instanced(gl::TRIANGLE_STRIP, 4, count, None)
+            }
+        }
+    }
+
+    fn placement(&self) -> &Placement {
+        &self.placement
+    }
+
+    fn placement_mut(&mut self) -> &mut Placement {
+        &mut self.placement
+    }
+}
+
+impl Shape2D for SSBOShape2D {}
+
+impl SSBOShape2D {
+    /// Draws every element of the ssbo as a single point.
+    /// # Arguments
+    /// * `ssbo` - The ssbo holding the elements, usually written by a compute shader. Its content can only be changed on the cpu once the shape is dropped.
+    /// * `attributes` - Layout of one element. The default shader reads the position from location 0 and the color from location 1.
+    ///
+    /// # Example
+    /// ```
+    /// #[repr(C)]
+    /// struct Particle {
+    ///     position: Vector3<f32>,
+    ///     color: Color,
+    /// }
+    ///
+    /// let particles = Rc::new(SSBO::create_from(0, particles, gl::DYNAMIC_DRAW));
+    /// let shape = SSBOShape2D::points(
+    ///     Rc::clone(&particles),
+    ///     &[
+    ///         Attribute::new(0, 3, gl::FLOAT, 0),
+    ///         Attribute::new(1, 4, gl::FLOAT, std::mem::size_of::<Vector3<f32>>()),
+    ///     ],
+    /// );
+    ///
+    /// compute_shader.dispatch(1000, 1, 1, gl::VERTEX_ATTRIB_ARRAY_BARRIER_BIT);
+    /// window.draw(&shape);
+    /// ```
+    pub fn points<T: 'static>(ssbo: Rc<SSBO<Vec<T>>>, attributes: &[Attribute]) -> SSBOShape2D {
+        let vao = VAO::new();
+        let vbo = VBO::new(None);
+
+        vao.attach_ssbo(&ssbo, std::mem::size_of::<T>() as i32, attributes, 0);
+
+        SSBOShape2D {
+            placement: Placement::default(),
+            ssbo,
+            limit: None,
+            draw_mode: SSBODrawMode::Points,
+            vao,
+            vbo,
+        }
+    }
+
+    /// Draws one quad per element of the ssbo using instanced rendering.
+    /// # Arguments
+    /// * `ssbo` - The ssbo holding the elements, usually written by a compute shader. Its content can only be changed on the cpu once the shape is dropped.
+    /// * `size` - Width and height of each quad
+    /// * `attributes` - Per instance layout of one element. The default shader reads the instance offset from location 3 and the color from location 1.
+    ///
+    /// # Example
+    /// ```
+    /// let particles = Rc::new(SSBO::create_from(0, particles, gl::DYNAMIC_DRAW));
+    /// let shape = SSBOShape2D::instanced_quads(
+    ///     Rc::clone(&particles),
+    ///     Vector2::new(4.0, 4.0),
+    ///     &[
+    ///         Attribute::new(3, 3, gl::FLOAT, 0),
+    ///         Attribute::new(1, 4, gl::FLOAT, std::mem::size_of::<Vector3<f32>>()),
+    ///     ],
+    /// );
+    /// ```
+    pub fn instanced_quads<T: 'static>(
+        ssbo: Rc<SSBO<Vec<T>>>,
+        size: Vector2<f32>,
+        attributes: &[Attribute],
+    ) -> SSBOShape2D {
+        let (w, h) = (size.x / 2.0, size.y / 2.0);
+
+        //  2 - 3
+        //  | \ |
+        //  0 - 1
+        let corners = vec![
+            Vertex::new(
+                Vector3::new(-w, h, 0.0),
+                color::WHITE,
+                Vector2::new(0.0, 0.0),
+            ),
+            Vertex::new(
+                Vector3::new(w, h, 0.0),
+                color::WHITE,
+                Vector2::new(1.0, 0.0),
+            ),
+            Vertex::new(
+                Vector3::new(-w, -h, 0.0),
+                color::WHITE,
+                Vector2::new(0.0, 1.0),
+            ),
+            Vertex::new(
+                Vector3::new(w, -h, 0.0),
+                color::WHITE,
+                Vector2::new(1.0, 1.0),
+            ),
+        ];
+
+        let vao = VAO::new();
+        let vbo = VBO::from_vertices(&corners);
+
+        // Per instance attributes override the per vertex ones on the same location
+        vao.attach_ssbo(&ssbo, std::mem::size_of::<T>() as i32, attributes, 1);
+
+        SSBOShape2D {
+            placement: Placement::default(),
+            ssbo,
+            limit: None,
+            draw_mode: SSBODrawMode::InstancedQuads,
+            vao,
+            vbo,
+        }
+    }
+
+    /// The quad corners, empty when drawing points
+    pub fn vbo(&self) -> &VBO {
+        &self.vbo
+    }
+
+    /// Number of elements which are drawn, read from the ssbo every time
+    pub fn count(&self) -> usize {
+        let len = self.ssbo.len();
+        self.limit.map_or(len, |limit| limit.min(len))
+    }
+
+    /// Limits the number of drawn elements, e.g. to the number of alive particles.
+    /// The limit never exceeds the length of the ssbo.
+    pub fn set_count(&mut self, count: usize) -> &mut Self {
+        self.limit = Some(count);
+        self
+    }
+
+    /// Draws all elements of the ssbo again
+    pub fn clear_count(&mut self) -> &mut Self {
+        self.limit = None;
+        self
+    }
+}
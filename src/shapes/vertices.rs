@@ -0,0 +1,86 @@
+use std::{
+    cell::Cell,
+    ops::{Deref, DerefMut},
+};
+
+use crate::vertices::{Vertex, VBO};
+
+/// The vertices of a shape together with a flag telling whether the vbo is out of date
+pub(crate) struct ShapeVertices {
+    vertices: Vec<Vertex>,
+    dirty: Cell<bool>,
+}
+
+impl ShapeVertices {
+    pub(crate) fn new(vertices: Vec<Vertex>) -> ShapeVertices {
+        ShapeVertices {
+            vertices,
+            dirty: Cell::new(false),
+        }
+    }
+
+    pub(crate) fn get(&self) -> &[Vertex] {
+        &self.vertices
+    }
+
+    pub(crate) fn get_mut(&mut self) -> VerticesMut<'_> {
+        VerticesMut {
+            vertices: &mut self.vertices,
+            dirty: &self.dirty,
+            changed: false,
+        }
+    }
+
+    /// Replaces the vertices and uploads them right away
+    pub(crate) fn set(&mut self, vertices: Vec<Vertex>, vbo: &VBO) {
+        vbo.transfer(&vertices);
+        self.vertices = vertices;
+        self.dirty.set(false);
+    }
+
+    /// Uploads the vertices if they were changed since the last upload
+    pub(crate) fn upload(&self, vbo: &VBO) {
+        if self.dirty.replace(false) {
+            vbo.transfer(&self.vertices);
+        }
+    }
+}
+
+/// Mutable access to the vertices of a shape, returned by `vertices_mut`.
+/// If the vertices were changed, they are uploaded again before the shape is drawn next.
+///
+/// # Examples
+/// ```
+/// for (i, vertex) in shape.vertices_mut().iter_mut().enumerate() {
+///     *vertex.color_mut() = Color::from_hsv(time * 90.0 + i as f32 * 30.0, 1.0, 1.0);
+/// }
+/// window.draw(&shape);
+/// ```
+pub struct VerticesMut<'a> {
+    vertices: &'a mut [Vertex],
+    dirty: &'a Cell<bool>,
+    changed: bool,
+}
+
+impl Deref for VerticesMut<'_> {
+    type Target = [Vertex];
+
+    fn deref(&self) -> &[Vertex] {
+        self.vertices
+    }
+}
+
+impl DerefMut for VerticesMut<'_> {
+    fn deref_mut(&mut self) -> &mut [Vertex] {
+        self.changed = true;
+        self.vertices
+    }
+}
+
+impl Drop for VerticesMut<'_> {
+    fn drop(&mut self) {
+        if self.changed {
+            self.dirty.set(true);
+        }
+    }
+}
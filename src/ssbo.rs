@@ -71,6 +71,21 @@ impl<T: GPU> SSBO<T> {
         ssbo
     }
 
+    /// Binds this ssbo to the given binding index
+    ///
+    /// # Arguments
+    /// * `binding` - Binding index. See https://www.khronos.org/registry/OpenGL-Refpages/gl4/html/glBindBufferBase.xhtml
+    pub fn bind(&self, binding: u32) {
+        unsafe {
+            gl::BindBufferBase(gl::SHADER_STORAGE_BUFFER, binding, self.id);
+        }
+    }
+
+    /// OpenGL id of the underlying buffer
+    pub(crate) fn id(&self) -> u32 {
+        self.id
+    }
+
     /// Sends content to the gpu
     pub fn update(&mut self) {
         unsafe {
@@ -0,0 +1,294 @@
+use super::{
+    check_level, check_region, level_size, mip_levels, mipmappable, read_region, write_region,
+    PixelData, Texture,
+};
+
+/// Faces of a cubemap in the order OpenGL stores them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CubemapFace {
+    PositiveX,
+    NegativeX,
+    PositiveY,
+    NegativeY,
+    PositiveZ,
+    NegativeZ,
+}
+
+impl CubemapFace {
+    /// All faces in storage order, matching the order of the face arrays taken by Cubemap
+    pub const ALL: [CubemapFace; 6] = [
+        CubemapFace::PositiveX,
+        CubemapFace::NegativeX,
+        CubemapFace::PositiveY,
+        CubemapFace::NegativeY,
+        CubemapFace::PositiveZ,
+        CubemapFace::NegativeZ,
+    ];
+
+    /// Layer index of the face
+    pub fn index(self) -> u32 {
+        self as u32
+    }
+}
+
+/// Six square faces, sampled with a direction vector through a samplerCube, e.g. for skyboxes
+pub struct Cubemap {
+    id: u32,
+    size: u32,
+    levels: i32,
+    internal_format: gl::types::GLenum,
+}
+
+impl Drop for Cubemap {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteTextures(1, &self.id);
+        }
+    }
+}
+
+impl Texture for Cubemap {
+    fn id(&self) -> u32 {
+        self.id
+    }
+
+    fn target(&self) -> gl::types::GLenum {
+        gl::TEXTURE_CUBE_MAP
+    }
+
+    fn internal_format(&self) -> gl::types::GLenum {
+        self.internal_format
+    }
+
+    // All faces are accessible through an imageCube
+    fn layered(&self) -> bool {
+        true
+    }
+}
+
+impl Cubemap {
+    /// Allocates an uninitialized cubemap without mipmaps, e.g. as target of a compute shader.
+    ///
+    /// # Arguments
+    /// * `size` - Width and height of each face in pixels
+    /// * `internal_format` - Sized internal format, e.g. RGBA8 or RGBA16F
+    pub fn new(size: u32, internal_format: gl::types::GLenum) -> Cubemap {
+        Cubemap::allocate(size, 1, internal_format)
+    }
+
+    /// Creates a cubemap from the pixel data of its six faces.
+    /// Storage for a complete mipmap chain is allocated and filled, see generate_mipmaps().
+    /// Integer and depth formats can't be mipmapped, they only get the base level.
+    ///
+    /// # Arguments
+    /// * `size` - Width and height of each face in pixels
+    /// * `internal_format` - Sized internal format, e.g. RGBA8 or RGBA16F
+    /// * `format` - Layout of the pixel data, e.g. RGBA
+    /// * `faces` - Pixel data of the faces in the order +X, -X, +Y, -Y, +Z, -Z
+    pub fn from_data<T: PixelData>(
+        size: u32,
+        internal_format: gl::types::GLenum,
+        format: gl::types::GLenum,
+        faces: [&[T]; 6],
+    ) -> Result<Cubemap, Box<dyn std::error::Error>> {
+        if size == 0 {
+            Err("Texture dimensions must not be zero")?;
+        }
+
+        let levels = if mipmappable(internal_format) {
+            mip_levels(size, size)
+        } else {
+            1
+        };
+
+        let cubemap = Cubemap::allocate(size, levels, internal_format);
+        for (face, data) in CubemapFace::ALL.iter().zip(&faces) {
+            cubemap.update(*face, [0, 0], [size, size], format, data)?;
+        }
+        if levels > 1 {
+            cubemap.generate_mipmaps();
+        }
+
+        Ok(cubemap)
+    }
+
+    /// Decodes six PNG, BMP or PPM image files and uploads them as faces.
+    /// Unlike Texture2D the images aren't flipped, cubemap faces start at the top left.
+    ///
+    /// # Arguments
+    /// * `paths` - Image files of the faces in the order +X, -X, +Y, -Y, +Z, -Z
+    /// * `internal_format` - Sized internal format, e.g. RGBA8 or SRGB8_ALPHA8
+    ///
+    /// # Examples
+    /// ```
+    /// let skybox = Cubemap::from_files(
+    ///     [
+    ///         "sky/right.png",
+    ///         "sky/left.png",
+    ///         "sky/top.png",
+    ///         "sky/bottom.png",
+    ///         "sky/front.png",
+    ///         "sky/back.png",
+    ///     ],
+    ///     gl::SRGB8_ALPHA8,
+    /// )?;
+    /// ```
+    pub fn from_files(
+        paths: [&str; 6],
+        internal_format: gl::types::GLenum,
+    ) -> Result<Cubemap, Box<dyn std::error::Error>> {
+        let mut images = Vec::with_capacity(6);
+        for path in paths.iter() {
+            images.push(image::open(path)?);
+        }
+
+        Cubemap::from_images(images, internal_format)
+    }
+
+    /// Decodes six PNG, BMP or PPM images from memory and uploads them as faces.
+    ///
+    /// # Arguments
+    /// * `faces` - Encoded images of the faces in the order +X, -X, +Y, -Y, +Z, -Z
+    /// * `internal_format` - Sized internal format, e.g. RGBA8 or SRGB8_ALPHA8
+    pub fn from_bytes(
+        faces: [&[u8]; 6],
+        internal_format: gl::types::GLenum,
+    ) -> Result<Cubemap, Box<dyn std::error::Error>> {
+        let mut images = Vec::with_capacity(6);
+        for bytes in faces.iter() {
+            images.push(image::load_from_memory(bytes)?);
+        }
+
+        Cubemap::from_images(images, internal_format)
+    }
+
+    fn from_images(
+        images: Vec<image::DynamicImage>,
+        internal_format: gl::types::GLenum,
+    ) -> Result<Cubemap, Box<dyn std::error::Error>> {
+        let images: Vec<image::RgbaImage> = images.into_iter().map(|i| i.into_rgba8()).collect();
+
+        let size = images[0].width();
+        if images
+            .iter()
+            .any(|i| i.width() != size || i.height() != size)
+        {
+            Err("Cubemap faces must be square and of equal size")?;
+        }
+
+        Cubemap::from_data(
+            size,
+            internal_format,
+            gl::RGBA,
+            [
+                images[0].as_raw(),
+                images[1].as_raw(),
+                images[2].as_raw(),
+                images[3].as_raw(),
+                images[4].as_raw(),
+                images[5].as_raw(),
+            ],
+        )
+    }
+
+    fn allocate(size: u32, levels: i32, internal_format: gl::types::GLenum) -> Cubemap {
+        let mut id = 0;
+        unsafe {
+            gl::CreateTextures(gl::TEXTURE_CUBE_MAP, 1, &mut id);
+            gl::TextureStorage2D(id, levels, internal_format, size as i32, size as i32);
+
+            // The default filter expects mipmaps, which would leave the texture incomplete
+            gl::TextureParameteri(id, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
+            gl::TextureParameteri(id, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
+
+            // Filtering at face edges must not wrap around to the opposite edge
+            gl::TextureParameteri(id, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as i32);
+            gl::TextureParameteri(id, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as i32);
+            gl::TextureParameteri(id, gl::TEXTURE_WRAP_R, gl::CLAMP_TO_EDGE as i32);
+        }
+
+        Cubemap {
+            id,
+            size,
+            levels,
+            internal_format,
+        }
+    }
+
+    /// Overwrites a rectangle of a face of the base level
+    ///
+    /// # Arguments
+    /// * `face` - The face to be written
+    /// * `offset` - Left and top edge of the rectangle
+    /// * `size` - Width and height of the rectangle
+    /// * `format` - Layout of the pixel data, e.g. RGBA
+    /// * `data` - Pixel rows of the rectangle
+    pub fn update<T: PixelData>(
+        &self,
+        face: CubemapFace,
+        offset: [u32; 2],
+        size: [u32; 2],
+        format: gl::types::GLenum,
+        data: &[T],
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let offset = [offset[0], offset[1], face.index()];
+        let size = [size[0], size[1], 1];
+
+        check_region(offset, size, [self.size, self.size, 6])?;
+        write_region(self.id, offset, size, format, data)
+    }
+
+    /// Reads a face of a mipmap level back from the gpu
+    ///
+    /// # Arguments
+    /// * `face` - The face to be read
+    /// * `level` - Mipmap level, 0 is the full resolution face
+    /// * `format` - Layout the pixel data is returned in, e.g. RGBA
+    pub fn read<T: PixelData>(
+        &self,
+        face: CubemapFace,
+        level: i32,
+        format: gl::types::GLenum,
+    ) -> Result<Vec<T>, Box<dyn std::error::Error>> {
+        check_level(level, self.levels)?;
+
+        let size = level_size(self.size, level);
+        read_region(
+            self.id,
+            level,
+            [0, 0, face.index()],
+            [size, size, 1],
+            format,
+        )
+    }
+
+    /// Fills all mipmap levels of every face from the base level.
+    /// Cubemaps created by new() only have a base level, so this does nothing for them.
+    pub fn generate_mipmaps(&self) {
+        unsafe {
+            gl::GenerateTextureMipmap(self.id);
+        }
+    }
+
+    /// Sets the filters used when the cubemap is minified or magnified
+    ///
+    /// # Arguments
+    /// * `min` - E.g. NEAREST, LINEAR or LINEAR_MIPMAP_LINEAR
+    /// * `mag` - NEAREST or LINEAR
+    pub fn set_filter(&self, min: gl::types::GLenum, mag: gl::types::GLenum) {
+        unsafe {
+            gl::TextureParameteri(self.id, gl::TEXTURE_MIN_FILTER, min as i32);
+            gl::TextureParameteri(self.id, gl::TEXTURE_MAG_FILTER, mag as i32);
+        }
+    }
+
+    /// Width and height of each face
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+
+    /// Number of mipmap levels the storage was allocated with
+    pub fn levels(&self) -> i32 {
+        self.levels
+    }
+}
@@ -0,0 +1,281 @@
+//! This module abstracts OpenGL textures.
+//! All texture types implement the drop trait for automatic clean-up.
+
+mod cubemap;
+mod sampler;
+mod texture_2d;
+mod texture_2d_array;
+mod texture_3d;
+mod texture_units;
+
+pub use cubemap::{Cubemap, CubemapFace};
+pub use sampler::Sampler;
+pub use texture_2d::Texture2D;
+pub use texture_2d_array::Texture2DArray;
+pub use texture_3d::Texture3D;
+pub use texture_units::TextureUnits;
+
+use std::ffi::c_void;
+
+/// Implemented by the element types pixel data can be uploaded from and read back to
+pub trait PixelData: Copy + Default {
+    /// Matching OpenGL type, e.g. UNSIGNED_BYTE for u8
+    const GL_TYPE: gl::types::GLenum;
+}
+
+impl PixelData for u8 {
+    const GL_TYPE: gl::types::GLenum = gl::UNSIGNED_BYTE;
+}
+
+impl PixelData for i8 {
+    const GL_TYPE: gl::types::GLenum = gl::BYTE;
+}
+
+impl PixelData for u16 {
+    const GL_TYPE: gl::types::GLenum = gl::UNSIGNED_SHORT;
+}
+
+impl PixelData for i16 {
+    const GL_TYPE: gl::types::GLenum = gl::SHORT;
+}
+
+impl PixelData for u32 {
+    const GL_TYPE: gl::types::GLenum = gl::UNSIGNED_INT;
+}
+
+impl PixelData for i32 {
+    const GL_TYPE: gl::types::GLenum = gl::INT;
+}
+
+impl PixelData for f32 {
+    const GL_TYPE: gl::types::GLenum = gl::FLOAT;
+}
+
+/// Number of components per pixel of a pixel format, e.g. 4 for RGBA
+pub(crate) fn components(format: gl::types::GLenum) -> Result<usize, Box<dyn std::error::Error>> {
+    match format {
+        gl::RED
+        | gl::GREEN
+        | gl::BLUE
+        | gl::RED_INTEGER
+        | gl::DEPTH_COMPONENT
+        | gl::STENCIL_INDEX => Ok(1),
+        gl::RG | gl::RG_INTEGER => Ok(2),
+        gl::RGB | gl::BGR | gl::RGB_INTEGER | gl::BGR_INTEGER => Ok(3),
+        gl::RGBA | gl::BGRA | gl::RGBA_INTEGER | gl::BGRA_INTEGER => Ok(4),
+        _ => Err(format!("Unsupported pixel format {:#x}", format))?,
+    }
+}
+
+/// Number of mipmap levels of a complete mipmap chain
+pub(crate) fn mip_levels(width: u32, height: u32) -> i32 {
+    32 - width.max(height).max(1).leading_zeros() as i32
+}
+
+/// Whether GenerateTextureMipmap works for the internal format.
+/// Integer, depth and stencil formats can't be filtered, so they don't get a mipmap chain.
+pub(crate) fn mipmappable(internal_format: gl::types::GLenum) -> bool {
+    !matches!(
+        internal_format,
+        gl::R8I
+            | gl::R8UI
+            | gl::R16I
+            | gl::R16UI
+            | gl::R32I
+            | gl::R32UI
+            | gl::RG8I
+            | gl::RG8UI
+            | gl::RG16I
+            | gl::RG16UI
+            | gl::RG32I
+            | gl::RG32UI
+            | gl::RGB8I
+            | gl::RGB8UI
+            | gl::RGB16I
+            | gl::RGB16UI
+            | gl::RGB32I
+            | gl::RGB32UI
+            | gl::RGBA8I
+            | gl::RGBA8UI
+            | gl::RGBA16I
+            | gl::RGBA16UI
+            | gl::RGBA32I
+            | gl::RGBA32UI
+            | gl::RGB10_A2UI
+            | gl::DEPTH_COMPONENT16
+            | gl::DEPTH_COMPONENT24
+            | gl::DEPTH_COMPONENT32
+            | gl::DEPTH_COMPONENT32F
+            | gl::DEPTH24_STENCIL8
+            | gl::DEPTH32F_STENCIL8
+            | gl::STENCIL_INDEX8
+    )
+}
+
+/// Implemented by all texture types, so they can be bound to texture and image units
+pub trait Texture {
+    /// OpenGL id of the texture
+    fn id(&self) -> u32;
+
+    /// Texture target, e.g. TEXTURE_2D
+    fn target(&self) -> gl::types::GLenum;
+
+    /// Internal format the storage was allocated with, e.g. RGBA8
+    fn internal_format(&self) -> gl::types::GLenum;
+
+    /// Whether all layers are bound when the texture is used as image
+    fn layered(&self) -> bool {
+        false
+    }
+
+    /// Binds the texture to the given texture unit for sampling
+    fn bind(&self, unit: u32) {
+        unsafe {
+            gl::BindTextureUnit(unit, self.id());
+        }
+    }
+
+    /// Binds a level of the texture to the given image unit for image load/store.
+    /// See https://www.khronos.org/registry/OpenGL-Refpages/gl4/html/glBindImageTexture.xhtml
+    ///
+    /// # Arguments
+    /// * `unit` - Image unit
+    /// * `level` - Mipmap level
+    /// * `access` - READ_ONLY, WRITE_ONLY or READ_WRITE
+    /// * `format` - Format the shader uses to access the image, e.g. RGBA32F
+    fn bind_image(
+        &self,
+        unit: u32,
+        level: i32,
+        access: gl::types::GLenum,
+        format: gl::types::GLenum,
+    ) {
+        let layered = if self.layered() { gl::TRUE } else { gl::FALSE };
+        unsafe {
+            gl::BindImageTexture(unit, self.id(), level, layered, 0, access, format);
+        }
+    }
+}
+
+/// Fails if the level is not part of the allocated mipmap chain
+pub(crate) fn check_level(level: i32, levels: i32) -> Result<(), Box<dyn std::error::Error>> {
+    if level < 0 || level >= levels {
+        Err(format!(
+            "Level {} does not exist, the texture has {} levels",
+            level, levels
+        ))?;
+    }
+
+    Ok(())
+}
+
+/// Fails if the region doesn't fit into a texture of the given size
+pub(crate) fn check_region(
+    offset: [u32; 3],
+    size: [u32; 3],
+    texture_size: [u32; 3],
+) -> Result<(), Box<dyn std::error::Error>> {
+    // Checked, so huge offsets can't wrap around into the texture
+    if (0..3).any(|i| {
+        offset[i]
+            .checked_add(size[i])
+            .map_or(true, |end| end > texture_size[i])
+    }) {
+        Err(format!(
+            "Region {:?} at {:?} exceeds the texture size {:?}",
+            size, offset, texture_size
+        ))?;
+    }
+
+    Ok(())
+}
+
+/// Fails if the data doesn't hold exactly the pixels of a region of the given size
+pub(crate) fn check_data_len<T>(
+    data: &[T],
+    size: [u32; 3],
+    format: gl::types::GLenum,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let expected = size.iter().map(|s| *s as usize).product::<usize>() * components(format)?;
+    if data.len() != expected {
+        Err(format!(
+            "Expected {} values of pixel data, got {}",
+            expected,
+            data.len()
+        ))?;
+    }
+
+    Ok(())
+}
+
+/// Uploads a region of the base level of a 3D, array or cubemap texture.
+/// For cubemaps the z offset selects the face.
+pub(crate) fn write_region<T: PixelData>(
+    id: u32,
+    offset: [u32; 3],
+    size: [u32; 3],
+    format: gl::types::GLenum,
+    data: &[T],
+) -> Result<(), Box<dyn std::error::Error>> {
+    check_data_len(data, size, format)?;
+
+    unsafe {
+        // Rows of single channel byte images are rarely 4 byte aligned
+        gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
+        gl::TextureSubImage3D(
+            id,
+            0,
+            offset[0] as i32,
+            offset[1] as i32,
+            offset[2] as i32,
+            size[0] as i32,
+            size[1] as i32,
+            size[2] as i32,
+            format,
+            T::GL_TYPE,
+            data.as_ptr() as *const c_void,
+        );
+        gl::PixelStorei(gl::UNPACK_ALIGNMENT, 4);
+    }
+
+    Ok(())
+}
+
+/// Reads a region of a mipmap level back from the gpu.
+/// For arrays and cubemaps the z offset selects the first layer or face.
+pub(crate) fn read_region<T: PixelData>(
+    id: u32,
+    level: i32,
+    offset: [u32; 3],
+    size: [u32; 3],
+    format: gl::types::GLenum,
+) -> Result<Vec<T>, Box<dyn std::error::Error>> {
+    let len = size.iter().map(|s| *s as usize).product::<usize>() * components(format)?;
+    let mut data = vec![T::default(); len];
+
+    unsafe {
+        gl::PixelStorei(gl::PACK_ALIGNMENT, 1);
+        gl::GetTextureSubImage(
+            id,
+            level,
+            offset[0] as i32,
+            offset[1] as i32,
+            offset[2] as i32,
+            size[0] as i32,
+            size[1] as i32,
+            size[2] as i32,
+            format,
+            T::GL_TYPE,
+            std::mem::size_of_val(data.as_slice()) as i32,
+            data.as_mut_ptr() as *mut c_void,
+        );
+        gl::PixelStorei(gl::PACK_ALIGNMENT, 4);
+    }
+
+    Ok(data)
+}
+
+/// Size of a mipmap level along one axis
+pub(crate) fn level_size(size: u32, level: i32) -> u32 {
+    (size >> level).max(1)
+}
@@ -0,0 +1,153 @@
+/// Not part of the 4.5 core bindings, but supported almost everywhere.
+/// See https://registry.khronos.org/OpenGL/extensions/ARB/ARB_texture_filter_anisotropic.txt
+const TEXTURE_MAX_ANISOTROPY: gl::types::GLenum = 0x84FE;
+const MAX_TEXTURE_MAX_ANISOTROPY: gl::types::GLenum = 0x84FF;
+
+/// Sampling parameters which are independent of a texture.
+/// A sampler bound to a texture unit overrides the parameters of the texture bound to it.
+pub struct Sampler {
+    id: u32,
+}
+
+impl Drop for Sampler {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteSamplers(1, &self.id);
+        }
+    }
+}
+
+impl Default for Sampler {
+    fn default() -> Sampler {
+        Sampler::new()
+    }
+}
+
+impl Sampler {
+    /// Creates a sampler with linear filtering and repeating texture coordinates
+    ///
+    /// # Examples
+    /// ```
+    /// let pixelated = Sampler::new()
+    ///     .filter(gl::NEAREST, gl::NEAREST)
+    ///     .wrap(gl::CLAMP_TO_EDGE, gl::CLAMP_TO_EDGE, gl::CLAMP_TO_EDGE);
+    ///
+    /// let smooth = Sampler::new()
+    ///     .filter(gl::LINEAR_MIPMAP_LINEAR, gl::LINEAR)
+    ///     .anisotropy(16.0);
+    /// ```
+    pub fn new() -> Sampler {
+        let mut id = 0;
+        unsafe {
+            gl::CreateSamplers(1, &mut id);
+        }
+
+        Sampler { id }.filter(gl::LINEAR, gl::LINEAR)
+    }
+
+    /// Sets the filters used when the texture is minified or magnified
+    ///
+    /// # Arguments
+    /// * `min` - E.g. NEAREST, LINEAR or LINEAR_MIPMAP_LINEAR
+    /// * `mag` - NEAREST or LINEAR
+    pub fn filter(self, min: gl::types::GLenum, mag: gl::types::GLenum) -> Sampler {
+        self.parameter(gl::TEXTURE_MIN_FILTER, min as i32);
+        self.parameter(gl::TEXTURE_MAG_FILTER, mag as i32);
+        self
+    }
+
+    /// Sets how texture coordinates outside of [0, 1] are handled
+    ///
+    /// # Arguments
+    /// * `s` - Horizontal wrap mode, e.g. REPEAT, MIRRORED_REPEAT, CLAMP_TO_EDGE or CLAMP_TO_BORDER
+    /// * `t` - Vertical wrap mode
+    /// * `r` - Depth wrap mode, only used by 3D textures and cubemaps
+    pub fn wrap(self, s: gl::types::GLenum, t: gl::types::GLenum, r: gl::types::GLenum) -> Sampler {
+        self.parameter(gl::TEXTURE_WRAP_S, s as i32);
+        self.parameter(gl::TEXTURE_WRAP_T, t as i32);
+        self.parameter(gl::TEXTURE_WRAP_R, r as i32);
+        self
+    }
+
+    /// Sets the color returned for coordinates outside of the texture when wrapping with CLAMP_TO_BORDER
+    pub fn border_color(self, color: [f32; 4]) -> Sampler {
+        unsafe {
+            gl::SamplerParameterfv(self.id, gl::TEXTURE_BORDER_COLOR, color.as_ptr());
+        }
+        self
+    }
+
+    /// Sets the maximum degree of anisotropic filtering, clamped to what the gpu supports.
+    /// 1.0 disables anisotropic filtering.
+    pub fn anisotropy(self, level: f32) -> Sampler {
+        let level = level.clamp(1.0, Sampler::max_anisotropy());
+        unsafe {
+            gl::SamplerParameterf(self.id, TEXTURE_MAX_ANISOTROPY, level);
+        }
+        self
+    }
+
+    /// Restricts the mipmap levels which are sampled
+    ///
+    /// # Arguments
+    /// * `min` - Lowest level of detail, i.e. the most detailed mipmap level used
+    /// * `max` - Highest level of detail
+    /// * `bias` - Added to the computed level of detail, positive values make the texture blurrier
+    pub fn lod(self, min: f32, max: f32, bias: f32) -> Sampler {
+        unsafe {
+            gl::SamplerParameterf(self.id, gl::TEXTURE_MIN_LOD, min);
+            gl::SamplerParameterf(self.id, gl::TEXTURE_MAX_LOD, max);
+            gl::SamplerParameterf(self.id, gl::TEXTURE_LOD_BIAS, bias);
+        }
+        self
+    }
+
+    /// Compares sampled depth values against a reference instead of returning them, as used for shadow maps.
+    /// The shader has to use a shadow sampler, e.g. sampler2DShadow.
+    ///
+    /// # Arguments
+    /// * `func` - Comparison function, e.g. LEQUAL. None disables the comparison.
+    pub fn compare(self, func: Option<gl::types::GLenum>) -> Sampler {
+        match func {
+            Some(func) => {
+                self.parameter(gl::TEXTURE_COMPARE_MODE, gl::COMPARE_REF_TO_TEXTURE as i32);
+                self.parameter(gl::TEXTURE_COMPARE_FUNC, func as i32);
+            }
+            None => self.parameter(gl::TEXTURE_COMPARE_MODE, gl::NONE as i32),
+        }
+        self
+    }
+
+    /// Binds the sampler to a texture unit
+    pub fn bind(&self, unit: u32) {
+        unsafe {
+            gl::BindSampler(unit, self.id);
+        }
+    }
+
+    /// Removes any sampler from a texture unit, so the parameters of the texture are used again
+    pub fn unbind(unit: u32) {
+        unsafe {
+            gl::BindSampler(unit, 0);
+        }
+    }
+
+    /// Highest anisotropy level supported by the gpu
+    pub fn max_anisotropy() -> f32 {
+        let mut max = 1.0;
+        unsafe {
+            gl::GetFloatv(MAX_TEXTURE_MAX_ANISOTROPY, &mut max);
+        }
+        max
+    }
+
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    fn parameter(&self, name: gl::types::GLenum, value: i32) {
+        unsafe {
+            gl::SamplerParameteri(self.id, name, value);
+        }
+    }
+}
@@ -0,0 +1,297 @@
+use std::ffi::c_void;
+
+use super::{
+    check_data_len, check_level, check_region, level_size, mip_levels, mipmappable, read_region,
+    PixelData, Texture,
+};
+
+pub struct Texture2D {
+    id: u32,
+    width: u32,
+    height: u32,
+    levels: i32,
+    internal_format: gl::types::GLenum,
+}
+
+impl Drop for Texture2D {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteTextures(1, &self.id);
+        }
+    }
+}
+
+impl Texture for Texture2D {
+    fn id(&self) -> u32 {
+        self.id
+    }
+
+    fn target(&self) -> gl::types::GLenum {
+        gl::TEXTURE_2D
+    }
+
+    fn internal_format(&self) -> gl::types::GLenum {
+        self.internal_format
+    }
+}
+
+impl Texture2D {
+    /// Allocates an uninitialized 2D texture without mipmaps, e.g. as target of a compute shader.
+    ///
+    /// # Arguments
+    /// * `width` - Width in pixels
+    /// * `height` - Height in pixels
+    /// * `internal_format` - Sized internal format, e.g. RGBA8 or RGBA32F. See https://www.khronos.org/registry/OpenGL-Refpages/gl4/html/glTexStorage2D.xhtml
+    ///
+    /// # Examples
+    /// ```
+    /// let texture = Texture2D::new(400, 400, gl::RGBA32F);
+    /// ```
+    pub fn new(width: u32, height: u32, internal_format: gl::types::GLenum) -> Texture2D {
+        Texture2D::allocate(width, height, 1, internal_format)
+    }
+
+    /// Creates a 2D texture from raw pixel data.
+    /// Storage for a complete mipmap chain is allocated and filled, see generate_mipmaps().
+    /// Integer and depth formats can't be mipmapped, they only get the base level.
+    ///
+    /// # Arguments
+    /// * `width` - Width in pixels
+    /// * `height` - Height in pixels
+    /// * `internal_format` - Sized internal format, e.g. RGBA8, R8 or RGBA32F
+    /// * `format` - Layout of the pixel data, e.g. RGBA or RED
+    /// * `data` - Pixel rows from bottom to top, width * height * components values
+    ///
+    /// # Examples
+    /// ```
+    /// let pixels: Vec<u8> = vec![
+    ///     255, 0, 0, 255,   0, 255, 0, 255,
+    ///     0, 0, 255, 255,   255, 255, 255, 255,
+    /// ];
+    /// let texture = Texture2D::from_data(2, 2, gl::RGBA8, gl::RGBA, &pixels)?;
+    /// ```
+    pub fn from_data<T: PixelData>(
+        width: u32,
+        height: u32,
+        internal_format: gl::types::GLenum,
+        format: gl::types::GLenum,
+        data: &[T],
+    ) -> Result<Texture2D, Box<dyn std::error::Error>> {
+        if width == 0 || height == 0 {
+            Err("Texture dimensions must not be zero")?;
+        }
+
+        let levels = if mipmappable(internal_format) {
+            mip_levels(width, height)
+        } else {
+            1
+        };
+
+        let texture = Texture2D::allocate(width, height, levels, internal_format);
+        texture.update(0, 0, width, height, format, data)?;
+        if levels > 1 {
+            texture.generate_mipmaps();
+        }
+
+        Ok(texture)
+    }
+
+    /// Decodes a PNG, BMP or PPM image file and uploads it as texture.
+    /// The image is flipped, so its first row ends up at the top when drawn.
+    ///
+    /// # Arguments
+    /// * `path` - Path to the image file
+    /// * `internal_format` - Sized internal format, e.g. RGBA8 or SRGB8_ALPHA8
+    ///
+    /// # Examples
+    /// ```
+    /// let texture = Texture2D::from_file("assets/crate.png", gl::SRGB8_ALPHA8)?;
+    /// ```
+    pub fn from_file(
+        path: &str,
+        internal_format: gl::types::GLenum,
+    ) -> Result<Texture2D, Box<dyn std::error::Error>> {
+        Texture2D::from_image(image::open(path)?, internal_format)
+    }
+
+    /// Decodes a PNG, BMP or PPM image from memory and uploads it as texture.
+    ///
+    /// # Arguments
+    /// * `bytes` - Encoded image, e.g. from include_bytes!
+    /// * `internal_format` - Sized internal format, e.g. RGBA8 or SRGB8_ALPHA8
+    ///
+    /// # Examples
+    /// ```
+    /// let texture = Texture2D::from_bytes(include_bytes!("crate.png"), gl::RGBA8)?;
+    /// ```
+    pub fn from_bytes(
+        bytes: &[u8],
+        internal_format: gl::types::GLenum,
+    ) -> Result<Texture2D, Box<dyn std::error::Error>> {
+        Texture2D::from_image(image::load_from_memory(bytes)?, internal_format)
+    }
+
+    fn from_image(
+        image: image::DynamicImage,
+        internal_format: gl::types::GLenum,
+    ) -> Result<Texture2D, Box<dyn std::error::Error>> {
+        // OpenGL expects the bottom row first
+        let image = image.flipv().into_rgba8();
+
+        Texture2D::from_data(
+            image.width(),
+            image.height(),
+            internal_format,
+            gl::RGBA,
+            image.as_raw(),
+        )
+    }
+
+    fn allocate(
+        width: u32,
+        height: u32,
+        levels: i32,
+        internal_format: gl::types::GLenum,
+    ) -> Texture2D {
+        let mut id = 0;
+        unsafe {
+            gl::CreateTextures(gl::TEXTURE_2D, 1, &mut id);
+            gl::TextureStorage2D(id, levels, internal_format, width as i32, height as i32);
+
+            // The default filter expects mipmaps, which would leave the texture incomplete
+            gl::TextureParameteri(id, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
+            gl::TextureParameteri(id, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
+        }
+
+        Texture2D {
+            id,
+            width,
+            height,
+            levels,
+            internal_format,
+        }
+    }
+
+    /// Overwrites a rectangle of the base level
+    ///
+    /// # Arguments
+    /// * `x` - Left edge of the rectangle
+    /// * `y` - Bottom edge of the rectangle
+    /// * `width` - Width of the rectangle
+    /// * `height` - Height of the rectangle
+    /// * `format` - Layout of the pixel data, e.g. RGBA or RED
+    /// * `data` - Pixel rows from bottom to top, width * height * components values
+    ///
+    /// # Examples
+    /// ```
+    /// // Paint the bottom left pixel red
+    /// texture.update(0, 0, 1, 1, gl::RGBA, &[255u8, 0, 0, 255])?;
+    /// ```
+    pub fn update<T: PixelData>(
+        &self,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+        format: gl::types::GLenum,
+        data: &[T],
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        check_region([x, y, 0], [width, height, 1], [self.width, self.height, 1])?;
+        check_data_len(data, [width, height, 1], format)?;
+
+        unsafe {
+            // Rows of single channel byte images are rarely 4 byte aligned
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
+            gl::TextureSubImage2D(
+                self.id,
+                0,
+                x as i32,
+                y as i32,
+                width as i32,
+                height as i32,
+                format,
+                T::GL_TYPE,
+                data.as_ptr() as *const c_void,
+            );
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, 4);
+        }
+
+        Ok(())
+    }
+
+    /// Reads a mipmap level back from the gpu
+    ///
+    /// # Arguments
+    /// * `level` - Mipmap level, 0 is the full resolution image
+    /// * `format` - Layout the pixel data is returned in, e.g. RGBA or RED
+    ///
+    /// # Examples
+    /// ```
+    /// let pixels: Vec<f32> = texture.read(0, gl::RGBA)?;
+    /// ```
+    pub fn read<T: PixelData>(
+        &self,
+        level: i32,
+        format: gl::types::GLenum,
+    ) -> Result<Vec<T>, Box<dyn std::error::Error>> {
+        check_level(level, self.levels)?;
+
+        let size = [
+            level_size(self.width, level),
+            level_size(self.height, level),
+            1,
+        ];
+        read_region(self.id, level, [0, 0, 0], size, format)
+    }
+
+    /// Fills all mipmap levels from the base level.
+    /// Textures created by new() only have a base level, so this does nothing for them.
+    pub fn generate_mipmaps(&self) {
+        unsafe {
+            gl::GenerateTextureMipmap(self.id);
+        }
+    }
+
+    /// Sets the filters used when the texture is minified or magnified
+    ///
+    /// # Arguments
+    /// * `min` - E.g. NEAREST, LINEAR or LINEAR_MIPMAP_LINEAR
+    /// * `mag` - NEAREST or LINEAR
+    ///
+    /// # Examples
+    /// ```
+    /// // Crisp pixel art
+    /// texture.set_filter(gl::NEAREST, gl::NEAREST);
+    /// ```
+    pub fn set_filter(&self, min: gl::types::GLenum, mag: gl::types::GLenum) {
+        unsafe {
+            gl::TextureParameteri(self.id, gl::TEXTURE_MIN_FILTER, min as i32);
+            gl::TextureParameteri(self.id, gl::TEXTURE_MAG_FILTER, mag as i32);
+        }
+    }
+
+    /// Sets how texture coordinates outside of [0, 1] are handled
+    ///
+    /// # Arguments
+    /// * `s` - Horizontal wrap mode, e.g. REPEAT, MIRRORED_REPEAT or CLAMP_TO_EDGE
+    /// * `t` - Vertical wrap mode
+    pub fn set_wrap(&self, s: gl::types::GLenum, t: gl::types::GLenum) {
+        unsafe {
+            gl::TextureParameteri(self.id, gl::TEXTURE_WRAP_S, s as i32);
+            gl::TextureParameteri(self.id, gl::TEXTURE_WRAP_T, t as i32);
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Number of mipmap levels the storage was allocated with
+    pub fn levels(&self) -> i32 {
+        self.levels
+    }
+}
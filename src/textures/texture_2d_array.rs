@@ -0,0 +1,242 @@
+use super::{
+    check_level, check_region, level_size, mip_levels, mipmappable, read_region, write_region,
+    PixelData, Texture,
+};
+
+/// A stack of equally sized 2D layers, sampled with a sampler2DArray.
+/// Mipmaps are generated per layer, layers are never blended into each other.
+pub struct Texture2DArray {
+    id: u32,
+    width: u32,
+    height: u32,
+    layers: u32,
+    levels: i32,
+    internal_format: gl::types::GLenum,
+}
+
+impl Drop for Texture2DArray {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteTextures(1, &self.id);
+        }
+    }
+}
+
+impl Texture for Texture2DArray {
+    fn id(&self) -> u32 {
+        self.id
+    }
+
+    fn target(&self) -> gl::types::GLenum {
+        gl::TEXTURE_2D_ARRAY
+    }
+
+    fn internal_format(&self) -> gl::types::GLenum {
+        self.internal_format
+    }
+
+    // All layers are accessible through an image2DArray
+    fn layered(&self) -> bool {
+        true
+    }
+}
+
+impl Texture2DArray {
+    /// Allocates an uninitialized texture array without mipmaps, e.g. as target of a compute shader.
+    ///
+    /// # Arguments
+    /// * `width` - Width of each layer in pixels
+    /// * `height` - Height of each layer in pixels
+    /// * `layers` - Number of layers
+    /// * `internal_format` - Sized internal format, e.g. RGBA8 or RGBA32F
+    ///
+    /// # Examples
+    /// ```
+    /// let frames = Texture2DArray::new(256, 256, 16, gl::RGBA8);
+    /// ```
+    pub fn new(
+        width: u32,
+        height: u32,
+        layers: u32,
+        internal_format: gl::types::GLenum,
+    ) -> Texture2DArray {
+        Texture2DArray::allocate(width, height, layers, 1, internal_format)
+    }
+
+    /// Creates a texture array from the pixel data of all layers.
+    /// Storage for a complete mipmap chain is allocated and filled, see generate_mipmaps().
+    /// Integer and depth formats can't be mipmapped, they only get the base level.
+    ///
+    /// # Arguments
+    /// * `width` - Width of each layer in pixels
+    /// * `height` - Height of each layer in pixels
+    /// * `layers` - Number of layers
+    /// * `internal_format` - Sized internal format, e.g. RGBA8 or R32F
+    /// * `format` - Layout of the pixel data, e.g. RGBA or RED
+    /// * `data` - All layers one after another, each one made of rows from bottom to top
+    ///
+    /// # Examples
+    /// ```
+    /// let tiles = Texture2DArray::from_data(16, 16, 64, gl::RGBA8, gl::RGBA, &tileset)?;
+    /// ```
+    pub fn from_data<T: PixelData>(
+        width: u32,
+        height: u32,
+        layers: u32,
+        internal_format: gl::types::GLenum,
+        format: gl::types::GLenum,
+        data: &[T],
+    ) -> Result<Texture2DArray, Box<dyn std::error::Error>> {
+        if width == 0 || height == 0 || layers == 0 {
+            Err("Texture dimensions must not be zero")?;
+        }
+
+        let levels = if mipmappable(internal_format) {
+            mip_levels(width, height)
+        } else {
+            1
+        };
+        let texture = Texture2DArray::allocate(width, height, layers, levels, internal_format);
+        texture.update([0, 0, 0], [width, height, layers], format, data)?;
+        if levels > 1 {
+            texture.generate_mipmaps();
+        }
+
+        Ok(texture)
+    }
+
+    fn allocate(
+        width: u32,
+        height: u32,
+        layers: u32,
+        levels: i32,
+        internal_format: gl::types::GLenum,
+    ) -> Texture2DArray {
+        let mut id = 0;
+        unsafe {
+            gl::CreateTextures(gl::TEXTURE_2D_ARRAY, 1, &mut id);
+            gl::TextureStorage3D(
+                id,
+                levels,
+                internal_format,
+                width as i32,
+                height as i32,
+                layers as i32,
+            );
+
+            // The default filter expects mipmaps, which would leave the texture incomplete
+            gl::TextureParameteri(id, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
+            gl::TextureParameteri(id, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
+        }
+
+        Texture2DArray {
+            id,
+            width,
+            height,
+            layers,
+            levels,
+            internal_format,
+        }
+    }
+
+    /// Overwrites a rectangle of one or more consecutive layers of the base level
+    ///
+    /// # Arguments
+    /// * `offset` - Left edge, bottom edge and first layer
+    /// * `size` - Width, height and number of layers
+    /// * `format` - Layout of the pixel data, e.g. RGBA or RED
+    /// * `data` - Pixel data of the layers one after another
+    ///
+    /// # Examples
+    /// ```
+    /// // Replace layer 3
+    /// tiles.update([0, 0, 3], [16, 16, 1], gl::RGBA, &tile)?;
+    /// ```
+    pub fn update<T: PixelData>(
+        &self,
+        offset: [u32; 3],
+        size: [u32; 3],
+        format: gl::types::GLenum,
+        data: &[T],
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        check_region(offset, size, [self.width, self.height, self.layers])?;
+        write_region(self.id, offset, size, format, data)
+    }
+
+    /// Reads a single layer of a mipmap level back from the gpu
+    ///
+    /// # Arguments
+    /// * `layer` - Index of the layer
+    /// * `level` - Mipmap level, 0 is the full resolution image
+    /// * `format` - Layout the pixel data is returned in, e.g. RGBA or RED
+    pub fn read<T: PixelData>(
+        &self,
+        layer: u32,
+        level: i32,
+        format: gl::types::GLenum,
+    ) -> Result<Vec<T>, Box<dyn std::error::Error>> {
+        check_level(level, self.levels)?;
+        if layer >= self.layers {
+            Err(format!(
+                "Layer {} does not exist, the texture has {} layers",
+                layer, self.layers
+            ))?;
+        }
+
+        let size = [
+            level_size(self.width, level),
+            level_size(self.height, level),
+            1,
+        ];
+        read_region(self.id, level, [0, 0, layer], size, format)
+    }
+
+    /// Fills all mipmap levels of every layer from the base level.
+    /// Textures created by new() only have a base level, so this does nothing for them.
+    pub fn generate_mipmaps(&self) {
+        unsafe {
+            gl::GenerateTextureMipmap(self.id);
+        }
+    }
+
+    /// Sets the filters used when the texture is minified or magnified
+    ///
+    /// # Arguments
+    /// * `min` - E.g. NEAREST, LINEAR or LINEAR_MIPMAP_LINEAR
+    /// * `mag` - NEAREST or LINEAR
+    pub fn set_filter(&self, min: gl::types::GLenum, mag: gl::types::GLenum) {
+        unsafe {
+            gl::TextureParameteri(self.id, gl::TEXTURE_MIN_FILTER, min as i32);
+            gl::TextureParameteri(self.id, gl::TEXTURE_MAG_FILTER, mag as i32);
+        }
+    }
+
+    /// Sets how texture coordinates outside of [0, 1] are handled
+    ///
+    /// # Arguments
+    /// * `s` - Horizontal wrap mode, e.g. REPEAT, MIRRORED_REPEAT or CLAMP_TO_EDGE
+    /// * `t` - Vertical wrap mode
+    pub fn set_wrap(&self, s: gl::types::GLenum, t: gl::types::GLenum) {
+        unsafe {
+            gl::TextureParameteri(self.id, gl::TEXTURE_WRAP_S, s as i32);
+            gl::TextureParameteri(self.id, gl::TEXTURE_WRAP_T, t as i32);
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn layers(&self) -> u32 {
+        self.layers
+    }
+
+    /// Number of mipmap levels the storage was allocated with
+    pub fn levels(&self) -> i32 {
+        self.levels
+    }
+}
@@ -0,0 +1,232 @@
+use super::{
+    check_level, check_region, level_size, mip_levels, mipmappable, read_region, write_region,
+    PixelData, Texture,
+};
+
+/// A volume of voxels, e.g. a density field written by a compute shader
+pub struct Texture3D {
+    id: u32,
+    width: u32,
+    height: u32,
+    depth: u32,
+    levels: i32,
+    internal_format: gl::types::GLenum,
+}
+
+impl Drop for Texture3D {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteTextures(1, &self.id);
+        }
+    }
+}
+
+impl Texture for Texture3D {
+    fn id(&self) -> u32 {
+        self.id
+    }
+
+    fn target(&self) -> gl::types::GLenum {
+        gl::TEXTURE_3D
+    }
+
+    fn internal_format(&self) -> gl::types::GLenum {
+        self.internal_format
+    }
+
+    // The whole volume is accessible through an image3D
+    fn layered(&self) -> bool {
+        true
+    }
+}
+
+impl Texture3D {
+    /// Allocates an uninitialized 3D texture without mipmaps, e.g. as target of a compute shader.
+    ///
+    /// # Arguments
+    /// * `width` - Width in voxels
+    /// * `height` - Height in voxels
+    /// * `depth` - Depth in voxels
+    /// * `internal_format` - Sized internal format, e.g. R32F or RGBA8
+    ///
+    /// # Examples
+    /// ```
+    /// let density = Texture3D::new(64, 64, 64, gl::R32F);
+    /// shader.bind_image("density", 0, &density, gl::WRITE_ONLY, gl::R32F);
+    /// shader.dispatch_threads(64, 64, 64, gl::SHADER_IMAGE_ACCESS_BARRIER_BIT)?;
+    /// ```
+    pub fn new(
+        width: u32,
+        height: u32,
+        depth: u32,
+        internal_format: gl::types::GLenum,
+    ) -> Texture3D {
+        Texture3D::allocate(width, height, depth, 1, internal_format)
+    }
+
+    /// Creates a 3D texture from a voxel grid.
+    /// Storage for a complete mipmap chain is allocated and filled, see generate_mipmaps().
+    /// Integer and depth formats can't be mipmapped, they only get the base level.
+    ///
+    /// # Arguments
+    /// * `width` - Width in voxels
+    /// * `height` - Height in voxels
+    /// * `depth` - Depth in voxels
+    /// * `internal_format` - Sized internal format, e.g. R32F or RGBA8
+    /// * `format` - Layout of the voxel data, e.g. RED or RGBA
+    /// * `data` - Slices from front to back, each one made of rows from bottom to top
+    ///
+    /// # Examples
+    /// ```
+    /// let grid: Vec<f32> = (0..32 * 32 * 32).map(|i| i as f32).collect();
+    /// let volume = Texture3D::from_data(32, 32, 32, gl::R32F, gl::RED, &grid)?;
+    /// ```
+    pub fn from_data<T: PixelData>(
+        width: u32,
+        height: u32,
+        depth: u32,
+        internal_format: gl::types::GLenum,
+        format: gl::types::GLenum,
+        data: &[T],
+    ) -> Result<Texture3D, Box<dyn std::error::Error>> {
+        if width == 0 || height == 0 || depth == 0 {
+            Err("Texture dimensions must not be zero")?;
+        }
+
+        let levels = if mipmappable(internal_format) {
+            mip_levels(width.max(height), depth)
+        } else {
+            1
+        };
+        let texture = Texture3D::allocate(width, height, depth, levels, internal_format);
+        texture.update([0, 0, 0], [width, height, depth], format, data)?;
+        if levels > 1 {
+            texture.generate_mipmaps();
+        }
+
+        Ok(texture)
+    }
+
+    fn allocate(
+        width: u32,
+        height: u32,
+        depth: u32,
+        levels: i32,
+        internal_format: gl::types::GLenum,
+    ) -> Texture3D {
+        let mut id = 0;
+        unsafe {
+            gl::CreateTextures(gl::TEXTURE_3D, 1, &mut id);
+            gl::TextureStorage3D(
+                id,
+                levels,
+                internal_format,
+                width as i32,
+                height as i32,
+                depth as i32,
+            );
+
+            // The default filter expects mipmaps, which would leave the texture incomplete
+            gl::TextureParameteri(id, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
+            gl::TextureParameteri(id, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
+        }
+
+        Texture3D {
+            id,
+            width,
+            height,
+            depth,
+            levels,
+            internal_format,
+        }
+    }
+
+    /// Overwrites a box of the base level
+    ///
+    /// # Arguments
+    /// * `offset` - Corner of the box with the lowest coordinates
+    /// * `size` - Width, height and depth of the box
+    /// * `format` - Layout of the voxel data, e.g. RED or RGBA
+    /// * `data` - Voxels of the box, width * height * depth * components values
+    pub fn update<T: PixelData>(
+        &self,
+        offset: [u32; 3],
+        size: [u32; 3],
+        format: gl::types::GLenum,
+        data: &[T],
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        check_region(offset, size, [self.width, self.height, self.depth])?;
+        write_region(self.id, offset, size, format, data)
+    }
+
+    /// Reads a mipmap level back from the gpu
+    ///
+    /// # Arguments
+    /// * `level` - Mipmap level, 0 is the full resolution volume
+    /// * `format` - Layout the voxel data is returned in, e.g. RED or RGBA
+    pub fn read<T: PixelData>(
+        &self,
+        level: i32,
+        format: gl::types::GLenum,
+    ) -> Result<Vec<T>, Box<dyn std::error::Error>> {
+        check_level(level, self.levels)?;
+
+        let size = [
+            level_size(self.width, level),
+            level_size(self.height, level),
+            level_size(self.depth, level),
+        ];
+        read_region(self.id, level, [0, 0, 0], size, format)
+    }
+
+    /// Fills all mipmap levels from the base level.
+    /// Textures created by new() only have a base level, so this does nothing for them.
+    pub fn generate_mipmaps(&self) {
+        unsafe {
+            gl::GenerateTextureMipmap(self.id);
+        }
+    }
+
+    /// Sets the filters used when the texture is minified or magnified
+    ///
+    /// # Arguments
+    /// * `min` - E.g. NEAREST, LINEAR or LINEAR_MIPMAP_LINEAR
+    /// * `mag` - NEAREST or LINEAR
+    pub fn set_filter(&self, min: gl::types::GLenum, mag: gl::types::GLenum) {
+        unsafe {
+            gl::TextureParameteri(self.id, gl::TEXTURE_MIN_FILTER, min as i32);
+            gl::TextureParameteri(self.id, gl::TEXTURE_MAG_FILTER, mag as i32);
+        }
+    }
+
+    /// Sets how texture coordinates outside of [0, 1] are handled
+    ///
+    /// # Arguments
+    /// * `s` - Horizontal wrap mode, e.g. REPEAT, MIRRORED_REPEAT or CLAMP_TO_EDGE
+    /// * `t` - Vertical wrap mode
+    /// * `r` - Depth wrap mode
+    pub fn set_wrap(&self, s: gl::types::GLenum, t: gl::types::GLenum, r: gl::types::GLenum) {
+        unsafe {
+            gl::TextureParameteri(self.id, gl::TEXTURE_WRAP_S, s as i32);
+            gl::TextureParameteri(self.id, gl::TEXTURE_WRAP_T, t as i32);
+            gl::TextureParameteri(self.id, gl::TEXTURE_WRAP_R, r as i32);
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn depth(&self) -> u32 {
+        self.depth
+    }
+
+    /// Number of mipmap levels the storage was allocated with
+    pub fn levels(&self) -> i32 {
+        self.levels
+    }
+}
@@ -0,0 +1,91 @@
+use crate::PipelineShader;
+
+use super::{Sampler, Texture};
+
+/// Collects the textures of a draw call and assigns them to consecutive texture units.
+///
+/// # Examples
+/// ```
+/// let sampler = Sampler::new().filter(gl::NEAREST, gl::NEAREST);
+///
+/// TextureUnits::new()
+///     .add("albedo", &albedo, Some(&sampler))
+///     .add("normals", &normals, None)
+///     .bind(window.pipeline_shader())?;
+///
+/// window.draw(&shape);
+/// ```
+pub struct TextureUnits<'a> {
+    first_unit: u32,
+    bindings: Vec<(&'a str, &'a dyn Texture, Option<&'a Sampler>)>,
+}
+
+impl<'a> Default for TextureUnits<'a> {
+    fn default() -> TextureUnits<'a> {
+        TextureUnits::new()
+    }
+}
+
+impl<'a> TextureUnits<'a> {
+    /// Starts at unit 1, unit 0 is used by the default shader for shape textures
+    pub fn new() -> TextureUnits<'a> {
+        TextureUnits::starting_at(1)
+    }
+
+    /// Allocates units starting from the given one
+    pub fn starting_at(first_unit: u32) -> TextureUnits<'a> {
+        TextureUnits {
+            first_unit,
+            bindings: Vec::new(),
+        }
+    }
+
+    /// Adds a texture for the sampler uniform with the given name.
+    /// Without a sampler, the parameters of the texture itself are used.
+    pub fn add(
+        &mut self,
+        name: &'a str,
+        texture: &'a dyn Texture,
+        sampler: Option<&'a Sampler>,
+    ) -> &mut Self {
+        self.bindings.push((name, texture, sampler));
+        self
+    }
+
+    /// Texture unit the texture of the given uniform is bound to
+    pub fn unit(&self, name: &str) -> Option<u32> {
+        self.bindings
+            .iter()
+            .position(|(n, _, _)| *n == name)
+            .map(|i| self.first_unit + i as u32)
+    }
+
+    /// Binds all textures and samplers and points the sampler uniforms of the shader to their units.
+    /// Fails if there are more textures than texture units.
+    pub fn bind(&self, shader: &PipelineShader) -> Result<(), Box<dyn std::error::Error>> {
+        let mut max_units = 0;
+        unsafe {
+            gl::GetIntegerv(gl::MAX_COMBINED_TEXTURE_IMAGE_UNITS, &mut max_units);
+        }
+
+        let needed = self.first_unit as usize + self.bindings.len();
+        if needed > max_units as usize {
+            Err(format!(
+                "{} texture units are needed, but only {} are available",
+                needed, max_units
+            ))?;
+        }
+
+        for (unit, (name, texture, sampler)) in (self.first_unit..).zip(&self.bindings) {
+            texture.bind(unit);
+            match sampler {
+                Some(sampler) => sampler.bind(unit),
+                None => Sampler::unbind(unit),
+            }
+
+            shader.set_uniform(name, unit as i32);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,179 @@
+//! This module implements 2D affine transforms, which place shapes in the window.
+//! A Transform2D is a matrix that can be combined with others,
+//! a Placement holds the parts of a shape's transform which are easy to animate.
+
+use std::ops::Mul;
+
+use vector::Vector2;
+
+/// An affine 2D transform, the upper two rows of a 3x3 matrix.
+/// Transforms are combined with `*`, where the right hand side is applied first, or with `then`.
+///
+/// # Examples
+/// ```
+/// // Rotates around (100, 100) instead of the origin
+/// let spin = Transform2D::translation(Vector2::new(-100.0, -100.0))
+///     .then(Transform2D::rotation(angle))
+///     .then(Transform2D::translation(Vector2::new(100.0, 100.0)));
+///
+/// let world = parent.transform() * spin;
+/// let point = world.apply(Vector2::new(10.0, 0.0));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform2D {
+    // x' = m[0] * x + m[2] * y + m[4]
+    // y' = m[1] * x + m[3] * y + m[5]
+    m: [f32; 6],
+}
+
+impl Default for Transform2D {
+    fn default() -> Self {
+        Transform2D::IDENTITY
+    }
+}
+
+impl Mul for Transform2D {
+    type Output = Transform2D;
+
+    fn mul(self, rhs: Transform2D) -> Transform2D {
+        let [a, b, c, d, x, y] = self.m;
+        let [ra, rb, rc, rd, rx, ry] = rhs.m;
+
+        Transform2D {
+            m: [
+                a * ra + c * rb,
+                b * ra + d * rb,
+                a * rc + c * rd,
+                b * rc + d * rd,
+                a * rx + c * ry + x,
+                b * rx + d * ry + y,
+            ],
+        }
+    }
+}
+
+impl Transform2D {
+    pub const IDENTITY: Transform2D = Transform2D::new(1.0, 0.0, 0.0, 1.0, 0.0, 0.0);
+
+    /// Creates a transform from the columns of its matrix.
+    ///
+    /// # Arguments
+    /// * `a`, `b` - Where the x axis ends up
+    /// * `c`, `d` - Where the y axis ends up
+    /// * `x`, `y` - Translation
+    pub const fn new(a: f32, b: f32, c: f32, d: f32, x: f32, y: f32) -> Transform2D {
+        Transform2D {
+            m: [a, b, c, d, x, y],
+        }
+    }
+
+    pub fn translation(translation: Vector2<f32>) -> Transform2D {
+        Transform2D::new(1.0, 0.0, 0.0, 1.0, translation.x, translation.y)
+    }
+
+    /// Rotation by the given angle in radians, in the same direction as Shape2D::rotate
+    pub fn rotation(angle: f32) -> Transform2D {
+        let (sin, cos) = angle.sin_cos();
+        Transform2D::new(cos, -sin, sin, cos, 0.0, 0.0)
+    }
+
+    pub fn scaling(scale: Vector2<f32>) -> Transform2D {
+        Transform2D::new(scale.x, 0.0, 0.0, scale.y, 0.0, 0.0)
+    }
+
+    /// Moves x by `shear.x * y` and y by `shear.y * x`
+    pub fn shearing(shear: Vector2<f32>) -> Transform2D {
+        Transform2D::new(1.0, shear.y, shear.x, 1.0, 0.0, 0.0)
+    }
+
+    /// Applies this transform first and the next one afterwards, the same as `next * self`
+    pub fn then(self, next: Transform2D) -> Transform2D {
+        next * self
+    }
+
+    /// The transform undoing this one, None if it squashes everything onto a line or point
+    pub fn inverse(&self) -> Option<Transform2D> {
+        let [a, b, c, d, x, y] = self.m;
+        let det = a * d - b * c;
+        if det.abs() <= f32::EPSILON {
+            return None;
+        }
+
+        let (a, b, c, d) = (d / det, -b / det, -c / det, a / det);
+        Some(Transform2D::new(
+            a,
+            b,
+            c,
+            d,
+            -(a * x + c * y),
+            -(b * x + d * y),
+        ))
+    }
+
+    /// Transforms a point
+    pub fn apply(&self, point: Vector2<f32>) -> Vector2<f32> {
+        let [a, b, c, d, x, y] = self.m;
+        Vector2::new(a * point.x + c * point.y + x, b * point.x + d * point.y + y)
+    }
+
+    /// The columns of the matrix, [a, b, c, d, x, y]
+    pub fn to_cols(&self) -> [f32; 6] {
+        self.m
+    }
+
+    /// The full 3x3 matrix in column major order, as expected by a GLSL mat3
+    pub fn to_mat3(&self) -> [f32; 9] {
+        let [a, b, c, d, x, y] = self.m;
+        [a, b, 0.0, c, d, 0.0, x, y, 1.0]
+    }
+}
+
+/// The parts a shape's transform is built from. The vertices of a shape are relative to its center,
+/// they are scaled, sheared and rotated around the origin which is then moved to `center`.
+///
+/// # Examples
+/// ```
+/// // Swings from the top edge instead of the middle
+/// let placement = shape.placement_mut();
+/// placement.origin = Vector2::new(0.0, -height / 2.0);
+/// placement.angle = time.sin() * 0.5;
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Placement {
+    /// Where the origin ends up in the window
+    pub center: Vector2<f32>,
+    /// Rotation in radians
+    pub angle: f32,
+    pub scale: Vector2<f32>,
+    /// See Transform2D::shearing
+    pub shear: Vector2<f32>,
+    /// Pivot in the coordinates of the vertices, (0, 0) by default
+    pub origin: Vector2<f32>,
+}
+
+impl Default for Placement {
+    fn default() -> Self {
+        Placement::new(Vector2::new(0.0, 0.0))
+    }
+}
+
+impl Placement {
+    /// Creates an unrotated, unscaled placement at the given center
+    pub fn new(center: Vector2<f32>) -> Placement {
+        Placement {
+            center,
+            angle: 0.0,
+            scale: Vector2::new(1.0, 1.0),
+            shear: Vector2::new(0.0, 0.0),
+            origin: Vector2::new(0.0, 0.0),
+        }
+    }
+
+    pub fn transform(&self) -> Transform2D {
+        Transform2D::translation(Vector2::new(-self.origin.x, -self.origin.y))
+            .then(Transform2D::scaling(self.scale))
+            .then(Transform2D::shearing(self.shear))
+            .then(Transform2D::rotation(self.angle))
+            .then(Transform2D::translation(self.center))
+    }
+}
@@ -0,0 +1,162 @@
+/// Describes where a single vertex attribute lives inside a buffer
+/// and how it is handed to the shader.
+#[derive(Debug, Clone, Copy)]
+pub struct Attribute {
+    pub(crate) name: &'static str,
+    pub(crate) location: u32,
+    pub(crate) size: i32,
+    pub(crate) gl_type: gl::types::GLenum,
+    pub(crate) normalized: gl::types::GLboolean,
+    pub(crate) offset: usize,
+    pub(crate) divisor: u32,
+}
+
+impl Attribute {
+    /// Creates a new attribute description.
+    ///
+    /// # Arguments
+    /// * `location` - Shader attribute location
+    /// * `size` - Number of components (1 to 4)
+    /// * `gl_type` - Component type (FLOAT, UNSIGNED_INT ...)
+    /// * `offset` - Byte offset of the attribute inside one element
+    ///
+    /// # Examples
+    /// ```
+    /// #[repr(C)]
+    /// struct Particle {
+    ///     position: Vector3<f32>,
+    ///     velocity: Vector3<f32>,
+    /// }
+    ///
+    /// let position = Attribute::new(0, 3, gl::FLOAT, 0);
+    /// ```
+    pub fn new(location: u32, size: i32, gl_type: gl::types::GLenum, offset: usize) -> Attribute {
+        Attribute {
+            name: "",
+            location,
+            size,
+            gl_type,
+            normalized: gl::FALSE,
+            offset,
+            divisor: 0,
+        }
+    }
+
+    /// Whether fixed point values should be normalized when accessed
+    pub fn normalized(self, normalized: bool) -> Attribute {
+        Attribute {
+            normalized: if normalized { gl::TRUE } else { gl::FALSE },
+            ..self
+        }
+    }
+
+    /// Name of the attribute in the shader
+    pub fn named(self, name: &'static str) -> Attribute {
+        Attribute { name, ..self }
+    }
+
+    /// Advances the attribute once per `divisor` instances instead of once per vertex, 0 by default.
+    /// See https://www.khronos.org/registry/OpenGL-Refpages/gl4/html/glVertexAttribDivisor.xhtml
+    pub fn divisor(self, divisor: u32) -> Attribute {
+        Attribute { divisor, ..self }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn location(&self) -> u32 {
+        self.location
+    }
+
+    pub fn size(&self) -> i32 {
+        self.size
+    }
+
+    pub fn gl_type(&self) -> gl::types::GLenum {
+        self.gl_type
+    }
+
+    pub fn is_normalized(&self) -> bool {
+        self.normalized == gl::TRUE
+    }
+
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    pub fn instance_divisor(&self) -> u32 {
+        self.divisor
+    }
+
+    /// Sets the attribute pointer for the buffer bound to ARRAY_BUFFER and enables it on the bound VAO.
+    /// Integer attributes which aren't normalized are passed with glVertexAttribIPointer.
+    pub(crate) fn enable(&self, stride: i32) {
+        let offset = self.offset as *const std::ffi::c_void;
+
+        unsafe {
+            if self.is_integer() {
+                gl::VertexAttribIPointer(self.location, self.size, self.gl_type, stride, offset);
+            } else {
+                gl::VertexAttribPointer(
+                    self.location,
+                    self.size,
+                    self.gl_type,
+                    self.normalized,
+                    stride,
+                    offset,
+                );
+            }
+
+            gl::EnableVertexAttribArray(self.location);
+            gl::VertexAttribDivisor(self.location, self.divisor);
+        }
+    }
+
+    /// Whether the attribute has to be passed with glVertexAttribIPointer
+    pub(crate) fn is_integer(&self) -> bool {
+        matches!(
+            self.gl_type,
+            gl::BYTE
+                | gl::UNSIGNED_BYTE
+                | gl::SHORT
+                | gl::UNSIGNED_SHORT
+                | gl::INT
+                | gl::UNSIGNED_INT
+        ) && self.normalized == gl::FALSE
+    }
+}
+
+/// Something about a shader's vertex input that the vertex attributes can't satisfy
+#[derive(Debug, Clone, PartialEq)]
+pub enum AttributeMismatch {
+    /// The shader reads an input no vertex attribute is named after
+    Missing { name: String, location: u32 },
+    /// Integer data is read as float by the shader, or the other way around
+    Type {
+        name: String,
+        gl_type: gl::types::GLenum,
+        shader_type: gl::types::GLenum,
+    },
+}
+
+impl std::fmt::Display for AttributeMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AttributeMismatch::Missing { name, location } => write!(
+                f,
+                "shader input '{}' (location {}) has no matching vertex attribute",
+                name, location
+            ),
+            AttributeMismatch::Type {
+                name,
+                gl_type,
+                shader_type,
+            } => write!(
+                f,
+                "vertex attribute '{}' of type 0x{:X} can't be read as shader type 0x{:X}",
+                name, gl_type, shader_type
+            ),
+        }
+    }
+}
@@ -0,0 +1,108 @@
+use std::{cell::Cell, ffi::c_void};
+
+/// Storage shared by VBO and EBO, which can be orphaned and grows on demand.
+/// Uses direct state access, so updates don't touch the bound VAO.
+pub(super) struct Buffer {
+    pub(super) id: u32,
+    capacity: Cell<usize>,
+    usage: gl::types::GLenum,
+}
+
+impl Drop for Buffer {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteBuffers(1, &self.id);
+        }
+    }
+}
+
+impl Buffer {
+    pub(super) fn new<T>(data: &[T], usage: gl::types::GLenum) -> Buffer {
+        let mut id = 0;
+        let capacity = std::mem::size_of_val(data);
+        unsafe {
+            gl::CreateBuffers(1, &mut id);
+            gl::NamedBufferData(id, capacity as isize, data.as_ptr() as *const c_void, usage);
+        }
+
+        Buffer {
+            id,
+            capacity: Cell::new(capacity),
+            usage,
+        }
+    }
+
+    pub(super) fn capacity(&self) -> usize {
+        self.capacity.get()
+    }
+
+    pub(super) fn usage(&self) -> gl::types::GLenum {
+        self.usage
+    }
+
+    /// Detaches the current storage, so writes don't wait for draws still reading it
+    pub(super) fn orphan(&self) {
+        unsafe {
+            gl::NamedBufferData(
+                self.id,
+                self.capacity() as isize,
+                std::ptr::null(),
+                self.usage,
+            );
+        }
+    }
+
+    /// Replaces the whole content, growing the buffer if the data doesn't fit
+    pub(super) fn write<T>(&self, data: &[T]) {
+        let size = std::mem::size_of_val(data);
+        if size > self.capacity() {
+            self.capacity.set(size.max(self.capacity() * 2));
+        }
+
+        self.orphan();
+        unsafe {
+            gl::NamedBufferSubData(self.id, 0, size as isize, data.as_ptr() as *const c_void);
+        }
+    }
+
+    /// Overwrites a part of the content, growing the buffer and keeping the rest
+    pub(super) fn write_range<T>(&self, offset: usize, data: &[T]) {
+        let size = std::mem::size_of_val(data);
+        self.reserve(offset + size);
+        unsafe {
+            gl::NamedBufferSubData(
+                self.id,
+                offset as isize,
+                size as isize,
+                data.as_ptr() as *const c_void,
+            );
+        }
+    }
+
+    /// Grows the buffer to hold at least `size` bytes, keeping the content.
+    /// The id stays the same, so VAOs don't need to be set up again.
+    fn reserve(&self, size: usize) {
+        let old = self.capacity();
+        if size <= old {
+            return;
+        }
+        let capacity = size.max(old * 2);
+
+        unsafe {
+            let mut copy = 0;
+            gl::CreateBuffers(1, &mut copy);
+            gl::NamedBufferData(copy, old as isize, std::ptr::null(), gl::STREAM_COPY);
+            if old > 0 {
+                gl::CopyNamedBufferSubData(self.id, copy, 0, 0, old as isize);
+            }
+
+            gl::NamedBufferData(self.id, capacity as isize, std::ptr::null(), self.usage);
+            if old > 0 {
+                gl::CopyNamedBufferSubData(copy, self.id, 0, 0, old as isize);
+            }
+            gl::DeleteBuffers(1, &copy);
+        }
+
+        self.capacity.set(capacity);
+    }
+}
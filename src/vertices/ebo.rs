@@ -1,43 +1,154 @@
-pub struct EBO {
-    id: u32,
-    len: usize,
-}
+use super::buffer::Buffer;
 
-impl Drop for EBO {
-    fn drop(&mut self) {
-        unsafe {
-            gl::DeleteBuffers(1, &self.id);
+/// Integer types which can be used as indices.
+/// The largest value of each type restarts the primitive, so one strip or fan can be split into several.
+pub trait IndexType: Copy + PartialEq + Into<u32> {
+    const GL_TYPE: gl::types::GLenum;
+    /// Index which ends the current strip or fan
+    const RESTART: Self;
+
+    /// The index as u32, keeping the meaning of RESTART
+    fn widen(self) -> u32 {
+        if self == Self::RESTART {
+            u32::RESTART
+        } else {
+            self.into()
         }
     }
 }
 
+impl IndexType for u8 {
+    const GL_TYPE: gl::types::GLenum = gl::UNSIGNED_BYTE;
+    const RESTART: u8 = u8::MAX;
+}
+
+impl IndexType for u16 {
+    const GL_TYPE: gl::types::GLenum = gl::UNSIGNED_SHORT;
+    const RESTART: u16 = u16::MAX;
+}
+
+impl IndexType for u32 {
+    const GL_TYPE: gl::types::GLenum = gl::UNSIGNED_INT;
+    const RESTART: u32 = u32::MAX;
+}
+
+pub struct EBO {
+    buffer: Buffer,
+    len: usize,
+    index_type: gl::types::GLenum,
+}
+
 impl EBO {
     pub fn new(indices: Option<&Vec<u32>>) -> EBO {
-        let mut id = 0 as u32;
+        let indices: &[u32] = match indices {
+            Some(ind) => ind,
+            None => &[],
+        };
+        EBO::with_usage(indices, gl::STATIC_DRAW)
+    }
+
+    /// Creates an ebo with a usage hint and binds it to the bound VAO
+    ///
+    /// # Arguments
+    /// * `indices` - Initial content, may be empty. The type (u8, u16 or u32) is used for drawing.
+    /// * `usage` - STATIC_DRAW, DYNAMIC_DRAW for indices changing now and then, STREAM_DRAW for indices changing every frame
+    ///
+    /// # Examples
+    /// ```
+    /// // Two strips in one draw call
+    /// let ebo = EBO::with_usage(&[0u16, 1, 2, 3, u16::RESTART, 4, 5, 6, 7], gl::STATIC_DRAW);
+    /// ```
+    pub fn with_usage<T: IndexType>(indices: &[T], usage: gl::types::GLenum) -> EBO {
+        let buffer = Buffer::new(indices, usage);
         unsafe {
-            gl::GenBuffers(1, &mut id);
+            gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, buffer.id);
+        }
+
+        EBO {
+            buffer,
+            len: indices.len(),
+            index_type: T::GL_TYPE,
         }
+    }
 
-        let mut len = 0;
-
-        match indices {
-            Some(ind) => unsafe {
-                len = ind.len();
-                gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, id);
-                gl::BufferData(
-                    gl::ELEMENT_ARRAY_BUFFER,
-                    (ind.len() * std::mem::size_of::<u32>()) as isize,
-                    ind.as_ptr() as *const _,
-                    gl::STATIC_DRAW,
-                );
-            },
-            None => {}
+    /// Replaces all indices. The old storage is orphaned and the ebo grows if needed.
+    /// The index type may differ from the previous one.
+    ///
+    /// # Examples
+    /// ```
+    /// // Adds a quad to a growing mesh
+    /// let first = (indices.len() / 6 * 4) as u32;
+    /// indices.extend([0, 1, 2, 1, 2, 3].iter().map(|i| first + i));
+    /// ebo.update(&indices);
+    /// ```
+    pub fn update<T: IndexType>(&mut self, indices: &[T]) {
+        self.buffer.write(indices);
+        self.len = indices.len();
+        self.index_type = T::GL_TYPE;
+    }
+
+    /// Overwrites some of the indices, keeping the others.
+    /// Writing past the end appends the indices, but the range must not start after the last index,
+    /// since the gap would be drawn with undefined indices.
+    ///
+    /// # Arguments
+    /// * `offset` - Position of the first index to overwrite, at most `len()`
+    /// * `indices` - The new indices, must have the type of the current ones
+    pub fn update_range<T: IndexType>(
+        &mut self,
+        offset: usize,
+        indices: &[T],
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        if T::GL_TYPE != self.index_type {
+            Err(format!(
+                "Index type 0x{:X} doesn't match the type 0x{:X} of the ebo",
+                T::GL_TYPE,
+                self.index_type
+            ))?
         }
 
-        EBO { id, len }
+        if offset > self.len {
+            Err(format!(
+                "Offset {} is past the end of the ebo with {} indices",
+                offset, self.len
+            ))?
+        }
+
+        self.buffer
+            .write_range(offset * std::mem::size_of::<T>(), indices);
+        self.len = self.len.max(offset + indices.len());
+        Ok(())
+    }
+
+    /// Detaches the current storage of the ebo, so following writes don't wait for the gpu.
+    /// The content is undefined afterwards.
+    pub fn orphan(&self) {
+        self.buffer.orphan();
+    }
+
+    pub fn id(&self) -> u32 {
+        self.buffer.id
+    }
+
+    /// GL type of the indices (UNSIGNED_BYTE, UNSIGNED_SHORT or UNSIGNED_INT)
+    pub fn index_type(&self) -> gl::types::GLenum {
+        self.index_type
     }
 
     pub fn len(&self) -> usize {
         self.len
     }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Allocated size in bytes
+    pub fn capacity(&self) -> usize {
+        self.buffer.capacity()
+    }
+
+    pub fn usage(&self) -> gl::types::GLenum {
+        self.buffer.usage()
+    }
 }
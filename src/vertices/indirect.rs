@@ -0,0 +1,33 @@
+use crate::GPU;
+
+/// Layout of one draw read by glMultiDrawArraysIndirect.
+/// See https://www.khronos.org/registry/OpenGL-Refpages/gl4/html/glMultiDrawArraysIndirect.xhtml
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct DrawArraysIndirectCommand {
+    /// Number of vertices
+    pub count: u32,
+    /// Number of instances, 0 skips the draw
+    pub instance_count: u32,
+    pub first: u32,
+    pub base_instance: u32,
+}
+
+impl GPU for DrawArraysIndirectCommand {}
+
+/// Layout of one draw read by glMultiDrawElementsIndirect.
+/// See https://www.khronos.org/registry/OpenGL-Refpages/gl4/html/glMultiDrawElementsIndirect.xhtml
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct DrawElementsIndirectCommand {
+    /// Number of indices
+    pub count: u32,
+    /// Number of instances, 0 skips the draw
+    pub instance_count: u32,
+    pub first_index: u32,
+    /// Added to every index before the vertex is fetched
+    pub base_vertex: i32,
+    pub base_instance: u32,
+}
+
+impl GPU for DrawElementsIndirectCommand {}
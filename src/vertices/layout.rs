@@ -0,0 +1,93 @@
+use vector::{Vector2, Vector3, Vector4};
+
+use crate::Color;
+
+use super::Attribute;
+
+/// Describes the attributes of a vertex type, so buffers of it can be set up automatically.
+/// Usually implemented with `#[derive(Vertex)]`.
+///
+/// # Examples
+/// ```
+/// #[derive(Vertex)]
+/// struct LitVertex {
+///     position: Vector3<f32>,
+///     normal: Vector3<f32>,
+///     #[vertex(normalized)]
+///     color: [u8; 4],
+///     #[vertex(location = 5)]
+///     material: u32,
+/// }
+///
+/// let vao = VAO::new();
+/// let vbo = VBO::from_vertices(&vertices);
+/// ```
+pub trait VertexLayout {
+    fn attributes() -> Vec<Attribute>;
+}
+
+/// Types which can be a single vertex attribute
+pub trait AttributeType {
+    /// Component type (FLOAT, UNSIGNED_INT ...)
+    const GL_TYPE: gl::types::GLenum;
+    /// Number of components (1 to 4)
+    const COUNT: i32;
+}
+
+/// Single component attribute types, which arrays and vectors can be made of.
+/// Larger or nested types don't fit into one attribute location.
+pub trait ScalarAttribute: AttributeType {}
+
+macro_rules! scalar {
+    ($type:ty, $gl_type:expr) => {
+        impl AttributeType for $type {
+            const GL_TYPE: gl::types::GLenum = $gl_type;
+            const COUNT: i32 = 1;
+        }
+
+        impl ScalarAttribute for $type {}
+    };
+}
+
+scalar!(f32, gl::FLOAT);
+scalar!(f64, gl::DOUBLE);
+scalar!(i8, gl::BYTE);
+scalar!(u8, gl::UNSIGNED_BYTE);
+scalar!(i16, gl::SHORT);
+scalar!(u16, gl::UNSIGNED_SHORT);
+scalar!(i32, gl::INT);
+scalar!(u32, gl::UNSIGNED_INT);
+
+// Only up to 4 components, more are rejected by glVertexAttribPointer
+macro_rules! array {
+    ($($n:expr),*) => {
+        $(
+            impl<T: ScalarAttribute> AttributeType for [T; $n] {
+                const GL_TYPE: gl::types::GLenum = T::GL_TYPE;
+                const COUNT: i32 = $n;
+            }
+        )*
+    };
+}
+
+array!(1, 2, 3, 4);
+
+impl<T: ScalarAttribute> AttributeType for Vector2<T> {
+    const GL_TYPE: gl::types::GLenum = T::GL_TYPE;
+    const COUNT: i32 = 2;
+}
+
+impl<T: ScalarAttribute> AttributeType for Vector3<T> {
+    const GL_TYPE: gl::types::GLenum = T::GL_TYPE;
+    const COUNT: i32 = 3;
+}
+
+impl<T: ScalarAttribute> AttributeType for Vector4<T> {
+    const GL_TYPE: gl::types::GLenum = T::GL_TYPE;
+    const COUNT: i32 = 4;
+}
+
+impl AttributeType for Color {
+    const GL_TYPE: gl::types::GLenum = gl::FLOAT;
+    const COUNT: i32 = 4;
+}
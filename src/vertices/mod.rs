@@ -1,9 +1,17 @@
+mod attribute;
+mod buffer;
 mod vao;
 mod vbo;
 mod ebo;
+mod indirect;
+mod layout;
 mod vertex;
 
-pub use vao::VAO;
+pub use attribute::{Attribute, AttributeMismatch};
+pub use vao::{VertexSource, VAO};
 pub use vbo::VBO;
-pub use ebo::EBO;
+pub use ebo::{IndexType, EBO};
+pub use indirect::{DrawArraysIndirectCommand, DrawElementsIndirectCommand};
+pub use layout::{AttributeType, ScalarAttribute, VertexLayout};
+pub use rusty_gl_derive::Vertex;
 pub use vertex::Vertex;
\ No newline at end of file
@@ -1,7 +1,33 @@
+use crate::{PipelineShader, GPU, SSBO};
+
+use super::{
+    Attribute, AttributeMismatch, DrawArraysIndirectCommand, DrawElementsIndirectCommand, EBO, VBO,
+};
+
 pub struct VAO {
     id: u32,
 }
 
+/// A vbo together with the layout of its elements, see VAO::bind_sources
+#[derive(Clone, Copy)]
+pub struct VertexSource<'a> {
+    pub vbo: &'a VBO,
+    /// Size of one element in bytes
+    pub stride: i32,
+    pub attributes: &'a [Attribute],
+}
+
+impl<'a> VertexSource<'a> {
+    /// Creates a source of elements of type T, using its size as stride
+    pub fn new<T>(vbo: &'a VBO, attributes: &'a [Attribute]) -> VertexSource<'a> {
+        VertexSource {
+            vbo,
+            stride: std::mem::size_of::<T>() as i32,
+            attributes,
+        }
+    }
+}
+
 impl Drop for VAO {
     fn drop(&mut self) {
         unsafe {
@@ -28,14 +54,281 @@ impl VAO {
         }
     }
 
-    pub fn draw(&self, mode: u32, vertex_count: i32, has_ebo: bool) {
+    /// Uses a ssbo as vertex buffer of this VAO, so data written by a compute shader can be drawn directly.
+    /// Integer attributes which aren't normalized are passed with glVertexAttribIPointer.
+    ///
+    /// # Arguments
+    /// * `ssbo` - The ssbo holding the vertex data
+    /// * `stride` - Size of one element in bytes
+    /// * `attributes` - Layout of the attributes inside one element
+    /// * `divisor` - 0 for per vertex data, 1 for per instance data. See https://www.khronos.org/registry/OpenGL-Refpages/gl4/html/glVertexAttribDivisor.xhtml
+    ///
+    /// # Examples
+    /// ```
+    /// let particles = SSBO::create_from(0, vec![Particle::default(); 1000], gl::DYNAMIC_DRAW);
+    ///
+    /// let vao = VAO::new();
+    /// vao.attach_ssbo(
+    ///     &particles,
+    ///     std::mem::size_of::<Particle>() as i32,
+    ///     &[Attribute::new(0, 3, gl::FLOAT, 0)],
+    ///     0,
+    /// );
+    /// ```
+    pub fn attach_ssbo<T: GPU>(
+        &self,
+        ssbo: &SSBO<T>,
+        stride: i32,
+        attributes: &[Attribute],
+        divisor: u32,
+    ) {
+        unsafe {
+            gl::BindVertexArray(self.id);
+            gl::BindBuffer(gl::ARRAY_BUFFER, ssbo.id());
+        }
+
+        for attribute in attributes {
+            Attribute {
+                divisor,
+                ..*attribute
+            }
+            .enable(stride);
+        }
+    }
+
+    /// Points the inputs of the shader at the vertex attributes with the same name,
+    /// so the locations don't have to be lined up with the GLSL by hand.
+    /// Inputs without a matching attribute are disabled and read their default value.
+    ///
+    /// # Arguments
+    /// * `vbo` - The vbo holding the vertex data
+    /// * `stride` - Size of one vertex in bytes
+    /// * `attributes` - Layout of one vertex, the locations are ignored
+    /// * `shader` - The shader the vertices are drawn with
+    ///
+    /// # Examples
+    /// ```
+    /// let mismatches = vao.bind_attributes(
+    ///     &vbo,
+    ///     std::mem::size_of::<LitVertex>() as i32,
+    ///     &LitVertex::attributes(),
+    ///     &shader,
+    /// );
+    /// for mismatch in mismatches {
+    ///     println!("{}", mismatch);
+    /// }
+    /// ```
+    pub fn bind_attributes(
+        &self,
+        vbo: &VBO,
+        stride: i32,
+        attributes: &[Attribute],
+        shader: &PipelineShader,
+    ) -> Vec<AttributeMismatch> {
+        self.bind_sources(
+            &[VertexSource {
+                vbo,
+                stride,
+                attributes,
+            }],
+            shader,
+        )
+    }
+
+    /// Like bind_attributes, but the inputs can be fed from several vbos,
+    /// e.g. per vertex data from one and per instance data from another.
+    /// If several sources have an attribute with the same name, the first one is used.
+    ///
+    /// # Examples
+    /// ```
+    /// let vertex_attributes = Vertex::attributes();
+    /// let instance_attributes: Vec<Attribute> = Particle::attributes()
+    ///     .into_iter()
+    ///     .map(|attribute| attribute.divisor(1))
+    ///     .collect();
+    ///
+    /// vao.bind_sources(
+    ///     &[
+    ///         VertexSource::new::<Vertex>(&vertices, &vertex_attributes),
+    ///         VertexSource::new::<Particle>(&particles, &instance_attributes),
+    ///     ],
+    ///     &shader,
+    /// );
+    /// ```
+    pub fn bind_sources(
+        &self,
+        sources: &[VertexSource],
+        shader: &PipelineShader,
+    ) -> Vec<AttributeMismatch> {
+        self.bind();
+
+        let mut mismatches = Vec::new();
+        for input in shader.attributes() {
+            let found = sources.iter().find_map(|source| {
+                source
+                    .attributes
+                    .iter()
+                    .find(|a| a.name == input.name)
+                    .map(|attribute| (source, attribute))
+            });
+
+            match found {
+                Some((source, attribute)) => {
+                    if attribute.is_integer() != input.is_integer() {
+                        mismatches.push(AttributeMismatch::Type {
+                            name: input.name.clone(),
+                            gl_type: attribute.gl_type,
+                            shader_type: input.gl_type,
+                        });
+                    }
+
+                    let attribute = Attribute {
+                        location: input.location,
+                        ..*attribute
+                    };
+                    source.vbo.set_attribute(&attribute, source.stride);
+                }
+                None => {
+                    unsafe {
+                        gl::DisableVertexAttribArray(input.location);
+                    }
+                    mismatches.push(AttributeMismatch::Missing {
+                        name: input.name.clone(),
+                        location: input.location,
+                    });
+                }
+            }
+        }
+
+        mismatches
+    }
+
+    /// Draws the bound vertices. With an ebo, `vertex_count` indices are read from it,
+    /// using the index type of the ebo.
+    ///
+    /// # Arguments
+    /// * `mode` - Primitive type (TRIANGLES, TRIANGLE_STRIP ...)
+    /// * `vertex_count` - Number of vertices, or indices if an ebo is given
+    /// * `ebo` - Index buffer bound to this VAO
+    pub fn draw(&self, mode: u32, vertex_count: i32, ebo: Option<&EBO>) {
+        self.draw_range(mode, 0, vertex_count, ebo);
+    }
+
+    /// Like draw, but starts at the given vertex, or index if an ebo is given
+    ///
+    /// # Examples
+    /// ```
+    /// // The second quad of a list of quads
+    /// vao.draw_range(gl::TRIANGLES, 6, 6, Some(&ebo));
+    /// ```
+    pub fn draw_range(&self, mode: u32, first: i32, vertex_count: i32, ebo: Option<&EBO>) {
+        unsafe {
+            gl::BindVertexArray(self.id);
+            match ebo {
+                Some(ebo) => {
+                    let index_size = match ebo.index_type() {
+                        gl::UNSIGNED_BYTE => 1,
+                        gl::UNSIGNED_SHORT => 2,
+                        _ => 4,
+                    };
+                    gl::DrawElements(
+                        mode,
+                        vertex_count,
+                        ebo.index_type(),
+                        (first as usize * index_size) as *const std::ffi::c_void,
+                    )
+                }
+                None => gl::DrawArrays(mode, first, vertex_count),
+            }
+        }
+    }
+
+    /// Draws `instance_count` instances of the bound vertices.
+    /// See https://www.khronos.org/registry/OpenGL-Refpages/gl4/html/glDrawArraysInstanced.xhtml
+    pub fn draw_instanced(
+        &self,
+        mode: u32,
+        vertex_count: i32,
+        instance_count: i32,
+        ebo: Option<&EBO>,
+    ) {
         unsafe {
             gl::BindVertexArray(self.id);
-            if has_ebo {
-                gl::DrawElements(mode, vertex_count, gl::UNSIGNED_INT, std::ptr::null());
-            } else {
-                gl::DrawArrays(mode, 0, vertex_count);
+            match ebo {
+                Some(ebo) => gl::DrawElementsInstanced(
+                    mode,
+                    vertex_count,
+                    ebo.index_type(),
+                    std::ptr::null(),
+                    instance_count,
+                ),
+                None => gl::DrawArraysInstanced(mode, 0, vertex_count, instance_count),
             }
         }
     }
+
+    /// Draws every command of the buffer with a single call, e.g. after a compute pass culled objects
+    /// by setting their instance count to 0. Commands written by a compute shader need a COMMAND_BARRIER_BIT.
+    ///
+    /// # Arguments
+    /// * `mode` - Primitive type (TRIANGLES, TRIANGLE_STRIP ...)
+    /// * `commands` - The draws, e.g. one per object in the vbo
+    ///
+    /// # Examples
+    /// ```
+    /// let commands = SSBO::create_from(
+    ///     2,
+    ///     objects.iter().map(|object| DrawArraysIndirectCommand {
+    ///         count: object.vertex_count,
+    ///         instance_count: 1,
+    ///         first: object.first_vertex,
+    ///         base_instance: 0,
+    ///     }).collect::<Vec<_>>(),
+    ///     gl::DYNAMIC_DRAW,
+    /// );
+    ///
+    /// // Writes instance_count = 0 for objects outside the view
+    /// culling.dispatch(groups, 1, 1, gl::COMMAND_BARRIER_BIT);
+    ///
+    /// shader.enable();
+    /// vao.multi_draw_arrays_indirect(gl::TRIANGLES, &commands);
+    /// ```
+    pub fn multi_draw_arrays_indirect(
+        &self,
+        mode: u32,
+        commands: &SSBO<Vec<DrawArraysIndirectCommand>>,
+    ) {
+        unsafe {
+            gl::BindVertexArray(self.id);
+            gl::BindBuffer(gl::DRAW_INDIRECT_BUFFER, commands.id());
+            gl::MultiDrawArraysIndirect(mode, std::ptr::null(), commands.len() as i32, 0);
+            gl::BindBuffer(gl::DRAW_INDIRECT_BUFFER, 0);
+        }
+    }
+
+    /// Like multi_draw_arrays_indirect, but reads the vertices through an index buffer
+    ///
+    /// # Arguments
+    /// * `mode` - Primitive type (TRIANGLES, TRIANGLE_STRIP ...)
+    /// * `commands` - The draws, e.g. one per object in the vbo
+    /// * `ebo` - Index buffer bound to this VAO
+    pub fn multi_draw_elements_indirect(
+        &self,
+        mode: u32,
+        commands: &SSBO<Vec<DrawElementsIndirectCommand>>,
+        ebo: &EBO,
+    ) {
+        unsafe {
+            gl::BindVertexArray(self.id);
+            gl::BindBuffer(gl::DRAW_INDIRECT_BUFFER, commands.id());
+            gl::MultiDrawElementsIndirect(
+                mode,
+                ebo.index_type(),
+                std::ptr::null(),
+                commands.len() as i32,
+                0,
+            );
+            gl::BindBuffer(gl::DRAW_INDIRECT_BUFFER, 0);
+        }
+    }
 }
@@ -1,41 +1,97 @@
 use std::ffi::c_void;
 
-use super::Vertex;
+use super::{buffer::Buffer, Attribute, Vertex, VertexLayout};
 
 pub struct VBO {
-    id: u32,
+    buffer: Buffer,
 }
 
-impl Drop for VBO {
-    fn drop(&mut self) {
+impl VBO {
+    pub fn new(vertices: Option<&Vec<Vertex>>) -> VBO {
+        let vertices: &[Vertex] = match vertices {
+            Some(verts) => verts,
+            None => &[],
+        };
+        let buffer = Buffer::new(vertices, gl::STATIC_DRAW);
+
         unsafe {
-            gl::DeleteBuffers(1, &self.id);
+            gl::BindBuffer(gl::ARRAY_BUFFER, buffer.id);
         }
+
+        VBO { buffer }
     }
-}
 
-impl VBO {
-    pub fn new(vertices: Option<&Vec<Vertex>>) -> VBO {
-        let mut id = 0 as u32;
-        unsafe {
-            gl::GenBuffers(1, &mut id);
+    /// Creates a vbo holding the vertices and sets up their attributes on the bound VAO
+    ///
+    /// # Examples
+    /// ```
+    /// #[derive(Vertex)]
+    /// struct ColoredVertex {
+    ///     position: Vector2<f32>,
+    ///     #[vertex(normalized)]
+    ///     color: [u8; 4],
+    /// }
+    ///
+    /// let vao = VAO::new();
+    /// let vbo = VBO::from_vertices(&vertices);
+    /// vao.draw(gl::TRIANGLES, vertices.len() as i32, None);
+    /// ```
+    pub fn from_vertices<T: VertexLayout>(vertices: &[T]) -> VBO {
+        VBO::with_usage(vertices, gl::STATIC_DRAW)
+    }
+
+    /// Like from_vertices, but with a usage hint for vertices which change often.
+    ///
+    /// # Arguments
+    /// * `vertices` - Initial content, may be empty
+    /// * `usage` - STATIC_DRAW, DYNAMIC_DRAW for data changing now and then, STREAM_DRAW for data changing every frame
+    ///
+    /// # Examples
+    /// ```
+    /// let vao = VAO::new();
+    /// let vbo = VBO::with_usage::<Vertex>(&[], gl::STREAM_DRAW);
+    ///
+    /// loop {
+    ///     trail.push(Vertex::new(mouse, color::WHITE, Vector2::default()));
+    ///     vbo.transfer(&trail);
+    ///     vao.draw(gl::LINE_STRIP, trail.len() as i32, None);
+    /// }
+    /// ```
+    pub fn with_usage<T: VertexLayout>(vertices: &[T], usage: gl::types::GLenum) -> VBO {
+        let vbo = VBO::from_data(vertices, usage);
+        vbo.set_layout::<T>();
+        vbo
+    }
+
+    /// Creates a vbo holding the data without touching the bound VAO.
+    /// The attributes can be set up later, e.g. with VAO::bind_sources.
+    ///
+    /// # Arguments
+    /// * `data` - Initial content, may be empty
+    /// * `usage` - STATIC_DRAW, DYNAMIC_DRAW or STREAM_DRAW
+    pub fn from_data<T>(data: &[T], usage: gl::types::GLenum) -> VBO {
+        VBO {
+            buffer: Buffer::new(data, usage),
         }
+    }
 
-        match vertices {
-            Some(verts) => unsafe {
-                gl::BindBuffer(gl::ARRAY_BUFFER, id);
-
-                gl::BufferData(
-                    gl::ARRAY_BUFFER,
-                    verts.len() as isize * std::mem::size_of::<Vertex>() as isize,
-                    verts.as_ptr() as *const _,
-                    gl::STATIC_DRAW,
-                );
-            },
-            None => {}
+    /// Sets up all attributes of the vertex type on the bound VAO
+    pub fn set_layout<T: VertexLayout>(&self) {
+        for attribute in T::attributes() {
+            self.set_attribute(&attribute, std::mem::size_of::<T>() as i32);
         }
+    }
 
-        VBO { id }
+    /// Sets up a single attribute on the bound VAO
+    ///
+    /// # Arguments
+    /// * `attribute` - Layout of the attribute inside one vertex
+    /// * `stride` - Size of one vertex in bytes
+    pub fn set_attribute(&self, attribute: &Attribute, stride: i32) {
+        unsafe {
+            gl::BindBuffer(gl::ARRAY_BUFFER, self.buffer.id);
+        }
+        attribute.enable(stride);
     }
 
     pub fn set_attributes(
@@ -48,21 +104,45 @@ impl VBO {
         offset: *const c_void,
     ) {
         unsafe {
-            gl::BindBuffer(gl::ARRAY_BUFFER, self.id);
+            gl::BindBuffer(gl::ARRAY_BUFFER, self.buffer.id);
             gl::VertexAttribPointer(location, size, gl_type, normalized, stride, offset);
             gl::EnableVertexAttribArray(location);
         }
     }
 
-    pub fn transfer(&self, vertices: &Vec<Vertex>) {
-        unsafe {
-            gl::BindBuffer(gl::ARRAY_BUFFER, self.id);
-            gl::BufferSubData(
-                gl::ARRAY_BUFFER,
-                0,
-                vertices.len() as isize * std::mem::size_of::<Vertex>() as isize,
-                vertices.as_ptr() as *const _,
-            );
-        }
+    /// Replaces the content of the vbo. The old storage is orphaned, so the upload doesn't wait
+    /// for draws still using it, and the vbo grows if the vertices don't fit.
+    pub fn transfer<T>(&self, vertices: &[T]) {
+        self.buffer.write(vertices);
+    }
+
+    /// Overwrites some of the vertices, keeping the others.
+    /// The vbo grows if the range reaches past its end.
+    ///
+    /// # Arguments
+    /// * `offset` - Index of the first vertex to overwrite
+    /// * `vertices` - The new vertices
+    pub fn update<T>(&self, offset: usize, vertices: &[T]) {
+        self.buffer
+            .write_range(offset * std::mem::size_of::<T>(), vertices);
+    }
+
+    /// Detaches the current storage of the vbo, so following writes don't wait for the gpu.
+    /// The content is undefined afterwards.
+    pub fn orphan(&self) {
+        self.buffer.orphan();
+    }
+
+    pub fn id(&self) -> u32 {
+        self.buffer.id
+    }
+
+    /// Allocated size in bytes
+    pub fn capacity(&self) -> usize {
+        self.buffer.capacity()
+    }
+
+    pub fn usage(&self) -> gl::types::GLenum {
+        self.buffer.usage()
     }
 }
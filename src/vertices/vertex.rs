@@ -1,19 +1,26 @@
 use crate::Color;
 use vector::{Vector2, Vector3};
 
-/// |-f32-|-f32-|-f32-|-f32-|-f32-|-f32-|-f32-|-f32-|  <br/>
-/// |-----Position----|------Color------|-UV-coords-|  
+/// |-f32-|-f32-|-f32-|-f32-|-f32-|-f32-|-f32-|-f32-|-f32-|  <br/>
+/// |-----Position----|---------Color---------|-UV-coords-|  
 #[repr(C)]
-#[derive(Debug)]
+#[derive(Debug, super::Vertex)]
 pub struct Vertex {
+    #[vertex(name = "vPos")]
     position: Vector3<f32>,
-    color: Color<f32>,
+    #[vertex(name = "vColor")]
+    color: Color,
+    #[vertex(name = "vTexCoord")]
     texture_coord: Vector2<f32>,
 }
 
 impl Vertex {
-    pub fn new(position: Vector3<f32>, color: Color<f32>, texture_coord: Vector2<f32>) -> Vertex {
-        Vertex { position, color, texture_coord }
+    pub fn new(position: Vector3<f32>, color: Color, texture_coord: Vector2<f32>) -> Vertex {
+        Vertex {
+            position,
+            color,
+            texture_coord,
+        }
     }
 
     pub fn position(&self) -> &Vector3<f32> {
@@ -24,11 +31,11 @@ impl Vertex {
         &mut self.position
     }
 
-    pub fn color(&self) -> &Color<f32> {
+    pub fn color(&self) -> &Color {
         &self.color
     }
 
-    pub fn color_mut(&mut self) -> &mut Color<f32> {
+    pub fn color_mut(&mut self) -> &mut Color {
         &mut self.color
     }
 
@@ -39,4 +46,4 @@ impl Vertex {
     pub fn texture_coord_mut(&mut self) -> &mut Vector2<f32> {
         &mut self.texture_coord
     }
-}
\ No newline at end of file
+}
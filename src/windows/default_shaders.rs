@@ -4,13 +4,18 @@ pub(super) const VERTEX_SHADER: &str = "
 uniform uvec2 resolution;
 
 layout (location = 0) in vec3 vPos;
-layout (location = 1) in vec3 vColor;
+layout (location = 1) in vec4 vColor;
 layout (location = 2) in vec2 vTexCoord;
-uniform vec3 center;
-uniform float angle;
-uniform float scale;
+layout (location = 3) in vec3 vOffset;
+layout (location = 4) in vec2 iCenter;
+layout (location = 5) in float iAngle;
+layout (location = 6) in float iScale;
+layout (location = 7) in vec4 iColor;
+uniform mat3 transform;
+uniform bool instanced;
 
-out vec3 outColor;
+out vec4 outColor;
+out vec2 outTexCoord;
 
 float map(float x, float in_min, float in_max, float out_min, float out_max) {
     return (x - in_min) * (out_max - out_min) / (in_max - in_min) + out_min;
@@ -25,16 +30,23 @@ vec2 rotate(vec2 pos, float angle) {
 
 void main() {
     vec2 pos = vPos.xy;
-    pos = rotate(pos, angle);
+    vec4 color = vColor;
+    if (instanced) {
+        pos = iCenter + rotate(pos * iScale, iAngle);
+        color *= iColor;
+    }
+
+    pos = (transform * vec3(pos, 1.0)).xy + vOffset.xy;
 
     gl_Position = vec4(
-        map(center.x + pos.x * scale, 0.0, resolution.x, -1.0, 1.0), 
-        map(center.y + pos.y * scale, 0.0, resolution.y, 1.0, -1.0),
-        center.z + vPos.z, 
+        map(pos.x, 0.0, resolution.x, -1.0, 1.0), 
+        map(pos.y, 0.0, resolution.y, 1.0, -1.0),
+        vOffset.z + vPos.z, 
         1.0
     );
 
-    outColor = vColor;
+    outColor = color;
+    outTexCoord = vTexCoord;
 }
 ";
 
@@ -42,9 +54,15 @@ pub(super) const FRAGMENT_SHADER: &str = "
 #version 430
 
 out vec4 FragColor;
-in vec3 outColor;
+in vec4 outColor;
+in vec2 outTexCoord;
+
+uniform sampler2D tex;
+uniform bool textured;
+uniform vec4 tint;
 
 void main() {
-    FragColor = vec4(outColor, 1.0);
+    vec4 color = textured ? texture(tex, outTexCoord) : outColor;
+    FragColor = color * tint;
 }
 ";
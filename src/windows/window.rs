@@ -1,4 +1,4 @@
-use crate::{shapes::Drawable, Color, PipelineShader};
+use crate::{render_state::RenderState, shapes::Drawable, Color, PipelineShader};
 
 use super::WindowBuilder;
 
@@ -6,6 +6,7 @@ pub struct Window {
     pub(super) window: sdl2::video::Window,
     pub(super) _gl_context: sdl2::video::GLContext,
     pub(super) pipeline_shader: PipelineShader,
+    pub(super) render_state: RenderState,
 }
 
 impl Window {
@@ -13,17 +14,46 @@ impl Window {
         WindowBuilder::default()
     }
 
-    pub fn clear(&self, color: Color<f32>) {
+    /// Clears the current target to the given color, depth to 1 and stencil to 0.
+    /// The scissor rectangle and the write masks of the last applied render state are respected.
+    pub fn clear(&self, color: Color) {
         unsafe {
-            gl::ClearColor(color.x, color.y, color.z, 1.0);
-            gl::Clear(gl::COLOR_BUFFER_BIT);
+            gl::ClearColor(color.r, color.g, color.b, color.a);
+            gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT | gl::STENCIL_BUFFER_BIT);
         }
     }
 
-    pub fn draw<T: Drawable>(&self, obj: &T) {
+    /// Draws the object using the render state of the window
+    pub fn draw<T: Drawable + ?Sized>(&self, obj: &T) {
+        self.render_state.apply();
         obj.draw(&self.pipeline_shader);
     }
 
+    /// Draws the object using the given render state instead of the one of the window
+    ///
+    /// # Examples
+    /// ```
+    /// window.draw_with(&glow, &RenderState::default().blend(BlendMode::Additive));
+    /// ```
+    pub fn draw_with<T: Drawable + ?Sized>(&self, obj: &T, state: &RenderState) {
+        state.apply();
+        obj.draw(&self.pipeline_shader);
+    }
+
+    /// Sets the render state used by all following draw() calls
+    pub fn set_render_state(&mut self, state: RenderState) {
+        self.render_state = state;
+    }
+
+    pub fn render_state(&self) -> &RenderState {
+        &self.render_state
+    }
+
+    /// Size of the drawable area in pixels
+    pub fn size(&self) -> (u32, u32) {
+        self.window.drawable_size()
+    }
+
     pub fn gl_swap(&self) {
         self.window.gl_swap_window();
     }
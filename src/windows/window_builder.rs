@@ -1,7 +1,7 @@
 use sdl2::VideoSubsystem;
 use vector::Vector2;
 
-use crate::{PipelineShader, ShaderSource};
+use crate::{render_state::RenderState, PipelineShader, ShaderSource};
 
 use super::{
     default_shaders::{FRAGMENT_SHADER, VERTEX_SHADER},
@@ -14,6 +14,7 @@ pub struct WindowBuilder {
     pub(super) height: u32,
     pub(super) fullscreen: bool,
     pub(super) resizable: bool,
+    pub(super) samples: u8,
     pub(super) vertex_shader: ShaderSource,
     pub(super) fragment_shader: ShaderSource,
 }
@@ -26,6 +27,7 @@ impl Default for WindowBuilder {
             height: 400,
             fullscreen: false,
             resizable: false,
+            samples: 0,
             vertex_shader: ShaderSource::String(VERTEX_SHADER),
             fragment_shader: ShaderSource::String(FRAGMENT_SHADER),
         }
@@ -56,6 +58,12 @@ impl WindowBuilder {
         WindowBuilder { resizable, ..self }
     }
 
+    /// Enables multisample anti-aliasing of the window with the given samples per pixel, 0 disables it.
+    /// Use Framebuffer::max_samples() to find out what the driver supports.
+    pub fn samples(self, samples: u8) -> WindowBuilder {
+        WindowBuilder { samples, ..self }
+    }
+
     pub fn vertex_shader(self, vertex_shader: ShaderSource) -> WindowBuilder {
         WindowBuilder {
             vertex_shader,
@@ -74,6 +82,11 @@ impl WindowBuilder {
         self,
         video_subsystem: &VideoSubsystem,
     ) -> Result<Window, Box<dyn std::error::Error>> {
+        // The default framebuffer is created with the window, so this has to happen first
+        let gl_attrib = video_subsystem.gl_attr();
+        gl_attrib.set_multisample_buffers(if self.samples > 0 { 1 } else { 0 });
+        gl_attrib.set_multisample_samples(self.samples);
+
         let mut window = video_subsystem.window(self.title.as_str(), self.width, self.height);
 
         if self.fullscreen {
@@ -92,6 +105,22 @@ impl WindowBuilder {
 
         gl::load_with(|s| video_subsystem.gl_get_proc_address(s) as *const _);
 
+        // The new context starts out with the default state
+        RenderState::invalidate();
+        let render_state = RenderState::default();
+        render_state.apply();
+
+        if self.samples > 0 {
+            unsafe {
+                gl::Enable(gl::MULTISAMPLE);
+            }
+        }
+
+        // The largest value of each index type splits strips and fans, see IndexType::RESTART
+        unsafe {
+            gl::Enable(gl::PRIMITIVE_RESTART_FIXED_INDEX);
+        }
+
         let pipeline_shader =
             PipelineShader::create(Some(self.vertex_shader), Some(self.fragment_shader))?;
         pipeline_shader.enable();
@@ -102,6 +131,7 @@ impl WindowBuilder {
             window,
             _gl_context,
             pipeline_shader,
+            render_state,
         })
     }
 }
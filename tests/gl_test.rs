@@ -5,9 +5,25 @@
 mod tests {
     use rusty_gl::{
         color,
-        shapes::{CustomShape2D, Rect, Shape2D},
-        vertices::Vertex,
-        Color, ComputeShader, ShaderSource, GPU, SSBO,
+        compute_graph::{ComputeGraph, Dispatch, HazardKind},
+        framebuffers::{Attachment, Framebuffer},
+        post_process::{Effect, PostProcess},
+        render_state::{BlendMode, RenderState, StencilState},
+        scene::Scene,
+        shapes::{
+            Batch2D, CustomShape2D, Drawable, Instance, Instanced, Rect, SSBOShape2D, Shape2D,
+        },
+        textures::{
+            Cubemap, CubemapFace, Sampler, Texture, Texture2D, Texture2DArray, Texture3D,
+            TextureUnits,
+        },
+        transform::Placement,
+        vertices::{
+            Attribute, AttributeMismatch, DrawArraysIndirectCommand, DrawElementsIndirectCommand,
+            IndexType, Vertex, VertexLayout, EBO, VAO, VBO,
+        },
+        Color, ComputeShader, DispatchIndirectCommand, PingPongSSBO, PingPongTexture,
+        PipelineShader, ShaderAttribute, ShaderSource, Transform2D, GPU, SSBO,
     };
 
     use vector::{Vector2, Vector3};
@@ -19,6 +35,37 @@ mod tests {
 
     impl GPU for Resolution {}
 
+    /// Reads a pixel of the bound framebuffer, with the origin in the bottom left corner like OpenGL
+    fn pixel(x: i32, y: i32) -> [u8; 4] {
+        let mut pixel = [0u8; 4];
+        unsafe {
+            gl::ReadPixels(
+                x,
+                y,
+                1,
+                1,
+                gl::RGBA,
+                gl::UNSIGNED_BYTE,
+                pixel.as_mut_ptr() as *mut _,
+            );
+        }
+        pixel
+    }
+
+    /// Reads a pixel of the 400x400 test window, with y pointing down like the default shader
+    fn window_pixel(x: i32, y: i32) -> [u8; 4] {
+        pixel(x, 400 - y)
+    }
+
+    fn assert_close(a: Vector2<f32>, b: Vector2<f32>) {
+        assert!(
+            (a.x - b.x).abs() < 1e-3 && (a.y - b.y).abs() < 1e-3,
+            "{:?} != {:?}",
+            (a.x, a.y),
+            (b.x, b.y)
+        );
+    }
+
     #[test]
     fn ssbo_test() -> Result<(), Box<dyn std::error::Error>> {
         let sdl = sdl2::init().unwrap();
@@ -195,7 +242,7 @@ mod tests {
             for angle in (0..360).step_by(10).map(|x| x as f32 * 3.14159265 / 180.0) {
                 vertices.push(Vertex::new(
                     Vector3::new(x + radius * angle.cos(), y + radius * angle.sin(), 0.0),
-                    Color::new(1.0, 1.0, 1.0),
+                    Color::rgb(1.0, 1.0, 1.0),
                     Vector2::new(0.0, 0.0),
                 ));
             }
@@ -212,29 +259,22 @@ mod tests {
         gl_attrib.set_context_profile(sdl2::video::GLProfile::Core);
         gl_attrib.set_context_version(4, 5);
 
-        let window = rusty_gl::Window::new()
+        let mut window = rusty_gl::Window::new()
             .dimensions(400, 400)
             .title("Testing Window")
+            .samples(8)
             .build(&video_subsystem)?;
 
         rusty_gl::debug::enable();
 
-        // gl_attrib.set_multisample_buffers(1);
-        // gl_attrib.set_multisample_samples(8);
-        // unsafe {
-        //     gl::Enable(gl::MULTISAMPLE);
-        // }
-
-        unsafe {
-            gl::PolygonMode(gl::FRONT_AND_BACK, gl::POINT);
-        }
+        window.set_render_state(RenderState::default().polygon_mode(gl::POINT));
 
         let mut vs = generate_circle(100.0, 100.0, 50.0);
         vs.insert(
             0,
             Vertex::new(
                 Vector3::new(100.0, 100.0, 0.0),
-                Color::new(1.0, 1.0, 1.0),
+                Color::rgb(1.0, 1.0, 1.0),
                 Vector2::new(0.0, 0.0),
             ),
         );
@@ -276,21 +316,15 @@ mod tests {
                 match event {
                     sdl2::event::Event::KeyDown { keycode, .. } => {
                         if keycode.unwrap() == sdl2::keyboard::Keycode::Num1 {
-                            unsafe {
-                                gl::PolygonMode(gl::FRONT_AND_BACK, gl::LINE);
-                            }
+                            window.set_render_state(RenderState::default().polygon_mode(gl::LINE));
                         }
 
                         if keycode.unwrap() == sdl2::keyboard::Keycode::Num2 {
-                            unsafe {
-                                gl::PolygonMode(gl::FRONT_AND_BACK, gl::POINT);
-                            }
+                            window.set_render_state(RenderState::default().polygon_mode(gl::POINT));
                         }
 
                         if keycode.unwrap() == sdl2::keyboard::Keycode::Num3 {
-                            unsafe {
-                                gl::PolygonMode(gl::FRONT_AND_BACK, gl::FILL);
-                            }
+                            window.set_render_state(RenderState::default().polygon_mode(gl::FILL));
                         }
                     }
                     sdl2::event::Event::Quit { .. } => break 'main,
@@ -318,4 +352,2839 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn ssbo_shape_test() -> Result<(), Box<dyn std::error::Error>> {
+        #[repr(C)]
+        #[derive(Clone, Copy)]
+        pub struct Particle {
+            position: [f32; 4],
+            color: [f32; 4],
+        }
+
+        let sdl = sdl2::init().unwrap();
+
+        let video_subsystem = sdl.video().unwrap();
+        let gl_attrib = video_subsystem.gl_attr();
+        gl_attrib.set_context_profile(sdl2::video::GLProfile::Core);
+        gl_attrib.set_context_version(4, 5);
+
+        let window = rusty_gl::Window::new()
+            .dimensions(400, 400)
+            .title("Testing Window")
+            .build(&video_subsystem)?;
+
+        rusty_gl::debug::enable();
+
+        pub const COMPUTE_SHADER: &str = "
+            #version 430
+            layout(local_size_x = 1) in;
+
+            struct Particle {
+                vec4 position;
+                vec4 color;
+            };
+
+            layout(std430, binding = 2) buffer Particles {
+                Particle particles[];
+            };
+
+            void main() {
+                uint i = gl_GlobalInvocationID.x;
+                particles[i].position = vec4(20.0 + 40.0 * i, 200.0, 0.0, 0.0);
+                particles[i].color = vec4(1.0, 0.0, 0.0, 1.0);
+            }
+        ";
+
+        let shader = ComputeShader::create(ShaderSource::String(COMPUTE_SHADER))?;
+
+        let particle = Particle {
+            position: [0.0; 4],
+            color: [0.0; 4],
+        };
+        let mut ssbo = std::rc::Rc::new(SSBO::create_from(2, vec![particle; 10], gl::DYNAMIC_DRAW));
+
+        let attributes = [
+            Attribute::new(3, 3, gl::FLOAT, 0),
+            Attribute::new(1, 4, gl::FLOAT, std::mem::size_of::<[f32; 4]>()),
+        ];
+        let mut quads = SSBOShape2D::instanced_quads(
+            std::rc::Rc::clone(&ssbo),
+            Vector2::new(10.0, 10.0),
+            &attributes,
+        );
+        let points = SSBOShape2D::points(
+            std::rc::Rc::clone(&ssbo),
+            &[Attribute::new(0, 3, gl::FLOAT, 0)],
+        );
+
+        assert_eq!(quads.count(), 10);
+        quads.set_count(5);
+        assert_eq!(quads.count(), 5);
+        quads.set_count(20);
+        assert_eq!(quads.count(), 10);
+        quads.clear_count();
+        assert_eq!(quads.count(), 10);
+
+        shader.dispatch(
+            10,
+            1,
+            1,
+            gl::VERTEX_ATTRIB_ARRAY_BARRIER_BIT | gl::SHADER_STORAGE_BARRIER_BIT,
+        );
+
+        window.clear(color::BLACK);
+        window.draw(&quads);
+        window.draw(&points);
+        window.gl_swap();
+
+        // The shapes share the ssbo, it can only be changed once they are gone
+        assert!(std::rc::Rc::get_mut(&mut ssbo).is_none());
+        drop((quads, points));
+
+        let ssbo = std::rc::Rc::get_mut(&mut ssbo).unwrap();
+        ssbo.load();
+        assert_eq!(ssbo[9].position[0], 380.0);
+        assert_eq!(ssbo[9].color, [1.0, 0.0, 0.0, 1.0]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn ping_pong_ssbo_test() -> Result<(), Box<dyn std::error::Error>> {
+        let sdl = sdl2::init().unwrap();
+
+        let video_subsystem = sdl.video().unwrap();
+        let gl_attrib = video_subsystem.gl_attr();
+        gl_attrib.set_context_profile(sdl2::video::GLProfile::Core);
+        gl_attrib.set_context_version(4, 5);
+
+        let _window = rusty_gl::Window::new()
+            .dimensions(400, 400)
+            .title("Testing Window")
+            .build(&video_subsystem)?;
+
+        rusty_gl::debug::enable();
+
+        pub const COMPUTE_SHADER: &str = "
+            #version 430
+            layout(local_size_x = 1) in;
+
+            layout(std430, binding = 0) buffer Read {
+                uint[] content;
+            } read_buffer;
+
+            layout(std430, binding = 1) buffer Write {
+                uint[] content;
+            } write_buffer;
+
+            void main() {
+                uint i = gl_GlobalInvocationID.x;
+                write_buffer.content[i] = read_buffer.content[i] + i;
+            }
+        ";
+
+        let shader = ComputeShader::create(ShaderSource::String(COMPUTE_SHADER))?;
+
+        let mut buffers = PingPongSSBO::create_from(0, 1, vec![0u32; 10], gl::DYNAMIC_DRAW);
+
+        for _ in 0..5 {
+            shader.dispatch(10, 1, 1, gl::SHADER_STORAGE_BARRIER_BIT);
+            buffers.swap();
+        }
+
+        buffers.load();
+        assert_eq!(*buffers, (0..10).map(|i| i * 5).collect::<Vec<u32>>());
+
+        Ok(())
+    }
+
+    #[test]
+    fn dispatch_test() -> Result<(), Box<dyn std::error::Error>> {
+        let sdl = sdl2::init().unwrap();
+
+        let video_subsystem = sdl.video().unwrap();
+        let gl_attrib = video_subsystem.gl_attr();
+        gl_attrib.set_context_profile(sdl2::video::GLProfile::Core);
+        gl_attrib.set_context_version(4, 5);
+
+        let _window = rusty_gl::Window::new()
+            .dimensions(400, 400)
+            .title("Testing Window")
+            .build(&video_subsystem)?;
+
+        rusty_gl::debug::enable();
+
+        pub const COMPUTE_SHADER: &str = "
+            #version 430
+            layout(local_size_x = 64) in;
+
+            uniform uint count;
+
+            layout(std430, binding = 1) buffer Content {
+                uint[] content;
+            } arr;
+
+            void main() {
+                if (gl_GlobalInvocationID.x >= count) return;
+                arr.content[gl_GlobalInvocationID.x] += 1;
+            }
+        ";
+
+        let shader = ComputeShader::create(ShaderSource::String(COMPUTE_SHADER))?;
+        assert_eq!(shader.local_size(), Vector3::new(64, 1, 1));
+
+        let mut ssbo = SSBO::create_from(1, vec![0u32; 100], gl::STATIC_DRAW);
+        shader.set_uniform("count", 100u32);
+
+        shader.dispatch_threads(100, 1, 1, gl::SHADER_STORAGE_BARRIER_BIT)?;
+        ssbo.load();
+        assert_eq!(*ssbo, vec![1u32; 100]);
+
+        let command = DispatchIndirectCommand {
+            num_groups_x: 2,
+            num_groups_y: 1,
+            num_groups_z: 1,
+        };
+        let indirect = SSBO::create_from(2, command, gl::STATIC_DRAW);
+
+        shader.dispatch_indirect(&indirect, 0, gl::SHADER_STORAGE_BARRIER_BIT)?;
+        ssbo.load();
+        assert_eq!(*ssbo, vec![2u32; 100]);
+
+        assert!(shader.dispatch_indirect(&indirect, 2, 0).is_err());
+        assert!(shader.dispatch_indirect(&indirect, 4, 0).is_err());
+
+        let max_count = ComputeShader::max_work_group_count();
+        assert!(shader
+            .dispatch_threads(u32::MAX, max_count.y + 1, 1, 0)
+            .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn compute_graph_test() -> Result<(), Box<dyn std::error::Error>> {
+        let sdl = sdl2::init().unwrap();
+
+        let video_subsystem = sdl.video().unwrap();
+        let gl_attrib = video_subsystem.gl_attr();
+        gl_attrib.set_context_profile(sdl2::video::GLProfile::Core);
+        gl_attrib.set_context_version(4, 5);
+
+        let _window = rusty_gl::Window::new()
+            .dimensions(400, 400)
+            .title("Testing Window")
+            .build(&video_subsystem)?;
+
+        rusty_gl::debug::enable();
+
+        pub const FILL_SHADER: &str = "
+            #version 430
+            layout(local_size_x = 32) in;
+
+            layout(std430, binding = 1) buffer Destination {
+                uint[] content;
+            } dst;
+
+            void main() {
+                uint i = gl_GlobalInvocationID.x;
+                if (i >= dst.content.length()) return;
+                dst.content[i] = i;
+            }
+        ";
+
+        pub const DOUBLE_SHADER: &str = "
+            #version 430
+            layout(local_size_x = 32) in;
+
+            layout(std430, binding = 0) buffer Source {
+                uint[] content;
+            } src;
+
+            layout(std430, binding = 1) buffer Destination {
+                uint[] content;
+            } dst;
+
+            void main() {
+                uint i = gl_GlobalInvocationID.x;
+                if (i >= dst.content.length()) return;
+                dst.content[i] = src.content[i] * 2;
+            }
+        ";
+
+        let fill = ComputeShader::create(ShaderSource::String(FILL_SHADER))?;
+        let double = ComputeShader::create(ShaderSource::String(DOUBLE_SHADER))?;
+
+        let a = SSBO::create_from(0, vec![0u32; 100], gl::DYNAMIC_DRAW);
+        let mut b = SSBO::create_from(0, vec![0u32; 100], gl::DYNAMIC_DRAW);
+        let mut c = SSBO::create_from(0, vec![0u32; 100], gl::DYNAMIC_DRAW);
+
+        let mut graph = ComputeGraph::new();
+        graph.set_debug(true);
+
+        graph
+            .add_pass("fill a", &fill, Dispatch::Threads(100, 1, 1))
+            .write(&a, 1);
+        graph
+            .add_pass("double", &double, Dispatch::Threads(100, 1, 1))
+            .read(&a, 0)
+            .write(&b, 1);
+        graph
+            .add_pass("fill c", &fill, Dispatch::Groups(4, 1, 1))
+            .write(&c, 1);
+
+        graph.output(&b, gl::BUFFER_UPDATE_BARRIER_BIT);
+        graph.output(&c, gl::BUFFER_UPDATE_BARRIER_BIT);
+
+        let hazards = graph.hazards();
+        assert_eq!(hazards.len(), 1);
+        assert_eq!(hazards[0].kind, HazardKind::ReadAfterWrite);
+        assert_eq!(hazards[0].producer, "fill a");
+        assert_eq!(hazards[0].consumer, "double");
+        assert_eq!(hazards[0].barrier, gl::SHADER_STORAGE_BARRIER_BIT);
+
+        graph.run()?;
+
+        b.load();
+        c.load();
+        assert_eq!(*b, (0..100).map(|i| i * 2).collect::<Vec<u32>>());
+        assert_eq!(*c, (0..100).collect::<Vec<u32>>());
+
+        // A pass exceeding the limits fails the graph before anything is dispatched
+        let mut d = SSBO::create_from(0, vec![0u32; 100], gl::DYNAMIC_DRAW);
+        let mut graph = ComputeGraph::new();
+        graph
+            .add_pass("fill d", &fill, Dispatch::Threads(100, 1, 1))
+            .write(&d, 1);
+        graph
+            .add_pass("too large", &double, Dispatch::Groups(u32::MAX, 1, 1))
+            .read(&d, 0)
+            .write(&b, 1);
+        assert!(graph.run().is_err());
+
+        d.load();
+        assert_eq!(*d, vec![0u32; 100]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn compute_graph_readers_test() -> Result<(), Box<dyn std::error::Error>> {
+        let sdl = sdl2::init().unwrap();
+
+        let video_subsystem = sdl.video().unwrap();
+        let gl_attrib = video_subsystem.gl_attr();
+        gl_attrib.set_context_profile(sdl2::video::GLProfile::Core);
+        gl_attrib.set_context_version(4, 5);
+
+        let _window = rusty_gl::Window::new()
+            .dimensions(400, 400)
+            .title("Testing Window")
+            .build(&video_subsystem)?;
+
+        rusty_gl::debug::enable();
+
+        pub const FILL_SHADER: &str = "
+            #version 430
+            layout(local_size_x = 32) in;
+
+            layout(std430, binding = 1) buffer Destination {
+                uint[] content;
+            } dst;
+
+            void main() {
+                uint i = gl_GlobalInvocationID.x;
+                if (i >= dst.content.length()) return;
+                dst.content[i] = i;
+            }
+        ";
+
+        pub const COPY_SHADER: &str = "
+            #version 430
+            layout(local_size_x = 32) in;
+
+            layout(std430, binding = 0) buffer Source {
+                uint[] content;
+            } src;
+
+            layout(std430, binding = 1) buffer Destination {
+                uint[] content;
+            } dst;
+
+            void main() {
+                uint i = gl_GlobalInvocationID.x;
+                if (i >= dst.content.length()) return;
+                dst.content[i] = src.content[i];
+            }
+        ";
+
+        let fill = ComputeShader::create(ShaderSource::String(FILL_SHADER))?;
+        let copy = ComputeShader::create(ShaderSource::String(COPY_SHADER))?;
+
+        let buffer = || SSBO::create_from(0, vec![0u32; 100], gl::DYNAMIC_DRAW);
+        let (a, b, c, d, e) = (buffer(), buffer(), buffer(), buffer(), buffer());
+
+        let mut graph = ComputeGraph::new();
+        graph
+            .add_pass("fill a", &fill, Dispatch::Threads(100, 1, 1))
+            .write(&a, 1);
+        graph
+            .add_pass("a to b", &copy, Dispatch::Threads(100, 1, 1))
+            .read(&a, 0)
+            .write(&b, 1);
+        graph
+            .add_pass("b to c", &copy, Dispatch::Threads(100, 1, 1))
+            .read(&b, 0)
+            .write(&c, 1);
+        // Reaches its level through c, later than the next reader of a
+        graph
+            .add_pass("late reader", &copy, Dispatch::Threads(100, 1, 1))
+            .read(&a, 0)
+            .read(&c, 2)
+            .write(&d, 1);
+        graph
+            .add_pass("early reader", &copy, Dispatch::Threads(100, 1, 1))
+            .read(&a, 0)
+            .write(&e, 1);
+        graph
+            .add_pass("overwrite a", &fill, Dispatch::Threads(100, 1, 1))
+            .write(&a, 1);
+
+        let hazards = graph.hazards();
+        let war: Vec<&str> = hazards
+            .iter()
+            .filter(|hazard| hazard.kind == HazardKind::WriteAfterRead)
+            .map(|hazard| hazard.producer.as_str())
+            .collect();
+        assert_eq!(war, ["early reader", "late reader", "a to b"]);
+        assert!(hazards
+            .iter()
+            .any(|hazard| hazard.kind == HazardKind::WriteAfterWrite
+                && hazard.producer == "fill a"
+                && hazard.consumer == "overwrite a"));
+
+        let levels = graph.levels();
+        let level = |name: &str| {
+            levels
+                .iter()
+                .position(|level| level.contains(&name))
+                .unwrap()
+        };
+        assert!(level("early reader") < level("late reader"));
+        assert!(level("overwrite a") > level("late reader"));
+        assert!(level("overwrite a") > level("early reader"));
+
+        graph.run()?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn image_test() -> Result<(), Box<dyn std::error::Error>> {
+        let sdl = sdl2::init().unwrap();
+
+        let video_subsystem = sdl.video().unwrap();
+        let gl_attrib = video_subsystem.gl_attr();
+        gl_attrib.set_context_profile(sdl2::video::GLProfile::Core);
+        gl_attrib.set_context_version(4, 5);
+
+        let _window = rusty_gl::Window::new()
+            .dimensions(400, 400)
+            .title("Testing Window")
+            .build(&video_subsystem)?;
+
+        rusty_gl::debug::enable();
+
+        pub const FILL_SHADER: &str = "
+            #version 430
+            layout(local_size_x = 8, local_size_y = 8) in;
+
+            layout(r32f) uniform writeonly image2D target;
+
+            void main() {
+                ivec2 p = ivec2(gl_GlobalInvocationID.xy);
+                imageStore(target, p, vec4(p.x + p.y * 16, 0.0, 0.0, 0.0));
+            }
+        ";
+
+        pub const STEP_SHADER: &str = "
+            #version 430
+            layout(local_size_x = 8, local_size_y = 8) in;
+
+            layout(r32f, binding = 0) uniform readonly image2D current;
+            layout(r32f, binding = 1) uniform writeonly image2D next;
+
+            void main() {
+                ivec2 p = ivec2(gl_GlobalInvocationID.xy);
+                imageStore(next, p, imageLoad(current, p) + vec4(1.0));
+            }
+        ";
+
+        pub const READ_SHADER: &str = "
+            #version 430
+            layout(local_size_x = 8, local_size_y = 8) in;
+
+            layout(std430, binding = 0) buffer Result {
+                float[] content;
+            } result;
+
+            uniform sampler2D source;
+
+            void main() {
+                ivec2 p = ivec2(gl_GlobalInvocationID.xy);
+                result.content[p.x + p.y * 16] = texelFetch(source, p, 0).r;
+            }
+        ";
+
+        let fill = ComputeShader::create(ShaderSource::String(FILL_SHADER))?;
+        let step = ComputeShader::create(ShaderSource::String(STEP_SHADER))?;
+        let read = ComputeShader::create(ShaderSource::String(READ_SHADER))?;
+
+        let texture = Texture2D::new(16, 16, gl::R32F);
+        fill.bind_image("target", 0, &texture, gl::WRITE_ONLY, gl::R32F);
+        fill.dispatch_threads(16, 16, 1, gl::TEXTURE_FETCH_BARRIER_BIT)?;
+
+        let mut result = SSBO::create_from(0, vec![0f32; 256], gl::DYNAMIC_DRAW);
+        read.bind_texture("source", 0, &texture);
+        read.dispatch_threads(16, 16, 1, gl::BUFFER_UPDATE_BARRIER_BIT)?;
+
+        result.load();
+        assert_eq!(*result, (0..256).map(|i| i as f32).collect::<Vec<f32>>());
+
+        let mut field = PingPongTexture::new(16, 16, gl::R32F, 0, 1);
+        fill.bind_image("target", 2, field.current(), gl::WRITE_ONLY, gl::R32F);
+        fill.dispatch_threads(16, 16, 1, gl::SHADER_IMAGE_ACCESS_BARRIER_BIT)?;
+
+        for _ in 0..5 {
+            step.dispatch_threads(16, 16, 1, gl::SHADER_IMAGE_ACCESS_BARRIER_BIT)?;
+            field.swap();
+        }
+
+        read.bind_texture("source", 0, field.current());
+        read.dispatch_threads(16, 16, 1, gl::BUFFER_UPDATE_BARRIER_BIT)?;
+
+        result.load();
+        assert_eq!(
+            *result,
+            (0..256).map(|i| i as f32 + 5.0).collect::<Vec<f32>>()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn texture_test() -> Result<(), Box<dyn std::error::Error>> {
+        let sdl = sdl2::init().unwrap();
+
+        let video_subsystem = sdl.video().unwrap();
+        let gl_attrib = video_subsystem.gl_attr();
+        gl_attrib.set_context_profile(sdl2::video::GLProfile::Core);
+        gl_attrib.set_context_version(4, 5);
+
+        let _window = rusty_gl::Window::new()
+            .dimensions(400, 400)
+            .title("Testing Window")
+            .build(&video_subsystem)?;
+
+        rusty_gl::debug::enable();
+
+        let pixels: Vec<u8> = vec![
+            255, 0, 0, 255, 0, 255, 0, 255, //
+            0, 0, 255, 255, 255, 255, 255, 255,
+        ];
+        let texture = Texture2D::from_data(2, 2, gl::RGBA8, gl::RGBA, &pixels)?;
+        assert_eq!(texture.levels(), 2);
+        assert_eq!(texture.read::<u8>(0, gl::RGBA)?, pixels);
+
+        // The single pixel of the last level is the average of all four
+        let average = texture.read::<f32>(1, gl::RGBA)?;
+        for (value, expected) in average.iter().zip(&[0.5, 0.5, 0.5, 1.0]) {
+            assert!((value - expected).abs() < 0.01);
+        }
+
+        texture.update(1, 1, 1, 1, gl::RGBA, &[1u8, 2, 3, 4])?;
+        assert_eq!(texture.read::<u8>(0, gl::RGBA)?[12..], [1, 2, 3, 4]);
+
+        assert!(texture.update(1, 1, 2, 1, gl::RGBA, &[0u8; 8]).is_err());
+        assert!(texture
+            .update(u32::MAX, 0, 1, 1, gl::RGBA, &[0u8; 4])
+            .is_err());
+        assert!(texture.update(0, 0, 1, 1, gl::RGBA, &[0u8; 3]).is_err());
+        assert!(texture.read::<u8>(2, gl::RGBA).is_err());
+
+        // Single channel rows of 3 bytes aren't 4 byte aligned
+        let gray: Vec<u8> = (0..9).collect();
+        let texture = Texture2D::from_data(3, 3, gl::R8, gl::RED, &gray)?;
+        assert_eq!(texture.read::<u8>(0, gl::RED)?, gray);
+
+        let floats = vec![0.25f32, -1.0, 1e6, 3.5];
+        let texture = Texture2D::from_data(2, 2, gl::R32F, gl::RED, &floats)?;
+        texture.set_filter(gl::NEAREST, gl::NEAREST);
+        texture.set_wrap(gl::REPEAT, gl::CLAMP_TO_EDGE);
+        assert_eq!(texture.read::<f32>(0, gl::RED)?, floats);
+
+        // Integer formats can't be mipmapped, they only get the base level
+        let ids = vec![1u32, 2, 3, u32::MAX];
+        let texture = Texture2D::from_data(2, 2, gl::R32UI, gl::RED_INTEGER, &ids)?;
+        assert_eq!(texture.levels(), 1);
+        assert_eq!(texture.read::<u32>(0, gl::RED_INTEGER)?, ids);
+
+        // A 2x2 PPM, its top row is red and green, rows are flipped when decoded
+        let mut ppm = b"P6 2 2 255\n".to_vec();
+        ppm.extend_from_slice(&[255, 0, 0, 0, 255, 0, 0, 0, 255, 255, 255, 255]);
+        let texture = Texture2D::from_bytes(&ppm, gl::RGBA8)?;
+        assert_eq!(
+            texture.read::<u8>(0, gl::RGBA)?,
+            vec![
+                0, 0, 255, 255, 255, 255, 255, 255, //
+                255, 0, 0, 255, 0, 255, 0, 255,
+            ]
+        );
+
+        assert!(Texture2D::from_bytes(b"not an image", gl::RGBA8).is_err());
+        assert!(Texture2D::from_file("does/not/exist.png", gl::RGBA8).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn textured_shape_test() -> Result<(), Box<dyn std::error::Error>> {
+        let sdl = sdl2::init().unwrap();
+
+        let video_subsystem = sdl.video().unwrap();
+        let gl_attrib = video_subsystem.gl_attr();
+        gl_attrib.set_context_profile(sdl2::video::GLProfile::Core);
+        gl_attrib.set_context_version(4, 5);
+
+        let window = rusty_gl::Window::new()
+            .dimensions(400, 400)
+            .title("Testing Window")
+            .build(&video_subsystem)?;
+
+        rusty_gl::debug::enable();
+
+        // Bottom row red and green, top row blue and white
+        let pixels: Vec<u8> = vec![
+            255, 0, 0, 255, 0, 255, 0, 255, //
+            0, 0, 255, 255, 255, 255, 255, 255,
+        ];
+        let texture = std::rc::Rc::new(Texture2D::from_data(2, 2, gl::RGBA8, gl::RGBA, &pixels)?);
+        texture.set_filter(gl::NEAREST, gl::NEAREST);
+
+        // Covers the whole window
+        let quad = || {
+            vec![
+                Vertex::new(
+                    Vector3::new(0.0, 0.0, 0.0),
+                    color::GREEN,
+                    Vector2::new(0.0, 1.0),
+                ),
+                Vertex::new(
+                    Vector3::new(400.0, 0.0, 0.0),
+                    color::GREEN,
+                    Vector2::new(1.0, 1.0),
+                ),
+                Vertex::new(
+                    Vector3::new(0.0, 400.0, 0.0),
+                    color::GREEN,
+                    Vector2::new(0.0, 0.0),
+                ),
+                Vertex::new(
+                    Vector3::new(400.0, 400.0, 0.0),
+                    color::GREEN,
+                    Vector2::new(1.0, 0.0),
+                ),
+            ]
+        };
+        let mut rect = Rect::new(quad());
+        rect.set_texture(Some(texture.clone()));
+
+        window.clear(color::BLACK);
+        window.draw(&rect);
+
+        // Read pixels start at the bottom left as well
+        assert_eq!(pixel(100, 100), [255, 0, 0, 255]);
+        assert_eq!(pixel(300, 100), [0, 255, 0, 255]);
+        assert_eq!(pixel(100, 300), [0, 0, 255, 255]);
+        assert_eq!(pixel(300, 300), [255, 255, 255, 255]);
+
+        rect.set_tint(color::YELLOW);
+        window.draw(&rect);
+        assert_eq!(pixel(300, 300), [255, 255, 0, 255]);
+
+        // Without texture the vertex colors are used again
+        rect.set_texture(None).set_tint(color::WHITE);
+        window.draw(&rect);
+        assert_eq!(pixel(300, 300), [0, 255, 0, 255]);
+
+        let mut strip = CustomShape2D::new(quad(), gl::TRIANGLE_STRIP);
+        strip.set_texture(Some(texture)).set_tint(color::CYAN);
+        window.draw(&strip);
+        assert_eq!(pixel(300, 300), [0, 255, 255, 255]);
+        assert_eq!(pixel(100, 100), [0, 0, 0, 255]);
+
+        window.gl_swap();
+
+        Ok(())
+    }
+
+    #[test]
+    fn sampler_test() -> Result<(), Box<dyn std::error::Error>> {
+        let sdl = sdl2::init().unwrap();
+
+        let video_subsystem = sdl.video().unwrap();
+        let gl_attrib = video_subsystem.gl_attr();
+        gl_attrib.set_context_profile(sdl2::video::GLProfile::Core);
+        gl_attrib.set_context_version(4, 5);
+
+        let window = rusty_gl::Window::new()
+            .dimensions(400, 400)
+            .title("Testing Window")
+            .build(&video_subsystem)?;
+
+        rusty_gl::debug::enable();
+
+        pub const VERTEX_SHADER: &str = "
+            #version 430
+            layout (location = 0) in vec3 vPos;
+            layout (location = 2) in vec2 vTexCoord;
+            out vec2 uv;
+
+            void main() {
+                gl_Position = vec4(vPos, 1.0);
+                uv = vTexCoord;
+            }
+        ";
+
+        pub const FRAGMENT_SHADER: &str = "
+            #version 430
+            in vec2 uv;
+            out vec4 FragColor;
+
+            uniform sampler2D a;
+            uniform sampler2D b;
+
+            void main() {
+                FragColor = vec4(texture(a, uv).r, texture(b, uv).r, 0.0, 1.0);
+            }
+        ";
+
+        let shader = PipelineShader::create(
+            Some(ShaderSource::String(VERTEX_SHADER)),
+            Some(ShaderSource::String(FRAGMENT_SHADER)),
+        )?;
+
+        // Covers the whole viewport, vertices are already in clip space
+        let quad = Rect::new(vec![
+            Vertex::new(
+                Vector3::new(-1.0, 1.0, 0.0),
+                color::WHITE,
+                Vector2::new(0.0, 1.0),
+            ),
+            Vertex::new(
+                Vector3::new(1.0, 1.0, 0.0),
+                color::WHITE,
+                Vector2::new(1.0, 1.0),
+            ),
+            Vertex::new(
+                Vector3::new(-1.0, -1.0, 0.0),
+                color::WHITE,
+                Vector2::new(0.0, 0.0),
+            ),
+            Vertex::new(
+                Vector3::new(1.0, -1.0, 0.0),
+                color::WHITE,
+                Vector2::new(1.0, 0.0),
+            ),
+        ]);
+
+        // A black and a white texel next to each other
+        let a = Texture2D::from_data(2, 1, gl::R8, gl::RED, &[0u8, 255])?;
+        let b = Texture2D::from_data(2, 1, gl::R8, gl::RED, &[0u8, 255])?;
+        a.set_filter(gl::LINEAR, gl::LINEAR);
+        b.set_filter(gl::NEAREST, gl::NEAREST);
+
+        let nearest = Sampler::new()
+            .filter(gl::NEAREST, gl::NEAREST)
+            .wrap(gl::CLAMP_TO_EDGE, gl::CLAMP_TO_EDGE, gl::CLAMP_TO_EDGE)
+            .lod(0.0, 0.0, 0.0);
+        let linear = Sampler::new()
+            .filter(gl::LINEAR, gl::LINEAR)
+            .anisotropy(4.0)
+            .compare(None);
+
+        // Samples at u = 0.45, linear filtering blends 40% of the white texel in
+        let sample = || {
+            window.clear(color::BLACK);
+            quad.draw(&shader);
+
+            let mut pixel = [0u8; 4];
+            unsafe {
+                gl::ReadPixels(
+                    180,
+                    200,
+                    1,
+                    1,
+                    gl::RGBA,
+                    gl::UNSIGNED_BYTE,
+                    pixel.as_mut_ptr() as *mut _,
+                );
+            }
+            (pixel[0] as i32, pixel[1] as i32)
+        };
+
+        // The samplers override the parameters of the textures
+        let mut units = TextureUnits::new();
+        units
+            .add("a", &a, Some(&nearest))
+            .add("b", &b, Some(&linear));
+        units.bind(&shader)?;
+        assert_eq!(units.unit("a"), Some(1));
+        assert_eq!(units.unit("b"), Some(2));
+        assert_eq!(units.unit("c"), None);
+
+        let (red, green) = sample();
+        assert_eq!(red, 0);
+        assert!((green - 102).abs() <= 3);
+
+        // Without samplers the parameters of the textures are used again
+        TextureUnits::starting_at(4)
+            .add("a", &a, None)
+            .add("b", &b, None)
+            .bind(&shader)?;
+
+        let (red, green) = sample();
+        assert!((red - 102).abs() <= 3);
+        assert_eq!(green, 0);
+
+        let mut max_units = 0;
+        unsafe {
+            gl::GetIntegerv(gl::MAX_COMBINED_TEXTURE_IMAGE_UNITS, &mut max_units);
+        }
+        assert!(TextureUnits::starting_at(max_units as u32)
+            .add("a", &a, None)
+            .bind(&shader)
+            .is_err());
+
+        assert!(Sampler::max_anisotropy() >= 1.0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn texture_types_test() -> Result<(), Box<dyn std::error::Error>> {
+        let sdl = sdl2::init().unwrap();
+
+        let video_subsystem = sdl.video().unwrap();
+        let gl_attrib = video_subsystem.gl_attr();
+        gl_attrib.set_context_profile(sdl2::video::GLProfile::Core);
+        gl_attrib.set_context_version(4, 5);
+
+        let _window = rusty_gl::Window::new()
+            .dimensions(400, 400)
+            .title("Testing Window")
+            .build(&video_subsystem)?;
+
+        rusty_gl::debug::enable();
+
+        pub const VOLUME_SHADER: &str = "
+            #version 430
+            layout(local_size_x = 4, local_size_y = 4, local_size_z = 4) in;
+
+            layout(r32f) uniform image3D volume;
+
+            void main() {
+                ivec3 p = ivec3(gl_GlobalInvocationID);
+                imageStore(volume, p, imageLoad(volume, p) * 2.0);
+            }
+        ";
+
+        pub const LAYER_SHADER: &str = "
+            #version 430
+            layout(local_size_x = 4, local_size_y = 4) in;
+
+            layout(r32f) uniform writeonly image2DArray layers;
+
+            void main() {
+                ivec3 p = ivec3(gl_GlobalInvocationID);
+                imageStore(layers, p, vec4(p.z));
+            }
+        ";
+
+        // Voxel grid, doubled by a compute shader
+        let grid: Vec<f32> = (0..8 * 8 * 4).map(|i| i as f32).collect();
+        let volume = Texture3D::from_data(8, 8, 4, gl::R32F, gl::RED, &grid)?;
+        assert_eq!(volume.levels(), 4);
+        assert_eq!(volume.read::<f32>(0, gl::RED)?, grid);
+        assert_eq!(volume.read::<f32>(3, gl::RED)?.len(), 1);
+
+        let shader = ComputeShader::create(ShaderSource::String(VOLUME_SHADER))?;
+        shader.bind_image("volume", 0, &volume, gl::READ_WRITE, gl::R32F);
+        shader.dispatch_threads(8, 8, 4, gl::TEXTURE_UPDATE_BARRIER_BIT)?;
+        assert_eq!(
+            volume.read::<f32>(0, gl::RED)?,
+            grid.iter().map(|v| v * 2.0).collect::<Vec<f32>>()
+        );
+
+        volume.update([1, 2, 3], [1, 1, 1], gl::RED, &[-1.0f32])?;
+        assert_eq!(volume.read::<f32>(0, gl::RED)?[3 * 64 + 2 * 8 + 1], -1.0);
+        assert!(volume
+            .update([7, 7, 3], [2, 1, 1], gl::RED, &[0.0f32; 2])
+            .is_err());
+
+        // Every layer is filled with its index by a compute shader
+        let layers = Texture2DArray::new(4, 4, 3, gl::R32F);
+        let shader = ComputeShader::create(ShaderSource::String(LAYER_SHADER))?;
+        shader.bind_image("layers", 0, &layers, gl::WRITE_ONLY, gl::R32F);
+        shader.dispatch_threads(4, 4, 3, gl::TEXTURE_UPDATE_BARRIER_BIT)?;
+        for layer in 0..3 {
+            assert_eq!(
+                layers.read::<f32>(layer, 0, gl::RED)?,
+                vec![layer as f32; 16]
+            );
+        }
+        assert!(layers.read::<f32>(3, 0, gl::RED).is_err());
+
+        let data: Vec<u8> = (0..2 * 2 * 3 * 4).collect();
+        let layers = Texture2DArray::from_data(2, 2, 3, gl::RGBA8, gl::RGBA, &data)?;
+        assert_eq!(layers.read::<u8>(1, 0, gl::RGBA)?, data[16..32]);
+        layers.update([0, 0, 2], [2, 2, 1], gl::RGBA, &[7u8; 16])?;
+        assert_eq!(layers.read::<u8>(2, 0, gl::RGBA)?, vec![7; 16]);
+
+        // Integer formats only get the base level
+        let ids: Vec<u32> = (0..2 * 2 * 3).collect();
+        let layers = Texture2DArray::from_data(2, 2, 3, gl::R32UI, gl::RED_INTEGER, &ids)?;
+        assert_eq!(layers.levels(), 1);
+        assert_eq!(layers.read::<u32>(2, 0, gl::RED_INTEGER)?, ids[8..]);
+        let volume = Texture3D::from_data(2, 2, 2, gl::R32UI, gl::RED_INTEGER, &ids[..8])?;
+        assert_eq!(volume.levels(), 1);
+
+        // Every face has its own color
+        let faces: Vec<Vec<u8>> = (0..6).map(|f| vec![f * 40; 4 * 4 * 4]).collect();
+        let cubemap = Cubemap::from_data(
+            4,
+            gl::RGBA8,
+            gl::RGBA,
+            [
+                &faces[0], &faces[1], &faces[2], &faces[3], &faces[4], &faces[5],
+            ],
+        )?;
+        for face in CubemapFace::ALL.iter() {
+            assert_eq!(
+                cubemap.read::<u8>(*face, 0, gl::RGBA)?,
+                faces[face.index() as usize]
+            );
+        }
+        assert_eq!(
+            cubemap.read::<u8>(CubemapFace::NegativeZ, 2, gl::RGBA)?,
+            vec![200; 4]
+        );
+
+        cubemap.update(
+            CubemapFace::PositiveY,
+            [3, 3],
+            [1, 1],
+            gl::RGBA,
+            &[1u8, 2, 3, 4],
+        )?;
+        assert_eq!(
+            cubemap.read::<u8>(CubemapFace::PositiveY, 0, gl::RGBA)?[60..],
+            [1, 2, 3, 4]
+        );
+
+        let ppm = |value: u8| {
+            let mut bytes = b"P6 1 1 255\n".to_vec();
+            bytes.extend_from_slice(&[value, value, value]);
+            bytes
+        };
+        let images: Vec<Vec<u8>> = (0..6).map(|f| ppm(f * 10)).collect();
+        let cubemap = Cubemap::from_bytes(
+            [
+                &images[0], &images[1], &images[2], &images[3], &images[4], &images[5],
+            ],
+            gl::RGBA8,
+        )?;
+        assert_eq!(
+            cubemap.read::<u8>(CubemapFace::NegativeX, 0, gl::RGBA)?,
+            vec![10, 10, 10, 255]
+        );
+
+        let wide = b"P6 2 1 255\n\0\0\0\0\0\0".to_vec();
+        assert!(Cubemap::from_bytes(
+            [&images[0], &images[1], &images[2], &images[3], &images[4], &wide],
+            gl::RGBA8,
+        )
+        .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn framebuffer_test() -> Result<(), Box<dyn std::error::Error>> {
+        let sdl = sdl2::init().unwrap();
+
+        let video_subsystem = sdl.video().unwrap();
+        let gl_attrib = video_subsystem.gl_attr();
+        gl_attrib.set_context_profile(sdl2::video::GLProfile::Core);
+        gl_attrib.set_context_version(4, 5);
+
+        let window = rusty_gl::Window::new()
+            .dimensions(400, 400)
+            .title("Testing Window")
+            .build(&video_subsystem)?;
+
+        rusty_gl::debug::enable();
+
+        pub const VERTEX_SHADER: &str = "
+            #version 430
+            layout (location = 0) in vec3 vPos;
+
+            void main() {
+                gl_Position = vec4(vPos, 1.0);
+            }
+        ";
+
+        pub const FRAGMENT_SHADER: &str = "
+            #version 430
+            layout (location = 0) out vec4 first;
+            layout (location = 1) out vec4 second;
+
+            void main() {
+                first = vec4(1.0, 0.0, 0.0, 1.0);
+                second = vec4(0.0, 0.0, 1.0, 1.0);
+            }
+        ";
+
+        let quad = |scale: f32| {
+            vec![
+                Vertex::new(
+                    Vector3::new(0.0, 0.0, 0.0),
+                    color::GREEN,
+                    Vector2::default(),
+                ),
+                Vertex::new(
+                    Vector3::new(scale, 0.0, 0.0),
+                    color::GREEN,
+                    Vector2::default(),
+                ),
+                Vertex::new(
+                    Vector3::new(0.0, scale, 0.0),
+                    color::GREEN,
+                    Vector2::default(),
+                ),
+                Vertex::new(
+                    Vector3::new(scale, scale, 0.0),
+                    color::GREEN,
+                    Vector2::default(),
+                ),
+            ]
+        };
+
+        let target = std::rc::Rc::new(Texture2D::new(100, 50, gl::RGBA8));
+        let framebuffer = Framebuffer::builder(100, 50)
+            .color_texture(target.clone())
+            .depth_stencil(gl::DEPTH24_STENCIL8)
+            .build()?;
+        assert!(framebuffer.color_texture(0).is_some());
+        assert!(matches!(
+            framebuffer.depth_stencil(),
+            Some(Attachment::Renderbuffer(_))
+        ));
+
+        window.clear(color::BLACK);
+
+        // Shapes keep using window coordinates, so the quad covers the target
+        {
+            let _target = framebuffer.bind();
+            window.clear(color::BLUE);
+            window.draw(&Rect::new(quad(400.0)));
+
+            let mut viewport = [0; 4];
+            unsafe {
+                gl::GetIntegerv(gl::VIEWPORT, viewport.as_mut_ptr());
+            }
+            assert_eq!(viewport, [0, 0, 100, 50]);
+        }
+
+        let mut viewport = [0; 4];
+        unsafe {
+            gl::GetIntegerv(gl::VIEWPORT, viewport.as_mut_ptr());
+        }
+        assert_eq!(viewport, [0, 0, 400, 400]);
+        assert_eq!(pixel(200, 200), [0, 0, 0, 255]);
+        assert!(target
+            .read::<u8>(0, gl::RGBA)?
+            .chunks(4)
+            .all(|p| p == [0, 255, 0, 255]));
+
+        framebuffer.clear(color::RED);
+        assert!(target
+            .read::<u8>(0, gl::RGBA)?
+            .chunks(4)
+            .all(|p| p == [255, 0, 0, 255]));
+
+        // Multiple render targets, one texture and one renderbuffer
+        let shader = PipelineShader::create(
+            Some(ShaderSource::String(VERTEX_SHADER)),
+            Some(ShaderSource::String(FRAGMENT_SHADER)),
+        )?;
+        let first = std::rc::Rc::new(Texture2D::new(8, 8, gl::RGBA8));
+        let second = std::rc::Rc::new(Texture2D::new(8, 8, gl::RGBA8));
+        let mrt = Framebuffer::builder(8, 8)
+            .color_texture(first.clone())
+            .color_texture(second.clone())
+            .color_renderbuffer(gl::RGBA8)
+            .build()?;
+        assert!(mrt.color_texture(1).is_some());
+        assert!(mrt.color_texture(2).is_none());
+
+        {
+            let _target = mrt.bind();
+            let fullscreen = Rect::new(vec![
+                Vertex::new(
+                    Vector3::new(-1.0, 1.0, 0.0),
+                    color::WHITE,
+                    Vector2::default(),
+                ),
+                Vertex::new(
+                    Vector3::new(1.0, 1.0, 0.0),
+                    color::WHITE,
+                    Vector2::default(),
+                ),
+                Vertex::new(
+                    Vector3::new(-1.0, -1.0, 0.0),
+                    color::WHITE,
+                    Vector2::default(),
+                ),
+                Vertex::new(
+                    Vector3::new(1.0, -1.0, 0.0),
+                    color::WHITE,
+                    Vector2::default(),
+                ),
+            ]);
+            fullscreen.draw(&shader);
+        }
+        window.pipeline_shader().enable();
+
+        assert_eq!(first.read::<u8>(0, gl::RGBA)?, [255, 0, 0, 255].repeat(64));
+        assert_eq!(second.read::<u8>(0, gl::RGBA)?, [0, 0, 255, 255].repeat(64));
+
+        // Readable errors instead of a silently broken target
+        let small = std::rc::Rc::new(Texture2D::new(4, 4, gl::RGBA8));
+        let error = Framebuffer::builder(8, 8)
+            .color_texture(small)
+            .build()
+            .err()
+            .unwrap();
+        assert_eq!(
+            error.to_string(),
+            "Color attachment 0 is 4x4, but the framebuffer is 8x8"
+        );
+
+        let unrenderable = std::rc::Rc::new(Texture2D::new(8, 8, gl::RGB9_E5));
+        assert!(Framebuffer::builder(8, 8)
+            .color_texture(unrenderable)
+            .build()
+            .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn post_process_test() -> Result<(), Box<dyn std::error::Error>> {
+        let sdl = sdl2::init().unwrap();
+
+        let video_subsystem = sdl.video().unwrap();
+        let gl_attrib = video_subsystem.gl_attr();
+        gl_attrib.set_context_profile(sdl2::video::GLProfile::Core);
+        gl_attrib.set_context_version(4, 5);
+
+        let window = rusty_gl::Window::new()
+            .dimensions(400, 400)
+            .title("Testing Window")
+            .build(&video_subsystem)?;
+
+        rusty_gl::debug::enable();
+
+        pub const INVERT_SHADER: &str = "
+            #version 430
+            in vec2 uv;
+            out vec4 FragColor;
+
+            uniform sampler2D source;
+            uniform vec2 resolution;
+            uniform float time;
+
+            void main() {
+                FragColor = vec4(1.0 - texture(source, uv).rgb, 1.0);
+
+                // Gray if the automatic uniforms are missing
+                if (resolution != vec2(400.0) || time <= 0.0) {
+                    FragColor = vec4(0.5);
+                }
+            }
+        ";
+
+        // Left half red, right half black
+        let left_half = || {
+            Rect::new(vec![
+                Vertex::new(Vector3::new(0.0, 0.0, 0.0), color::RED, Vector2::default()),
+                Vertex::new(
+                    Vector3::new(200.0, 0.0, 0.0),
+                    color::RED,
+                    Vector2::default(),
+                ),
+                Vertex::new(
+                    Vector3::new(0.0, 400.0, 0.0),
+                    color::RED,
+                    Vector2::default(),
+                ),
+                Vertex::new(
+                    Vector3::new(200.0, 400.0, 0.0),
+                    color::RED,
+                    Vector2::default(),
+                ),
+            ])
+        };
+
+        let render = |post: &PostProcess| -> Result<(), Box<dyn std::error::Error>> {
+            {
+                let _scene = post.begin();
+                window.clear(color::BLACK);
+                window.draw(&left_half());
+            }
+
+            window.clear(color::WHITE);
+            post.present(&window)
+        };
+
+        // Without effects the scene is copied into the window
+        let post = PostProcess::new(&window)?;
+        render(&post)?;
+        assert_eq!(pixel(100, 200), [255, 0, 0, 255]);
+        assert_eq!(pixel(300, 200), [0, 0, 0, 255]);
+
+        let mut post = PostProcess::new(&window)?;
+        post.add(Effect::Custom(ShaderSource::String(INVERT_SHADER)))?;
+        render(&post)?;
+        assert_eq!(pixel(100, 200), [0, 255, 255, 255]);
+        assert_eq!(pixel(300, 200), [255, 255, 255, 255]);
+
+        // Only pixels close to the edge are blurred
+        let mut post = PostProcess::new(&window)?;
+        post.add(Effect::GaussianBlur { radius: 6.0 })?;
+        render(&post)?;
+        assert_eq!(pixel(100, 200), [255, 0, 0, 255]);
+        assert_eq!(pixel(300, 200), [0, 0, 0, 255]);
+        assert!((pixel(199, 200)[0] as i32 - 128).abs() < 40);
+        assert!((pixel(200, 200)[0] as i32 - 128).abs() < 40);
+
+        // An inverting lookup table with 2 entries per channel
+        let mut lut = Vec::new();
+        for b in 0..2u8 {
+            for g in 0..2u8 {
+                for r in 0..2u8 {
+                    lut.extend_from_slice(&[255 - r * 255, 255 - g * 255, 255 - b * 255, 255]);
+                }
+            }
+        }
+        let lut = std::rc::Rc::new(Texture3D::from_data(2, 2, 2, gl::RGBA8, gl::RGBA, &lut)?);
+
+        let mut post = PostProcess::new(&window)?;
+        post.add(Effect::ColorGrading { lut: lut.clone() })?;
+        render(&post)?;
+        assert_eq!(pixel(100, 200), [0, 255, 255, 255]);
+        assert_eq!(pixel(300, 200), [255, 255, 255, 255]);
+
+        // The lookup table is clamped by a sampler of the effect, its own parameters stay untouched
+        let mut wrap = 0;
+        unsafe {
+            gl::GetTextureParameteriv(lut.id(), gl::TEXTURE_WRAP_S, &mut wrap);
+        }
+        assert_eq!(wrap, gl::REPEAT as i32);
+
+        // Red is too dark to bloom and uniform areas have no edges to smooth
+        let mut post = PostProcess::new(&window)?;
+        post.add(Effect::Fxaa)?;
+        post.add(Effect::Bloom {
+            threshold: 0.5,
+            intensity: 1.0,
+            radius: 4.0,
+        })?;
+        let vignette = post.add(Effect::Vignette {
+            radius: 0.5,
+            strength: 1.0,
+        })?;
+        render(&post)?;
+        assert_eq!(pixel(190, 200), [255, 0, 0, 255]);
+        assert_eq!(pixel(2, 2), [0, 0, 0, 255]);
+
+        // The corners are darkened by the vignette unless its strength is set to zero
+        let mut corner = PostProcess::new(&window)?;
+        let shade = corner.add(Effect::Vignette {
+            radius: 0.5,
+            strength: 1.0,
+        })?;
+        {
+            let _scene = corner.begin();
+            window.clear(color::WHITE);
+        }
+        corner.present(&window)?;
+        assert_eq!(pixel(200, 200), [255, 255, 255, 255]);
+        assert_eq!(pixel(2, 2), [0, 0, 0, 255]);
+
+        corner.set_uniform(shade, "strength", 0.0f32);
+        corner.present(&window)?;
+        assert_eq!(pixel(2, 2), [255, 255, 255, 255]);
+
+        post.set_uniform(vignette, "strength", 0.0f32);
+        assert!(post
+            .add(Effect::Custom(ShaderSource::String("not glsl")))
+            .is_err());
+
+        window.gl_swap();
+
+        Ok(())
+    }
+
+    #[test]
+    fn msaa_test() -> Result<(), Box<dyn std::error::Error>> {
+        let sdl = sdl2::init().unwrap();
+
+        let video_subsystem = sdl.video().unwrap();
+        let gl_attrib = video_subsystem.gl_attr();
+        gl_attrib.set_context_profile(sdl2::video::GLProfile::Core);
+        gl_attrib.set_context_version(4, 5);
+
+        let window = rusty_gl::Window::new()
+            .dimensions(400, 400)
+            .title("Testing Window")
+            .build(&video_subsystem)?;
+
+        rusty_gl::debug::enable();
+
+        let max_samples = Framebuffer::max_samples();
+        assert!(max_samples >= 4);
+
+        // A triangle whose hypotenuse crosses pixels diagonally
+        let triangle = CustomShape2D::new(
+            vec![
+                Vertex::new(
+                    Vector3::new(0.0, 0.0, 0.0),
+                    color::WHITE,
+                    Vector2::default(),
+                ),
+                Vertex::new(
+                    Vector3::new(400.0, 0.0, 0.0),
+                    color::WHITE,
+                    Vector2::default(),
+                ),
+                Vertex::new(
+                    Vector3::new(0.0, 400.0, 0.0),
+                    color::WHITE,
+                    Vector2::default(),
+                ),
+            ],
+            gl::TRIANGLES,
+        );
+
+        let msaa = Framebuffer::builder(400, 400)
+            .samples(4)
+            .color_renderbuffer(gl::RGBA8)
+            .depth_stencil(gl::DEPTH24_STENCIL8)
+            .build()?;
+        assert_eq!(msaa.samples(), 4);
+        match msaa.color(0) {
+            Some(Attachment::Renderbuffer(renderbuffer)) => assert_eq!(renderbuffer.samples(), 4),
+            _ => panic!("Expected a renderbuffer"),
+        }
+
+        let resolved = std::rc::Rc::new(Texture2D::new(400, 400, gl::RGBA8));
+        let target = Framebuffer::builder(400, 400)
+            .color_texture(resolved.clone())
+            .depth_stencil(gl::DEPTH24_STENCIL8)
+            .build()?;
+
+        {
+            let _msaa = msaa.bind();
+            window.clear(color::BLACK);
+            window.draw(&triangle);
+        }
+        msaa.resolve(&target)?;
+
+        // Pixels on the edge are partially covered
+        let pixels = resolved.read::<u8>(0, gl::RGBA)?;
+        let partial = pixels.chunks(4).filter(|p| p[0] > 0 && p[0] < 255).count();
+        assert!(partial >= 300);
+
+        window.clear(color::BLACK);
+        msaa.resolve_to_window(&window)?;
+        let mut pixel = [0u8; 4];
+        unsafe {
+            gl::ReadPixels(
+                50,
+                300,
+                1,
+                1,
+                gl::RGBA,
+                gl::UNSIGNED_BYTE,
+                pixel.as_mut_ptr() as *mut _,
+            );
+        }
+        assert_eq!(pixel, [255, 255, 255, 255]);
+
+        // Multisampled framebuffers can't have texture attachments
+        let texture = std::rc::Rc::new(Texture2D::new(400, 400, gl::RGBA8));
+        assert!(Framebuffer::builder(400, 400)
+            .samples(4)
+            .color_texture(texture)
+            .build()
+            .is_err());
+        assert!(Framebuffer::builder(400, 400)
+            .samples(max_samples + 1)
+            .color_renderbuffer(gl::RGBA8)
+            .build()
+            .is_err());
+
+        let small = Framebuffer::builder(200, 200)
+            .color_renderbuffer(gl::RGBA8)
+            .build()?;
+        assert!(msaa.resolve(&small).is_err());
+        assert!(small.resolve_to_window(&window).is_err());
+        assert!(target.resolve(&msaa).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn render_state_test() -> Result<(), Box<dyn std::error::Error>> {
+        let sdl = sdl2::init().unwrap();
+
+        let video_subsystem = sdl.video().unwrap();
+        let gl_attrib = video_subsystem.gl_attr();
+        gl_attrib.set_context_profile(sdl2::video::GLProfile::Core);
+        gl_attrib.set_context_version(4, 5);
+        gl_attrib.set_stencil_size(8);
+
+        let mut window = rusty_gl::Window::new()
+            .dimensions(400, 400)
+            .title("Testing Window")
+            .build(&video_subsystem)?;
+
+        rusty_gl::debug::enable();
+
+        fn integer(name: gl::types::GLenum) -> i32 {
+            let mut value = 0;
+            unsafe {
+                gl::GetIntegerv(name, &mut value);
+            }
+            value
+        }
+
+        // Covers the whole window with a half transparent texture
+        let texture = std::rc::Rc::new(Texture2D::from_data(
+            1,
+            1,
+            gl::RGBA8,
+            gl::RGBA,
+            &[255u8, 255, 255, 128],
+        )?);
+        let mut quad = Rect::new(vec![
+            Vertex::new(
+                Vector3::new(0.0, 0.0, 0.0),
+                color::WHITE,
+                Vector2::default(),
+            ),
+            Vertex::new(
+                Vector3::new(400.0, 0.0, 0.0),
+                color::WHITE,
+                Vector2::default(),
+            ),
+            Vertex::new(
+                Vector3::new(0.0, 400.0, 0.0),
+                color::WHITE,
+                Vector2::default(),
+            ),
+            Vertex::new(
+                Vector3::new(400.0, 400.0, 0.0),
+                color::WHITE,
+                Vector2::default(),
+            ),
+        ]);
+        quad.set_texture(Some(texture));
+
+        // Without blending alpha has no effect
+        window.clear(color::BLACK);
+        window.draw(&quad);
+        assert_eq!(pixel(200, 200)[0], 255);
+
+        window.clear(color::BLACK);
+        window.draw_with(&quad, &RenderState::default().blend(BlendMode::Alpha));
+        assert!((pixel(200, 200)[0] as i32 - 128).abs() <= 1);
+
+        // The window state is used again by draw()
+        assert_eq!(window.render_state(), &RenderState::default());
+        window.clear(color::BLACK);
+        window.draw(&quad);
+        assert_eq!(pixel(200, 200)[0], 255);
+
+        window.clear(color::BLACK);
+        window.set_render_state(RenderState::default().blend(BlendMode::Additive));
+        window.draw(&quad);
+        window.draw(&quad);
+        assert!((pixel(200, 200)[0] as i32 - 255).abs() <= 1);
+
+        // Only the scissor rectangle and the red channel are written
+        window.clear(color::BLACK);
+        window.set_render_state(
+            RenderState::default()
+                .scissor(Some([0, 0, 100, 100]))
+                .color_mask([true, false, false, true]),
+        );
+        window.draw(&quad);
+        assert_eq!(pixel(50, 50), [255, 0, 0, 255]);
+        assert_eq!(pixel(150, 150), [0, 0, 0, 255]);
+
+        // Mask the bottom left quarter with the stencil buffer, then only draw inside of it
+        window.set_render_state(RenderState::default());
+        window.clear(color::BLACK);
+        window.draw_with(
+            &quad,
+            &RenderState::default()
+                .scissor(Some([0, 0, 200, 200]))
+                .color_mask([false; 4])
+                .stencil(Some(StencilState::write(1))),
+        );
+        window.draw_with(
+            &quad,
+            &RenderState::default().stencil(Some(StencilState::equal(1))),
+        );
+        assert_eq!(pixel(100, 100), [255, 255, 255, 255]);
+        assert_eq!(pixel(300, 300), [0, 0, 0, 255]);
+
+        let state = RenderState::default()
+            .depth_test(Some(gl::LEQUAL))
+            .depth_write(false)
+            .cull_face(Some(gl::FRONT))
+            .front_face(gl::CW)
+            .polygon_mode(gl::LINE);
+        state.apply();
+        unsafe {
+            assert_eq!(gl::IsEnabled(gl::DEPTH_TEST), gl::TRUE);
+            assert_eq!(gl::IsEnabled(gl::CULL_FACE), gl::TRUE);
+        }
+        assert_eq!(integer(gl::DEPTH_FUNC), gl::LEQUAL as i32);
+        assert_eq!(integer(gl::DEPTH_WRITEMASK), gl::FALSE as i32);
+        assert_eq!(integer(gl::CULL_FACE_MODE), gl::FRONT as i32);
+        assert_eq!(integer(gl::FRONT_FACE), gl::CW as i32);
+        assert_eq!(integer(gl::POLYGON_MODE), gl::LINE as i32);
+
+        // Applying the same state again is filtered, so a raw change survives it
+        unsafe {
+            gl::DepthFunc(gl::ALWAYS);
+        }
+        state.apply();
+        assert_eq!(integer(gl::DEPTH_FUNC), gl::ALWAYS as i32);
+
+        RenderState::invalidate();
+        state.apply();
+        assert_eq!(integer(gl::DEPTH_FUNC), gl::LEQUAL as i32);
+
+        RenderState::default().apply();
+        unsafe {
+            assert_eq!(gl::IsEnabled(gl::DEPTH_TEST), gl::FALSE);
+            assert_eq!(gl::IsEnabled(gl::CULL_FACE), gl::FALSE);
+        }
+        assert_eq!(integer(gl::POLYGON_MODE), gl::FILL as i32);
+
+        Ok(())
+    }
+
+    #[test]
+    fn color_test() -> Result<(), Box<dyn std::error::Error>> {
+        fn close(a: Color, b: Color) -> bool {
+            let (a, b): ([f32; 4], [f32; 4]) = (a.into(), b.into());
+            a.iter().zip(b.iter()).all(|(a, b)| (a - b).abs() < 0.002)
+        }
+
+        assert_eq!(
+            Color::from_hex("#ff8000")?,
+            Color::from_u8(255, 128, 0, 255)
+        );
+        assert_eq!(Color::from_hex("f80")?, Color::from_u8(255, 136, 0, 255));
+        assert_eq!(Color::from_hex("#0008")?, Color::from_u8(0, 0, 0, 136));
+        assert_eq!(
+            Color::from_hex("11223344")?.to_u8(),
+            [0x11, 0x22, 0x33, 0x44]
+        );
+        assert_eq!(Color::from_hex("#11223344")?.to_hex(), "#11223344");
+        assert!(Color::from_hex("#12345").is_err());
+        assert!(Color::from_hex("#ggg").is_err());
+
+        assert!(close(Color::from_hsv(0.0, 1.0, 1.0), color::RED));
+        assert!(close(Color::from_hsv(120.0, 1.0, 1.0), color::GREEN));
+        assert!(close(Color::from_hsv(-120.0, 1.0, 1.0), color::BLUE));
+        assert!(close(Color::from_hsl(60.0, 1.0, 0.5), color::YELLOW));
+        assert!(close(Color::from_hsl(0.0, 0.0, 1.0), color::WHITE));
+
+        let orange = Color::from_u8(240, 120, 30, 255);
+        let [h, s, v] = orange.to_hsv();
+        assert!(close(Color::from_hsv(h, s, v), orange));
+        let [h, s, l] = orange.to_hsl();
+        assert!(close(Color::from_hsl(h, s, l), orange));
+        let [l, a, b] = orange.to_oklab();
+        assert!(close(Color::from_oklab(l, a, b), orange));
+
+        // Reference values from https://bottosson.github.io/posts/oklab/
+        let [l, a, b] = color::WHITE.to_oklab();
+        assert!((l - 1.0).abs() < 0.001 && a.abs() < 0.001 && b.abs() < 0.001);
+
+        assert!(close(
+            Color::rgb(0.5, 0.5, 0.5).to_linear(),
+            Color::rgb(0.214, 0.214, 0.214)
+        ));
+        assert!(close(orange.to_linear().to_srgb(), orange));
+        assert_eq!(color::RED.with_alpha(0.5).to_linear().a, 0.5);
+
+        assert_eq!(
+            color::BLACK.lerp(color::WHITE.with_alpha(0.0), 0.25),
+            Color::new(0.25, 0.25, 0.25, 0.75)
+        );
+        assert!(close(color::BLUE.mix(color::YELLOW, 0.0), color::BLUE));
+        assert!(close(color::BLUE.mix(color::YELLOW, 1.0), color::YELLOW));
+
+        let sdl = sdl2::init().unwrap();
+
+        let video_subsystem = sdl.video().unwrap();
+        let gl_attrib = video_subsystem.gl_attr();
+        gl_attrib.set_context_profile(sdl2::video::GLProfile::Core);
+        gl_attrib.set_context_version(4, 5);
+
+        let mut window = rusty_gl::Window::new()
+            .dimensions(400, 400)
+            .title("Testing Window")
+            .build(&video_subsystem)?;
+
+        rusty_gl::debug::enable();
+
+        // Vertex alpha is blended onto the background
+        let red = color::RED.with_alpha(0.5);
+        let quad = Rect::new(vec![
+            Vertex::new(Vector3::new(0.0, 0.0, 0.0), red, Vector2::default()),
+            Vertex::new(Vector3::new(400.0, 0.0, 0.0), red, Vector2::default()),
+            Vertex::new(Vector3::new(0.0, 400.0, 0.0), red, Vector2::default()),
+            Vertex::new(Vector3::new(400.0, 400.0, 0.0), red, Vector2::default()),
+        ]);
+
+        window.set_render_state(RenderState::default().blend(BlendMode::Alpha));
+        window.clear(color::BLUE);
+        window.draw(&quad);
+
+        let mut pixel = [0u8; 4];
+        unsafe {
+            gl::ReadPixels(
+                200,
+                200,
+                1,
+                1,
+                gl::RGBA,
+                gl::UNSIGNED_BYTE,
+                pixel.as_mut_ptr() as *mut _,
+            );
+        }
+        assert!((pixel[0] as i32 - 128).abs() <= 1);
+        assert_eq!(pixel[1], 0);
+        assert!((pixel[2] as i32 - 128).abs() <= 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn vertex_layout_test() -> Result<(), Box<dyn std::error::Error>> {
+        #[repr(C)]
+        #[derive(Vertex)]
+        struct MarkedVertex {
+            position: Vector2<f32>,
+            #[vertex(normalized, name = "vColor")]
+            color: [u8; 4],
+            #[vertex(skip)]
+            _padding: [f32; 2],
+            #[vertex(location = 4)]
+            marker: u32,
+        }
+
+        let attributes = MarkedVertex::attributes();
+        let layout = attributes
+            .iter()
+            .map(|a| {
+                (
+                    a.name(),
+                    a.location(),
+                    a.size(),
+                    a.gl_type(),
+                    a.is_normalized(),
+                    a.offset(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            layout,
+            vec![
+                ("position", 0, 2, gl::FLOAT, false, 0),
+                ("vColor", 1, 4, gl::UNSIGNED_BYTE, true, 8),
+                ("marker", 4, 1, gl::UNSIGNED_INT, false, 20),
+            ]
+        );
+
+        let names = Vertex::attributes()
+            .iter()
+            .map(|a| (a.name(), a.location(), a.size()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            vec![("vPos", 0, 3), ("vColor", 1, 4), ("vTexCoord", 2, 2)]
+        );
+
+        let sdl = sdl2::init().unwrap();
+
+        let video_subsystem = sdl.video().unwrap();
+        let gl_attrib = video_subsystem.gl_attr();
+        gl_attrib.set_context_profile(sdl2::video::GLProfile::Core);
+        gl_attrib.set_context_version(4, 5);
+
+        let window = rusty_gl::Window::new()
+            .dimensions(400, 400)
+            .title("Testing Window")
+            .build(&video_subsystem)?;
+
+        rusty_gl::debug::enable();
+
+        pub const VERTEX_SHADER: &str = "
+            #version 430
+            layout (location = 0) in vec2 position;
+            layout (location = 1) in vec4 vColor;
+            layout (location = 4) in uint marker;
+            out vec4 color;
+
+            void main() {
+                gl_Position = vec4(position, 0.0, 1.0);
+                // Integer attributes arrive unconverted
+                color = marker == 7u ? vColor : vec4(0.0);
+            }
+        ";
+
+        pub const FRAGMENT_SHADER: &str = "
+            #version 430
+            in vec4 color;
+            out vec4 FragColor;
+
+            void main() {
+                FragColor = color;
+            }
+        ";
+
+        let shader = PipelineShader::create(
+            Some(ShaderSource::String(VERTEX_SHADER)),
+            Some(ShaderSource::String(FRAGMENT_SHADER)),
+        )?;
+
+        // A triangle covering the whole viewport
+        let vertex = |x, y| MarkedVertex {
+            position: Vector2::new(x, y),
+            color: [255, 128, 0, 255],
+            _padding: [0.0; 2],
+            marker: 7,
+        };
+        let vertices = vec![vertex(-1.0, -1.0), vertex(3.0, -1.0), vertex(-1.0, 3.0)];
+
+        let vao = VAO::new();
+        let _vbo = VBO::from_vertices(&vertices);
+
+        window.clear(color::BLACK);
+        shader.enable();
+        vao.draw(gl::TRIANGLES, vertices.len() as i32, None);
+
+        let mut pixel = [0u8; 4];
+        unsafe {
+            gl::ReadPixels(
+                200,
+                200,
+                1,
+                1,
+                gl::RGBA,
+                gl::UNSIGNED_BYTE,
+                pixel.as_mut_ptr() as *mut _,
+            );
+        }
+        assert_eq!(pixel, [255, 128, 0, 255]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn attribute_binding_test() -> Result<(), Box<dyn std::error::Error>> {
+        let sdl = sdl2::init().unwrap();
+
+        let video_subsystem = sdl.video().unwrap();
+        let gl_attrib = video_subsystem.gl_attr();
+        gl_attrib.set_context_profile(sdl2::video::GLProfile::Core);
+        gl_attrib.set_context_version(4, 5);
+
+        let window = rusty_gl::Window::new()
+            .dimensions(400, 400)
+            .title("Testing Window")
+            .build(&video_subsystem)?;
+
+        rusty_gl::debug::enable();
+
+        // The locations are swapped compared to the default shader
+        pub const SWAPPED_SHADER: &str = "
+            #version 430
+            layout (location = 0) in vec4 vColor;
+            layout (location = 1) in vec3 vPos;
+            out vec4 color;
+
+            void main() {
+                gl_Position = vec4(vPos, 1.0);
+                color = vColor;
+            }
+        ";
+
+        pub const RENAMED_SHADER: &str = "
+            #version 430
+            in vec3 aPosition;
+            in vec4 aColor;
+            out vec4 color;
+
+            void main() {
+                gl_Position = vec4(aPosition, 1.0);
+                color = aColor.bgra;
+            }
+        ";
+
+        pub const MISMATCHED_SHADER: &str = "
+            #version 430
+            layout (location = 0) in vec3 vPos;
+            layout (location = 1) in uvec4 vColor;
+            layout (location = 2) in vec3 vNormal;
+
+            void main() {
+                gl_Position = vec4(vPos + vNormal, float(vColor.x));
+            }
+        ";
+
+        pub const FRAGMENT_SHADER: &str = "
+            #version 430
+            in vec4 color;
+            out vec4 FragColor;
+
+            void main() {
+                FragColor = color;
+            }
+        ";
+
+        let swapped = PipelineShader::create(
+            Some(ShaderSource::String(SWAPPED_SHADER)),
+            Some(ShaderSource::String(FRAGMENT_SHADER)),
+        )?;
+        let renamed = PipelineShader::create(
+            Some(ShaderSource::String(RENAMED_SHADER)),
+            Some(ShaderSource::String(FRAGMENT_SHADER)),
+        )?;
+        let mismatched =
+            PipelineShader::create(Some(ShaderSource::String(MISMATCHED_SHADER)), None)?;
+
+        assert_eq!(
+            swapped.attributes(),
+            &[
+                ShaderAttribute {
+                    name: String::from("vColor"),
+                    location: 0,
+                    gl_type: gl::FLOAT_VEC4,
+                },
+                ShaderAttribute {
+                    name: String::from("vPos"),
+                    location: 1,
+                    gl_type: gl::FLOAT_VEC3,
+                },
+            ]
+        );
+        assert!(renamed.attribute("aColor").is_some());
+        assert!(renamed.attribute("vColor").is_none());
+
+        // Covers the whole viewport, vertices are already in clip space
+        let red = color::RED;
+        let mut quad = Rect::new(vec![
+            Vertex::new(Vector3::new(-1.0, 1.0, 0.0), red, Vector2::default()),
+            Vertex::new(Vector3::new(1.0, 1.0, 0.0), red, Vector2::default()),
+            Vertex::new(Vector3::new(-1.0, -1.0, 0.0), red, Vector2::default()),
+            Vertex::new(Vector3::new(1.0, -1.0, 0.0), red, Vector2::default()),
+        ]);
+
+        window.clear(color::BLACK);
+        quad.draw(&swapped);
+        assert_eq!(pixel(200, 200), [255, 0, 0, 255]);
+
+        // Switching shaders rebinds the attributes
+        window.clear(color::BLACK);
+        window.draw(&quad);
+        window.clear(color::BLACK);
+        quad.draw(&swapped);
+        assert_eq!(pixel(200, 200), [255, 0, 0, 255]);
+
+        quad.set_attribute_names("aPosition", "aColor", "aUv");
+        window.clear(color::BLACK);
+        quad.draw(&renamed);
+        assert_eq!(pixel(200, 200), [0, 0, 255, 255]);
+
+        let vao = VAO::new();
+        let vbo = VBO::from_vertices(&[Vertex::new(Vector3::default(), red, Vector2::default())]);
+        let mismatches = vao.bind_attributes(
+            &vbo,
+            std::mem::size_of::<Vertex>() as i32,
+            &Vertex::attributes(),
+            &mismatched,
+        );
+        assert_eq!(
+            mismatches,
+            vec![
+                AttributeMismatch::Type {
+                    name: String::from("vColor"),
+                    gl_type: gl::FLOAT,
+                    shader_type: gl::UNSIGNED_INT_VEC4,
+                },
+                AttributeMismatch::Missing {
+                    name: String::from("vNormal"),
+                    location: 2,
+                },
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn dynamic_buffer_test() -> Result<(), Box<dyn std::error::Error>> {
+        let sdl = sdl2::init().unwrap();
+
+        let video_subsystem = sdl.video().unwrap();
+        let gl_attrib = video_subsystem.gl_attr();
+        gl_attrib.set_context_profile(sdl2::video::GLProfile::Core);
+        gl_attrib.set_context_version(4, 5);
+
+        let window = rusty_gl::Window::new()
+            .dimensions(400, 400)
+            .title("Testing Window")
+            .build(&video_subsystem)?;
+
+        rusty_gl::debug::enable();
+
+        fn read<T: Default + Clone>(id: u32, count: usize) -> Vec<T> {
+            let mut data = vec![T::default(); count];
+            unsafe {
+                gl::GetNamedBufferSubData(
+                    id,
+                    0,
+                    std::mem::size_of_val(&data[..]) as isize,
+                    data.as_mut_ptr() as *mut _,
+                );
+            }
+            data
+        }
+
+        // Ranged updates keep the rest of the content, also when growing
+        let _vao = VAO::new();
+        let mut ebo = EBO::with_usage(&[0u32, 1, 2, 3], gl::DYNAMIC_DRAW);
+        assert_eq!(
+            (ebo.len(), ebo.capacity(), ebo.usage()),
+            (4, 16, gl::DYNAMIC_DRAW)
+        );
+
+        ebo.update_range(2, &[7u32, 8, 9])?;
+        assert_eq!(ebo.len(), 5);
+        assert_eq!(ebo.capacity(), 32);
+        assert_eq!(read::<u32>(ebo.id(), 5), vec![0, 1, 7, 8, 9]);
+
+        // A gap of undefined indices isn't allowed
+        assert!(ebo.update_range(6, &[1u32]).is_err());
+        assert_eq!(ebo.len(), 5);
+        ebo.update_range(5, &[6u32])?;
+        assert_eq!(ebo.len(), 6);
+        assert_eq!(read::<u32>(ebo.id(), 6), vec![0, 1, 7, 8, 9, 6]);
+
+        ebo.update(&[4u32, 5]);
+        assert_eq!(ebo.len(), 2);
+        assert_eq!(ebo.capacity(), 32);
+        assert_eq!(read::<u32>(ebo.id(), 2), vec![4, 5]);
+
+        let vbo = VBO::with_usage::<Vertex>(&[], gl::STREAM_DRAW);
+        assert_eq!(vbo.capacity(), 0);
+        vbo.update(0, &[1.0f32, 2.0]);
+        vbo.update(3, &[4.0f32]);
+        assert_eq!(vbo.capacity(), 16);
+        assert_eq!(read::<f32>(vbo.id(), 2), vec![1.0, 2.0]);
+        assert_eq!(read::<f32>(vbo.id(), 4)[3], 4.0);
+
+        // A trail growing every frame reuses one buffer
+        let mut trail = CustomShape2D::with_usage(Vec::new(), gl::LINES, gl::STREAM_DRAW);
+        let id = trail.vbo().id();
+
+        for frame in 1..=4 {
+            let lines = (0..frame)
+                .flat_map(|i| {
+                    let x = 50.5 + i as f32 * 100.0;
+                    vec![
+                        Vertex::new(Vector3::new(x, 0.0, 0.0), color::WHITE, Vector2::default()),
+                        Vertex::new(
+                            Vector3::new(x, 400.0, 0.0),
+                            color::WHITE,
+                            Vector2::default(),
+                        ),
+                    ]
+                })
+                .collect();
+            trail.set_vertices(lines);
+
+            window.clear(color::BLACK);
+            window.draw(&trail);
+        }
+
+        assert_eq!(trail.vbo().id(), id);
+        assert_eq!(trail.vbo().usage(), gl::STREAM_DRAW);
+        assert!(trail.vbo().capacity() >= 8 * std::mem::size_of::<Vertex>());
+        for x in [50, 150, 250, 350].iter() {
+            assert_eq!(pixel(*x, 200), [255, 255, 255, 255]);
+        }
+
+        // Shrinking keeps the capacity, only the first line is left
+        trail.set_vertices(vec![
+            Vertex::new(
+                Vector3::new(50.5, 0.0, 0.0),
+                color::WHITE,
+                Vector2::default(),
+            ),
+            Vertex::new(
+                Vector3::new(50.5, 400.0, 0.0),
+                color::WHITE,
+                Vector2::default(),
+            ),
+        ]);
+        window.clear(color::BLACK);
+        window.draw(&trail);
+        assert!(trail.vbo().capacity() >= 8 * std::mem::size_of::<Vertex>());
+        assert_eq!(pixel(50, 200), [255, 255, 255, 255]);
+        assert_eq!(pixel(150, 200), [0, 0, 0, 255]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn editable_vertices_test() -> Result<(), Box<dyn std::error::Error>> {
+        let sdl = sdl2::init().unwrap();
+
+        let video_subsystem = sdl.video().unwrap();
+        let gl_attrib = video_subsystem.gl_attr();
+        gl_attrib.set_context_profile(sdl2::video::GLProfile::Core);
+        gl_attrib.set_context_version(4, 5);
+
+        let window = rusty_gl::Window::new()
+            .dimensions(400, 400)
+            .title("Testing Window")
+            .build(&video_subsystem)?;
+
+        rusty_gl::debug::enable();
+
+        let corner = |x, y| Vertex::new(Vector3::new(x, y, 0.0), color::RED, Vector2::default());
+        let mut rect = Rect::new(vec![
+            corner(0.0, 0.0),
+            corner(400.0, 0.0),
+            corner(0.0, 400.0),
+            corner(400.0, 400.0),
+        ]);
+
+        // Positions are relative to the center
+        assert_eq!(rect.vertices().len(), 4);
+        assert_eq!(
+            rect.vertices()[0].position(),
+            &Vector3::new(-200.0, -200.0, 0.0)
+        );
+
+        window.clear(color::BLACK);
+        window.draw(&rect);
+        assert_eq!(pixel(200, 200), [255, 0, 0, 255]);
+
+        for vertex in rect.vertices_mut().iter_mut() {
+            *vertex.color_mut() = color::GREEN;
+        }
+        assert_eq!(rect.vertices()[3].color(), &color::GREEN);
+
+        window.clear(color::BLACK);
+        window.draw(&rect);
+        assert_eq!(pixel(200, 200), [0, 255, 0, 255]);
+
+        // Pulls the bottom corners up to the middle, the lower half stays empty
+        {
+            let mut vertices = rect.vertices_mut();
+            vertices[2].position_mut().y = 0.0;
+            vertices[3].position_mut().y = 0.0;
+        }
+
+        window.clear(color::BLACK);
+        window.draw(&rect);
+        assert_eq!(pixel(200, 300), [0, 255, 0, 255]);
+        assert_eq!(pixel(200, 100), [0, 0, 0, 255]);
+
+        let mut fan = CustomShape2D::new(
+            vec![
+                corner(0.0, 0.0),
+                corner(400.0, 0.0),
+                corner(400.0, 400.0),
+                corner(0.0, 400.0),
+            ],
+            gl::TRIANGLE_FAN,
+        );
+        *fan.vertices_mut()[0].color_mut() = color::BLUE;
+        assert_eq!(fan.vertices()[0].color(), &color::BLUE);
+
+        // Only the corner at the top left is blue, the others still red
+        window.clear(color::BLACK);
+        window.draw(&fan);
+        let top_left = pixel(1, 398);
+        let bottom_right = pixel(398, 1);
+        assert!(top_left[2] > 200 && top_left[0] < 50);
+        assert!(bottom_right[0] > 200 && bottom_right[2] < 50);
+
+        Ok(())
+    }
+
+    #[test]
+    fn index_buffer_test() -> Result<(), Box<dyn std::error::Error>> {
+        let sdl = sdl2::init().unwrap();
+
+        let video_subsystem = sdl.video().unwrap();
+        let gl_attrib = video_subsystem.gl_attr();
+        gl_attrib.set_context_profile(sdl2::video::GLProfile::Core);
+        gl_attrib.set_context_version(4, 5);
+
+        let window = rusty_gl::Window::new()
+            .dimensions(400, 400)
+            .title("Testing Window")
+            .build(&video_subsystem)?;
+
+        rusty_gl::debug::enable();
+
+        let corner = |x, y| Vertex::new(Vector3::new(x, y, 0.0), color::WHITE, Vector2::default());
+
+        // A quad sharing two of its four vertices between the triangles
+        let mut quad = CustomShape2D::with_indices(
+            vec![
+                corner(0.0, 0.0),
+                corner(400.0, 0.0),
+                corner(0.0, 400.0),
+                corner(400.0, 400.0),
+            ],
+            &[0u8, 1, 2, 2, 1, 3],
+            gl::TRIANGLES,
+        );
+        assert_eq!(quad.ebo().unwrap().index_type(), gl::UNSIGNED_BYTE);
+        assert_eq!(quad.ebo().unwrap().len(), 6);
+
+        window.clear(color::BLACK);
+        window.draw(&quad);
+        assert_eq!(pixel(100, 100), [255, 255, 255, 255]);
+        assert_eq!(pixel(300, 300), [255, 255, 255, 255]);
+
+        // Only the first triangle is left, it covers the top left half
+        quad.set_indices(&[0u32, 1, 2]);
+        assert_eq!(quad.ebo().unwrap().index_type(), gl::UNSIGNED_INT);
+        window.clear(color::BLACK);
+        window.draw(&quad);
+        assert_eq!(pixel(100, 300), [255, 255, 255, 255]);
+        assert_eq!(pixel(300, 100), [0, 0, 0, 255]);
+
+        quad.clear_indices();
+        assert!(quad.ebo().is_none());
+
+        // Two strips, without the restart they would be connected through the middle
+        let strips = CustomShape2D::with_indices(
+            vec![
+                corner(0.0, 0.0),
+                corner(100.0, 0.0),
+                corner(0.0, 400.0),
+                corner(100.0, 400.0),
+                corner(300.0, 0.0),
+                corner(400.0, 0.0),
+                corner(300.0, 400.0),
+                corner(400.0, 400.0),
+            ],
+            &[0u16, 1, 2, 3, u16::RESTART, 4, 5, 6, 7],
+            gl::TRIANGLE_STRIP,
+        );
+
+        window.clear(color::BLACK);
+        window.draw(&strips);
+        assert_eq!(pixel(50, 200), [255, 255, 255, 255]);
+        assert_eq!(pixel(350, 200), [255, 255, 255, 255]);
+        assert_eq!(pixel(200, 200), [0, 0, 0, 255]);
+
+        let _vao = VAO::new();
+        let mut ebo = EBO::with_usage(&[0u16, 1, 2], gl::STATIC_DRAW);
+        assert_eq!(ebo.index_type(), gl::UNSIGNED_SHORT);
+        assert!(ebo.update_range(1, &[5u32]).is_err());
+        ebo.update_range(3, &[3u16])?;
+        assert_eq!(ebo.len(), 4);
+
+        Ok(())
+    }
+
+    #[test]
+    fn instanced_test() -> Result<(), Box<dyn std::error::Error>> {
+        let sdl = sdl2::init().unwrap();
+
+        let video_subsystem = sdl.video().unwrap();
+        let gl_attrib = video_subsystem.gl_attr();
+        gl_attrib.set_context_profile(sdl2::video::GLProfile::Core);
+        gl_attrib.set_context_version(4, 5);
+
+        let window = rusty_gl::Window::new()
+            .dimensions(400, 400)
+            .title("Testing Window")
+            .build(&video_subsystem)?;
+
+        rusty_gl::debug::enable();
+
+        let corner = |x, y| Vertex::new(Vector3::new(x, y, 0.0), color::WHITE, Vector2::default());
+
+        // A 20x20 square around the origin, drawn through an ebo
+        let square = Rect::new(vec![
+            corner(-10.0, -10.0),
+            corner(10.0, -10.0),
+            corner(-10.0, 10.0),
+            corner(10.0, 10.0),
+        ]);
+
+        let mut squares = Instanced::new(
+            square,
+            vec![
+                Instance::new(Vector2::new(50.0, 50.0)).color(color::RED),
+                Instance::new(Vector2::new(150.0, 50.0)).color(color::GREEN),
+                Instance::new(Vector2::new(250.0, 50.0)).scale(3.0),
+            ],
+        );
+
+        window.clear(color::BLACK);
+        window.draw(&squares);
+        assert_eq!(window_pixel(50, 50), [255, 0, 0, 255]);
+        assert_eq!(window_pixel(150, 50), [0, 255, 0, 255]);
+        assert_eq!(window_pixel(275, 75), [255, 255, 255, 255]);
+        assert_eq!(window_pixel(100, 50), [0, 0, 0, 255]);
+
+        // Instances are offsets from the shape
+        squares.translate(Vector2::new(0.0, 100.0));
+        squares.instances_mut()[1].color = color::BLUE;
+        squares
+            .instances_mut()
+            .push(Instance::new(Vector2::new(350.0, 50.0)));
+
+        window.clear(color::BLACK);
+        window.draw(&squares);
+        assert_eq!(window_pixel(50, 50), [0, 0, 0, 255]);
+        assert_eq!(window_pixel(50, 150), [255, 0, 0, 255]);
+        assert_eq!(window_pixel(150, 150), [0, 0, 255, 255]);
+        assert_eq!(window_pixel(350, 150), [255, 255, 255, 255]);
+
+        // The shape itself still draws without instances
+        window.clear(color::BLACK);
+        window.draw(squares.shape());
+        assert_eq!(window_pixel(5, 100), [255, 255, 255, 255]);
+        assert_eq!(window_pixel(50, 150), [0, 0, 0, 255]);
+
+        // A custom instance layout without an ebo
+        #[repr(C)]
+        #[derive(Clone, Copy, Vertex)]
+        struct Marker {
+            #[vertex(name = "iCenter")]
+            center: Vector2<f32>,
+            #[vertex(name = "iColor", divisor = 2)]
+            color: Color,
+        }
+
+        const VERTEX_SHADER: &str = "
+            #version 430
+            in vec3 vPos;
+            in vec2 iCenter;
+            in vec4 iColor;
+            out vec4 color;
+            void main() {
+                vec2 pos = (vPos.xy + iCenter) / 200.0 - 1.0;
+                gl_Position = vec4(pos.x, -pos.y, 0.0, 1.0);
+                color = iColor;
+            }
+        ";
+
+        const FRAGMENT_SHADER: &str = "
+            #version 430
+            in vec4 color;
+            out vec4 FragColor;
+            void main() {
+                FragColor = color;
+            }
+        ";
+
+        let shader = PipelineShader::create(
+            Some(ShaderSource::String(VERTEX_SHADER)),
+            Some(ShaderSource::String(FRAGMENT_SHADER)),
+        )?;
+
+        let square = CustomShape2D::new(
+            vec![
+                corner(-10.0, -10.0),
+                corner(10.0, -10.0),
+                corner(-10.0, 10.0),
+                corner(10.0, -10.0),
+                corner(10.0, 10.0),
+                corner(-10.0, 10.0),
+            ],
+            gl::TRIANGLES,
+        );
+
+        // Every instance reads the next center, but only every second one the next color
+        let marker = |x, color| Marker {
+            center: Vector2::new(x, 300.0),
+            color,
+        };
+        let markers: Instanced<CustomShape2D, Marker> = Instanced::new(
+            square,
+            vec![
+                marker(50.0, color::RED),
+                marker(150.0, color::GREEN),
+                marker(250.0, color::BLUE),
+                marker(350.0, color::BLUE),
+            ],
+        );
+
+        window.clear(color::BLACK);
+        shader.enable();
+        markers.draw(&shader);
+        assert_eq!(window_pixel(50, 300), [255, 0, 0, 255]);
+        assert_eq!(window_pixel(150, 300), [255, 0, 0, 255]);
+        assert_eq!(window_pixel(250, 300), [0, 255, 0, 255]);
+        assert_eq!(window_pixel(350, 300), [0, 255, 0, 255]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn batch_test() -> Result<(), Box<dyn std::error::Error>> {
+        let sdl = sdl2::init().unwrap();
+
+        let video_subsystem = sdl.video().unwrap();
+        let gl_attrib = video_subsystem.gl_attr();
+        gl_attrib.set_context_profile(sdl2::video::GLProfile::Core);
+        gl_attrib.set_context_version(4, 5);
+
+        let window = rusty_gl::Window::new()
+            .dimensions(400, 400)
+            .title("Testing Window")
+            .build(&video_subsystem)?;
+
+        rusty_gl::debug::enable();
+
+        let vertex = |x, y, color| Vertex::new(Vector3::new(x, y, 0.0), color, Vector2::default());
+        let rect = |x, y, width, height, color| {
+            Rect::new(vec![
+                vertex(x, y, color),
+                vertex(x + width, y, color),
+                vertex(x, y + height, color),
+                vertex(x + width, y + height, color),
+            ])
+        };
+
+        let mut batch = Batch2D::new();
+        assert!(batch.is_empty());
+
+        // Triangles, strips and fans end up in the same draw call
+        let red = rect(30.0, 30.0, 40.0, 40.0, color::RED);
+        let mut green = rect(120.0, 40.0, 60.0, 20.0, color::GREEN);
+        green.rotate(std::f32::consts::FRAC_PI_2);
+        let blue_fan = CustomShape2D::new(
+            vec![
+                vertex(230.0, 30.0, color::BLUE),
+                vertex(270.0, 30.0, color::BLUE),
+                vertex(270.0, 70.0, color::BLUE),
+                vertex(230.0, 70.0, color::BLUE),
+            ],
+            gl::TRIANGLE_FAN,
+        );
+        let mut tinted = rect(330.0, 30.0, 40.0, 40.0, color::WHITE);
+        tinted.set_tint(color::RED);
+
+        batch.push(&red).push(&green).push(&blue_fan).push(&tinted);
+        assert_eq!(batch.draw_calls(), 1);
+        assert_eq!(batch.vertex_count(), 16);
+
+        // Lines need their own draw call
+        let line = CustomShape2D::new(
+            vec![
+                vertex(0.0, 299.5, color::WHITE),
+                vertex(200.0, 299.5, color::WHITE),
+                vertex(400.0, 299.5, color::WHITE),
+            ],
+            gl::LINE_STRIP,
+        );
+        batch.push(&line);
+        assert_eq!(batch.draw_calls(), 2);
+
+        // So do textures and blend modes
+        let pixels: Vec<u8> = vec![255, 0, 255, 255];
+        let texture = std::rc::Rc::new(Texture2D::from_data(1, 1, gl::RGBA8, gl::RGBA, &pixels)?);
+        texture.set_filter(gl::NEAREST, gl::NEAREST);
+        let mut textured = rect(30.0, 330.0, 40.0, 40.0, color::WHITE);
+        textured.set_texture(Some(texture.clone()));
+        batch.push(&textured);
+        assert_eq!(batch.draw_calls(), 3);
+
+        batch
+            .set_blend(Some(BlendMode::Additive))
+            .push(&rect(0.0, 120.0, 60.0, 60.0, color::RED))
+            .push(&rect(40.0, 120.0, 60.0, 60.0, color::GREEN));
+        assert_eq!(batch.draw_calls(), 4);
+
+        window.clear(color::BLACK);
+        window.draw(&batch);
+        assert_eq!(window_pixel(50, 50), [255, 0, 0, 255]);
+        assert_eq!(window_pixel(150, 75), [0, 255, 0, 255]);
+        assert_eq!(window_pixel(175, 50), [0, 0, 0, 255]);
+        assert_eq!(window_pixel(250, 50), [0, 0, 255, 255]);
+        assert_eq!(window_pixel(350, 50), [255, 0, 0, 255]);
+        assert_eq!(window_pixel(200, 300), [255, 255, 255, 255]);
+        assert_eq!(window_pixel(50, 350), [255, 0, 255, 255]);
+        assert_eq!(window_pixel(20, 150), [255, 0, 0, 255]);
+        assert_eq!(window_pixel(50, 150), [255, 255, 0, 255]);
+        assert_eq!(window_pixel(80, 150), [0, 255, 0, 255]);
+
+        // The blend mode only applies inside the batch
+        assert_eq!(window.render_state(), &RenderState::default());
+        window.draw(&red);
+        assert_eq!(window_pixel(50, 50), [255, 0, 0, 255]);
+
+        // Without a known render state, batches without a blend mode use the default one
+        let mut layered = Batch2D::new();
+        layered
+            .set_blend(Some(BlendMode::Additive))
+            .push(&rect(0.0, 120.0, 60.0, 60.0, color::RED))
+            .set_blend(None)
+            .push(&rect(40.0, 120.0, 60.0, 60.0, color::GREEN));
+
+        window.clear(color::BLACK);
+        RenderState::invalidate();
+        layered.draw(window.pipeline_shader());
+        assert_eq!(window_pixel(20, 150), [255, 0, 0, 255]);
+        assert_eq!(window_pixel(50, 150), [0, 255, 0, 255]);
+
+        // The whole batch can be moved like a shape
+        batch.clear();
+        assert!(batch.is_empty());
+        batch.push(&red);
+        batch.translate(Vector2::new(0.0, 100.0));
+
+        window.clear(color::BLACK);
+        window.draw(&batch);
+        assert_eq!(window_pixel(50, 50), [0, 0, 0, 255]);
+        assert_eq!(window_pixel(50, 150), [255, 0, 0, 255]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn multi_draw_indirect_test() -> Result<(), Box<dyn std::error::Error>> {
+        let sdl = sdl2::init().unwrap();
+
+        let video_subsystem = sdl.video().unwrap();
+        let gl_attrib = video_subsystem.gl_attr();
+        gl_attrib.set_context_profile(sdl2::video::GLProfile::Core);
+        gl_attrib.set_context_version(4, 5);
+
+        let window = rusty_gl::Window::new()
+            .dimensions(400, 400)
+            .title("Testing Window")
+            .build(&video_subsystem)?;
+
+        rusty_gl::debug::enable();
+
+        const VERTEX_SHADER: &str = "
+            #version 430
+            layout (location = 0) in vec3 vPos;
+            layout (location = 1) in vec4 vColor;
+            out vec4 color;
+            void main() {
+                gl_Position = vec4(vPos, 1.0);
+                color = vColor;
+            }
+        ";
+
+        const FRAGMENT_SHADER: &str = "
+            #version 430
+            in vec4 color;
+            out vec4 FragColor;
+            void main() {
+                FragColor = color;
+            }
+        ";
+
+        let shader = PipelineShader::create(
+            Some(ShaderSource::String(VERTEX_SHADER)),
+            Some(ShaderSource::String(FRAGMENT_SHADER)),
+        )?;
+
+        // Three quads next to each other, each from four corners
+        let colors = [color::RED, color::GREEN, color::BLUE];
+        let vertices: Vec<Vertex> = colors
+            .iter()
+            .enumerate()
+            .flat_map(|(i, &color)| {
+                let left = -1.0 + i as f32 * 2.0 / 3.0;
+                let right = left + 2.0 / 3.0;
+                vec![
+                    Vertex::new(Vector3::new(left, -1.0, 0.0), color, Vector2::default()),
+                    Vertex::new(Vector3::new(right, -1.0, 0.0), color, Vector2::default()),
+                    Vertex::new(Vector3::new(left, 1.0, 0.0), color, Vector2::default()),
+                    Vertex::new(Vector3::new(right, 1.0, 0.0), color, Vector2::default()),
+                ]
+            })
+            .collect();
+
+        // Strips, filled from the cpu and the middle one skipped
+        let vao = VAO::new();
+        let _vbo = VBO::from_vertices(&vertices);
+        let commands = SSBO::create_from(
+            3,
+            (0..3)
+                .map(|i| DrawArraysIndirectCommand {
+                    count: 4,
+                    instance_count: if i == 1 { 0 } else { 1 },
+                    first: i * 4,
+                    base_instance: 0,
+                })
+                .collect::<Vec<_>>(),
+            gl::DYNAMIC_DRAW,
+        );
+
+        window.clear(color::BLACK);
+        shader.enable();
+        vao.multi_draw_arrays_indirect(gl::TRIANGLE_STRIP, &commands);
+        assert_eq!(pixel(66, 200), [255, 0, 0, 255]);
+        assert_eq!(pixel(200, 200), [0, 0, 0, 255]);
+        assert_eq!(pixel(333, 200), [0, 0, 255, 255]);
+
+        // One index list shared by all quads, the commands are culled by a compute shader
+        let indexed_vao = VAO::new();
+        let _indexed_vbo = VBO::from_vertices(&vertices);
+        let ebo = EBO::with_usage(&[0u8, 1, 2, 2, 1, 3], gl::STATIC_DRAW);
+        let element_commands = SSBO::create_from(
+            4,
+            (0..3)
+                .map(|i| DrawElementsIndirectCommand {
+                    count: 6,
+                    instance_count: 1,
+                    first_index: 0,
+                    base_vertex: i * 4,
+                    base_instance: 0,
+                })
+                .collect::<Vec<_>>(),
+            gl::DYNAMIC_DRAW,
+        );
+
+        const COMPUTE_SHADER: &str = "
+            #version 430
+            layout(local_size_x = 1) in;
+
+            struct Command {
+                uint count;
+                uint instanceCount;
+                uint firstIndex;
+                int baseVertex;
+                uint baseInstance;
+            };
+
+            layout(std430, binding = 4) buffer Commands {
+                Command commands[];
+            };
+
+            void main() {
+                uint i = gl_GlobalInvocationID.x;
+                commands[i].instanceCount = i == 1 ? 1 : 0;
+            }
+        ";
+
+        let culling = ComputeShader::create(ShaderSource::String(COMPUTE_SHADER))?;
+        culling.dispatch(3, 1, 1, gl::COMMAND_BARRIER_BIT);
+
+        window.clear(color::BLACK);
+        shader.enable();
+        indexed_vao.multi_draw_elements_indirect(gl::TRIANGLES, &element_commands, &ebo);
+        assert_eq!(pixel(66, 200), [0, 0, 0, 255]);
+        assert_eq!(pixel(200, 200), [0, 255, 0, 255]);
+        assert_eq!(pixel(333, 200), [0, 0, 0, 255]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn transform_test() -> Result<(), Box<dyn std::error::Error>> {
+        let sdl = sdl2::init().unwrap();
+
+        let video_subsystem = sdl.video().unwrap();
+        let gl_attrib = video_subsystem.gl_attr();
+        gl_attrib.set_context_profile(sdl2::video::GLProfile::Core);
+        gl_attrib.set_context_version(4, 5);
+
+        let window = rusty_gl::Window::new()
+            .dimensions(400, 400)
+            .title("Testing Window")
+            .build(&video_subsystem)?;
+
+        rusty_gl::debug::enable();
+
+        // The right hand side is applied first
+        let translate = Transform2D::translation(Vector2::new(10.0, 0.0));
+        let scale = Transform2D::scaling(Vector2::new(2.0, 3.0));
+        assert_close(
+            (translate * scale).apply(Vector2::new(1.0, 1.0)),
+            Vector2::new(12.0, 3.0),
+        );
+        assert_close(
+            translate.then(scale).apply(Vector2::new(1.0, 1.0)),
+            Vector2::new(22.0, 3.0),
+        );
+
+        // Same direction as the default shader rotated before
+        let rotate = Transform2D::rotation(std::f32::consts::FRAC_PI_2);
+        assert_close(
+            rotate.apply(Vector2::new(1.0, 0.0)),
+            Vector2::new(0.0, -1.0),
+        );
+
+        let shear = Transform2D::shearing(Vector2::new(0.5, 0.0));
+        assert_close(shear.apply(Vector2::new(0.0, 2.0)), Vector2::new(1.0, 2.0));
+
+        let combined = translate * rotate * shear * scale;
+        let inverse = combined.inverse().unwrap();
+        assert_close(
+            (inverse * combined).apply(Vector2::new(3.0, -7.0)),
+            Vector2::new(3.0, -7.0),
+        );
+        assert!(Transform2D::scaling(Vector2::new(0.0, 1.0))
+            .inverse()
+            .is_none());
+        assert_eq!(
+            Transform2D::IDENTITY.to_mat3(),
+            [1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0]
+        );
+
+        let corner = |x, y| Vertex::new(Vector3::new(x, y, 0.0), color::WHITE, Vector2::default());
+        let square = || {
+            Rect::new(vec![
+                corner(190.0, 190.0),
+                corner(210.0, 190.0),
+                corner(190.0, 210.0),
+                corner(210.0, 210.0),
+            ])
+        };
+
+        // Scaling multiplies
+        let mut rect = square();
+        rect.scale(2.0).scale(2.0);
+        assert_eq!(rect.placement().scale, Vector2::new(4.0, 4.0));
+
+        // Non uniform scale
+        let mut rect = square();
+        rect.scale_xy(Vector2::new(3.0, 1.0));
+
+        window.clear(color::BLACK);
+        window.draw(&rect);
+        assert_eq!(window_pixel(225, 200), [255, 255, 255, 255]);
+        assert_eq!(window_pixel(200, 215), [0, 0, 0, 255]);
+
+        // The top left corner is moved to the center and rotated around
+        let mut rect = square();
+        rect.set_origin(Vector2::new(-10.0, -10.0));
+
+        window.clear(color::BLACK);
+        window.draw(&rect);
+        assert_eq!(window_pixel(215, 215), [255, 255, 255, 255]);
+        assert_eq!(window_pixel(195, 195), [0, 0, 0, 255]);
+
+        rect.rotate(std::f32::consts::PI);
+        window.clear(color::BLACK);
+        window.draw(&rect);
+        assert_eq!(window_pixel(185, 185), [255, 255, 255, 255]);
+        assert_eq!(window_pixel(215, 215), [0, 0, 0, 255]);
+
+        // A sheared shape looks the same when batched
+        let mut rect = square();
+        rect.shear(Vector2::new(1.0, 0.0));
+        assert_close(
+            rect.transform().apply(Vector2::new(10.0, 10.0)),
+            Vector2::new(220.0, 210.0),
+        );
+
+        let mut batch = Batch2D::new();
+        batch.push(&rect);
+
+        for drawable in [&rect as &dyn Drawable, &batch] {
+            window.clear(color::BLACK);
+            drawable.draw(window.pipeline_shader());
+            assert_eq!(window_pixel(215, 208), [255, 255, 255, 255]);
+            assert_eq!(window_pixel(185, 208), [0, 0, 0, 255]);
+            assert_eq!(window_pixel(185, 192), [255, 255, 255, 255]);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn scene_test() -> Result<(), Box<dyn std::error::Error>> {
+        let sdl = sdl2::init().unwrap();
+
+        let video_subsystem = sdl.video().unwrap();
+        let gl_attrib = video_subsystem.gl_attr();
+        gl_attrib.set_context_profile(sdl2::video::GLProfile::Core);
+        gl_attrib.set_context_version(4, 5);
+
+        let window = rusty_gl::Window::new()
+            .dimensions(400, 400)
+            .title("Testing Window")
+            .build(&video_subsystem)?;
+
+        rusty_gl::debug::enable();
+
+        // Squares centered on the origin of their node
+        let square = |color: Color| {
+            let corner = |x, y| Vertex::new(Vector3::new(x, y, 0.0), color, Vector2::default());
+            Rect::new(vec![
+                corner(-10.0, -10.0),
+                corner(10.0, -10.0),
+                corner(-10.0, 10.0),
+                corner(10.0, 10.0),
+            ])
+        };
+
+        let mut scene = Scene::new();
+        let body = scene.add(None, Placement::new(Vector2::new(200.0, 200.0)))?;
+        scene.set_drawable(body, square(color::WHITE))?;
+        let arm = scene.add(Some(body), Placement::new(Vector2::new(100.0, 0.0)))?;
+        scene.set_drawable(arm, square(color::RED))?;
+
+        assert_eq!(scene.len(), 2);
+        assert_eq!(scene.roots(), &[body]);
+        assert_eq!(scene.children(body), &[arm]);
+        assert_eq!(scene.parent(arm), Some(body));
+
+        window.clear(color::BLACK);
+        window.draw(&scene);
+        assert_eq!(window_pixel(200, 200), [255, 255, 255, 255]);
+        assert_eq!(window_pixel(300, 200), [255, 0, 0, 255]);
+
+        // Rotating the body swings the arm around it
+        scene.node_placement_mut(body).unwrap().angle = std::f32::consts::FRAC_PI_2;
+        assert_close(
+            scene
+                .world_transform(arm)
+                .unwrap()
+                .apply(Vector2::new(0.0, 0.0)),
+            Vector2::new(200.0, 100.0),
+        );
+
+        window.clear(color::BLACK);
+        window.draw(&scene);
+        assert_eq!(window_pixel(200, 100), [255, 0, 0, 255]);
+        assert_eq!(window_pixel(300, 200), [0, 0, 0, 255]);
+
+        // The placement of the drawable is applied inside its node
+        scene.drawable_mut(arm).unwrap().placement_mut().center = Vector2::new(0.0, 50.0);
+        window.clear(color::BLACK);
+        window.draw(&scene);
+        assert_eq!(window_pixel(250, 100), [255, 0, 0, 255]);
+        assert_eq!(window_pixel(200, 100), [0, 0, 0, 255]);
+        *scene.drawable_mut(arm).unwrap().center_mut() = Vector2::new(0.0, 0.0);
+
+        // Moving the scene moves every node
+        scene.translate(Vector2::new(-100.0, 0.0));
+        assert_close(
+            scene
+                .world_transform(arm)
+                .unwrap()
+                .apply(Vector2::new(0.0, 0.0)),
+            Vector2::new(100.0, 100.0),
+        );
+        scene.translate(Vector2::new(100.0, 0.0));
+
+        // Hidden nodes hide their children
+        scene.set_visible(body, false)?;
+        window.clear(color::BLACK);
+        window.draw(&scene);
+        assert_eq!(window_pixel(200, 200), [0, 0, 0, 255]);
+        assert_eq!(window_pixel(200, 100), [0, 0, 0, 255]);
+
+        scene.set_visible(body, true)?.set_visible(arm, false)?;
+        window.clear(color::BLACK);
+        window.draw(&scene);
+        assert_eq!(window_pixel(200, 200), [255, 255, 255, 255]);
+        assert_eq!(window_pixel(200, 100), [0, 0, 0, 255]);
+        scene.set_visible(arm, true)?;
+
+        // Children are drawn after their parent, siblings by order and then in the order they were added
+        let green = scene.add(Some(body), Placement::default())?;
+        scene.set_drawable(green, square(color::GREEN))?;
+        let blue = scene.add(Some(body), Placement::default())?;
+        scene.set_drawable(blue, square(color::BLUE))?;
+
+        window.clear(color::BLACK);
+        window.draw(&scene);
+        assert_eq!(window_pixel(200, 200), [0, 0, 255, 255]);
+
+        scene.set_order(blue, -1)?;
+        assert_eq!(scene.children(body), &[blue, arm, green]);
+        window.clear(color::BLACK);
+        window.draw(&scene);
+        assert_eq!(window_pixel(200, 200), [0, 255, 0, 255]);
+
+        // Reparenting keeps the local placement
+        scene.set_parent(green, Some(arm))?;
+        assert_close(
+            scene
+                .world_transform(green)
+                .unwrap()
+                .apply(Vector2::new(0.0, 0.0)),
+            Vector2::new(200.0, 100.0),
+        );
+        window.clear(color::BLACK);
+        window.draw(&scene);
+        assert_eq!(window_pixel(200, 200), [0, 0, 255, 255]);
+        assert_eq!(window_pixel(200, 100), [0, 255, 0, 255]);
+
+        // Cycles are rejected
+        assert!(scene.set_parent(body, Some(green)).is_err());
+        assert!(scene.set_parent(arm, Some(arm)).is_err());
+
+        // Removing a node removes its children, old handles stay invalid
+        scene.remove(arm)?;
+        assert_eq!(scene.len(), 2);
+        assert!(!scene.contains(arm));
+        assert!(!scene.contains(green));
+        assert!(scene.world_transform(green).is_none());
+
+        let reused = scene.add(None, Placement::default())?;
+        assert!(scene.contains(reused));
+        assert!(!scene.contains(arm) && !scene.contains(green));
+        assert!(scene.set_visible(arm, true).is_err());
+
+        window.clear(color::BLACK);
+        window.draw(&scene);
+        assert_eq!(window_pixel(200, 100), [0, 0, 0, 255]);
+
+        Ok(())
+    }
 }
@@ -0,0 +1,238 @@
+//! Use "cargo test -- --test-threads=1" to test this crate.
+//! Every primitive is checked against a cpu reference implementation.
+
+#[cfg(test)]
+mod tests {
+    use rusty_gl::{primitives::Primitives, SSBO};
+
+    /// Creates a hidden window, so a GL context is current
+    fn context() -> Result<(rusty_gl::Window, sdl2::Sdl), Box<dyn std::error::Error>> {
+        let sdl = sdl2::init()?;
+
+        let video_subsystem = sdl.video()?;
+        let gl_attrib = video_subsystem.gl_attr();
+        gl_attrib.set_context_profile(sdl2::video::GLProfile::Core);
+        gl_attrib.set_context_version(4, 5);
+
+        let window = rusty_gl::Window::new()
+            .dimensions(400, 400)
+            .title("Testing Window")
+            .build(&video_subsystem)?;
+
+        rusty_gl::debug::enable();
+
+        Ok((window, sdl))
+    }
+
+    /// Deterministic pseudo random numbers
+    fn random(count: usize, seed: u32) -> Vec<u32> {
+        let mut state = seed;
+        (0..count)
+            .map(|_| {
+                state = state.wrapping_mul(1664525).wrapping_add(1013904223);
+                state
+            })
+            .collect()
+    }
+
+    // More than 256 * 256 elements, so scans need three levels
+    const COUNT: usize = 100_000;
+
+    #[test]
+    fn reduce_test() -> Result<(), Box<dyn std::error::Error>> {
+        let _context = context()?;
+
+        let values: Vec<u32> = random(COUNT, 1).iter().map(|v| v % 1000).collect();
+        let ssbo = SSBO::create_from(0, values.clone(), gl::STATIC_DRAW);
+        let primitives = Primitives::<u32>::new()?;
+
+        assert_eq!(primitives.sum(&ssbo)?, values.iter().sum::<u32>());
+        assert_eq!(primitives.min(&ssbo)?, *values.iter().min().unwrap());
+        assert_eq!(primitives.max(&ssbo)?, *values.iter().max().unwrap());
+
+        let values: Vec<i32> = random(COUNT, 2).iter().map(|v| *v as i32).collect();
+        let ssbo = SSBO::create_from(0, values.clone(), gl::STATIC_DRAW);
+        let primitives = Primitives::<i32>::new()?;
+
+        assert_eq!(primitives.min(&ssbo)?, *values.iter().min().unwrap());
+        assert_eq!(primitives.max(&ssbo)?, *values.iter().max().unwrap());
+
+        let values: Vec<f32> = random(COUNT, 3)
+            .iter()
+            .map(|v| (*v % 1000) as f32 / 10.0 - 50.0)
+            .collect();
+        let ssbo = SSBO::create_from(0, values.clone(), gl::STATIC_DRAW);
+        let primitives = Primitives::<f32>::new()?;
+
+        let sum: f64 = values.iter().map(|v| *v as f64).sum();
+        assert!((primitives.sum(&ssbo)? as f64 - sum).abs() < 1.0);
+        assert_eq!(
+            primitives.min(&ssbo)?,
+            values.iter().cloned().fold(f32::INFINITY, f32::min)
+        );
+        assert_eq!(
+            primitives.max(&ssbo)?,
+            values.iter().cloned().fold(f32::NEG_INFINITY, f32::max)
+        );
+
+        let empty = SSBO::create_from(0, Vec::<f32>::new(), gl::STATIC_DRAW);
+        assert!(primitives.sum(&empty).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn scan_test() -> Result<(), Box<dyn std::error::Error>> {
+        let _context = context()?;
+
+        let values: Vec<u32> = random(COUNT, 4).iter().map(|v| v % 100).collect();
+        let input = SSBO::create_from(0, values.clone(), gl::STATIC_DRAW);
+        let mut output = SSBO::create_from(1, vec![0u32; COUNT], gl::STATIC_DRAW);
+        let primitives = Primitives::<u32>::new()?;
+
+        let inclusive: Vec<u32> = values
+            .iter()
+            .scan(0, |acc, v| {
+                *acc += v;
+                Some(*acc)
+            })
+            .collect();
+
+        primitives.inclusive_scan(&input, &output)?;
+        output.load();
+        assert_eq!(*output, inclusive);
+
+        let mut exclusive = vec![0];
+        exclusive.extend_from_slice(&inclusive[..COUNT - 1]);
+
+        primitives.exclusive_scan(&input, &output)?;
+        output.load();
+        assert_eq!(*output, exclusive);
+
+        let short = SSBO::create_from(1, vec![0u32; 10], gl::STATIC_DRAW);
+        assert!(primitives.inclusive_scan(&input, &short).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn sort_test() -> Result<(), Box<dyn std::error::Error>> {
+        let _context = context()?;
+
+        let values = random(COUNT, 5);
+        let mut keys = SSBO::create_from(0, values.clone(), gl::STATIC_DRAW);
+        Primitives::<u32>::new()?.sort(&keys)?;
+
+        let mut sorted = values;
+        sorted.sort();
+        keys.load();
+        assert_eq!(*keys, sorted);
+
+        let values: Vec<i32> = random(COUNT, 6).iter().map(|v| *v as i32).collect();
+        let mut keys = SSBO::create_from(0, values.clone(), gl::STATIC_DRAW);
+        Primitives::<i32>::new()?.sort(&keys)?;
+
+        let mut sorted = values;
+        sorted.sort();
+        keys.load();
+        assert_eq!(*keys, sorted);
+
+        let values: Vec<f32> = random(COUNT, 7)
+            .iter()
+            .map(|v| *v as i32 as f32 / 1000.0)
+            .collect();
+        let indices: Vec<u32> = (0..COUNT as u32).collect();
+        let mut keys = SSBO::create_from(0, values.clone(), gl::STATIC_DRAW);
+        let mut payload = SSBO::create_from(1, indices.clone(), gl::STATIC_DRAW);
+        Primitives::<f32>::new()?.sort_by_key(&keys, &payload)?;
+
+        // The sort is stable, so equal keys keep the order of their indices
+        let mut sorted: Vec<(f32, u32)> = values.iter().cloned().zip(indices).collect();
+        sorted.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+        keys.load();
+        payload.load();
+        assert_eq!(*keys, sorted.iter().map(|p| p.0).collect::<Vec<f32>>());
+        assert_eq!(*payload, sorted.iter().map(|p| p.1).collect::<Vec<u32>>());
+
+        Ok(())
+    }
+
+    #[test]
+    fn compact_test() -> Result<(), Box<dyn std::error::Error>> {
+        let _context = context()?;
+
+        let values: Vec<i32> = random(COUNT, 8).iter().map(|v| *v as i32).collect();
+        let flags: Vec<u32> = values.iter().map(|v| (*v > 0) as u32 * 7).collect();
+
+        let input = SSBO::create_from(0, values.clone(), gl::STATIC_DRAW);
+        let flag_buffer = SSBO::create_from(1, flags, gl::STATIC_DRAW);
+        let mut output = SSBO::create_from(2, vec![0i32; COUNT], gl::STATIC_DRAW);
+
+        let count = Primitives::<i32>::new()?.compact(&input, &flag_buffer, &output)?;
+
+        let kept: Vec<i32> = values.into_iter().filter(|v| *v > 0).collect();
+        output.load();
+        assert_eq!(count, kept.len());
+        assert_eq!(output[..count], kept[..]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn histogram_test() -> Result<(), Box<dyn std::error::Error>> {
+        let _context = context()?;
+
+        let values: Vec<u32> = random(COUNT, 9).iter().map(|v| v % 120).collect();
+        let input = SSBO::create_from(0, values.clone(), gl::STATIC_DRAW);
+        let primitives = Primitives::<u32>::new()?;
+
+        // Values above 99 are outside of the range
+        let histogram = primitives.histogram(&input, 10, 0, 99)?;
+
+        let mut reference = vec![0u32; 10];
+        for v in values.iter().filter(|v| **v <= 99) {
+            reference[((*v * 10) / 99).min(9) as usize] += 1;
+        }
+        assert_eq!(histogram, reference);
+
+        assert!(primitives.histogram(&input, 0, 0, 99).is_err());
+        assert!(primitives.histogram(&input, 10, 99, 0).is_err());
+
+        // The range is wider than i32::MAX
+        let values = vec![
+            -2_000_000_000i32,
+            -1_500_000_000,
+            -900_000_000,
+            500_000_000,
+            2_000_000_000,
+        ];
+        let input = SSBO::create_from(0, values, gl::STATIC_DRAW);
+        let primitives = Primitives::<i32>::new()?;
+        assert_eq!(
+            primitives.histogram(&input, 4, -2_000_000_000, 2_000_000_000)?,
+            vec![2, 1, 1, 1]
+        );
+
+        // Values right at the bin edges of a range wider than 2^24 need exact integer math
+        let (lower, upper, bins) = (100u32, 4_000_000_100u32, 7u32);
+        let range = (upper - lower) as u64;
+        let mut values: Vec<u32> = random(COUNT, 10);
+        for bin in 1..bins as u64 {
+            let edge = lower as u64 + (bin * range).div_ceil(bins as u64);
+            values.extend_from_slice(&[edge as u32 - 1, edge as u32, edge as u32 + 1]);
+        }
+        values.extend_from_slice(&[lower - 1, lower, upper, upper + 1]);
+
+        let input = SSBO::create_from(0, values.clone(), gl::STATIC_DRAW);
+        let primitives = Primitives::<u32>::new()?;
+
+        let mut reference = vec![0u32; bins as usize];
+        for v in values.iter().filter(|v| (lower..=upper).contains(*v)) {
+            let bin = (*v - lower) as u64 * bins as u64 / range;
+            reference[bin.min(bins as u64 - 1) as usize] += 1;
+        }
+        assert_eq!(primitives.histogram(&input, bins, lower, upper)?, reference);
+
+        Ok(())
+    }
+}